@@ -51,6 +51,8 @@ internal static partial class NativeMethods
         internal nint Scheme;   // UTF-8, must stay valid for duration of wry_window_create
         internal nint Callback; // ProtocolHandlerCallback function pointer
         internal nint Context;
+        internal int Secure;   // non-zero = serve as a secure context (Linux: registered per scheme; Windows: always)
+        internal int Standard; // non-zero = http-like origin / CORS (Linux: registered per scheme; Windows: always)
     }
 
     /// <summary>
@@ -112,6 +114,7 @@ internal static partial class NativeMethods
         internal int Theme;
         internal int HttpsScheme;
         internal int BrowserAcceleratorKeys;
+        internal int BlockDevToolsShortcut;
         internal int ScrollBarStyle;
         internal int SkipTaskbar;
         internal int ContentProtected;
@@ -124,6 +127,12 @@ internal static partial class NativeMethods
         internal nint WindowClassname;
         internal nuint OwnerWindowId;
         internal nuint ParentWindowId;
+        internal int ShowWhenReady;
+        internal int AppRegionDrag;
+        internal int AutoTitle;
+        internal int ZoomPersistence;
+        internal int Spellcheck;
+        internal int HtmlFullscreenWindow;
 
         // Event callbacks: function pointer + opaque context. 0 = not set.
         internal nint IpcHandler;
@@ -142,6 +151,44 @@ internal static partial class NativeMethods
         internal nint PageLoadHandlerCtx;
         internal nint DragDropHandler;
         internal nint DragDropHandlerCtx;
+        internal nint TiledHandler;
+        internal nint TiledHandlerCtx;
+        internal nint FirstPaintHandler;
+        internal nint FirstPaintHandlerCtx;
+        internal nint HitTestHandler;
+        internal nint HitTestHandlerCtx;
+        internal nint TitleChangedHandler;
+        internal nint TitleChangedHandlerCtx;
+        internal nint FaviconChangedHandler;
+        internal nint FaviconChangedHandlerCtx;
+        internal nint MouseWheelHandler;
+        internal nint MouseWheelHandlerCtx;
+        internal nint CursorMovedHandler;
+        internal nint CursorMovedHandlerCtx;
+        internal nint MouseButtonHandler;
+        internal nint MouseButtonHandlerCtx;
+        internal nint TouchHandler;
+        internal nint TouchHandlerCtx;
+        internal nint WebviewCrashHandler;
+        internal nint WebviewCrashHandlerCtx;
+        internal nint ScriptDialogHandler;
+        internal nint ScriptDialogHandlerCtx;
+        internal nint ConsoleMessageHandler;
+        internal nint ConsoleMessageHandlerCtx;
+        internal nint RedrawHandler;
+        internal nint RedrawHandlerCtx;
+        internal nint HtmlFullscreenHandler;
+        internal nint HtmlFullscreenHandlerCtx;
+        internal nint InitialLoadHandler;
+        internal nint InitialLoadHandlerCtx;
+        internal nint MonitorChangedHandler;
+        internal nint MonitorChangedHandlerCtx;
+        internal nint BackgroundThrottleHandler;
+        internal nint BackgroundThrottleHandlerCtx;
+        internal nint OcclusionHandler;
+        internal nint OcclusionHandlerCtx;
+        internal nint ResponsivenessHandler;
+        internal nint ResponsivenessHandlerCtx;
     }
 
     /// <summary>
@@ -360,6 +407,12 @@ internal static partial class NativeMethods
     [LibraryImport(LibName)]
     internal static partial nint wry_webview_version();
 
+    [LibraryImport(LibName)]
+    internal static partial nint wry_native_version();
+
+    [LibraryImport(LibName)]
+    internal static partial int wry_native_abi_version();
+
     // -----------------------------------------------------------------------
     // Windows native window handles (HWND, HINSTANCE)
     // -----------------------------------------------------------------------
@@ -390,6 +443,17 @@ internal static partial class NativeMethods
     [LibraryImport(LibName)]
     internal static partial void wry_window_dispatch(nint app, nuint windowId, nint callback, nint ctx);
 
+    [LibraryImport(LibName)]
+    [return: MarshalAs(UnmanagedType.U1)]
+    internal static partial bool wry_window_is_loading(nint app, nuint windowId);
+
+    [LibraryImport(LibName)]
+    [return: MarshalAs(UnmanagedType.U1)]
+    internal static partial bool wry_window_wait_for_load(nint app, nuint windowId, int timeoutMs);
+
+    [LibraryImport(LibName, StringMarshalling = StringMarshalling.Utf8)]
+    internal static partial int wry_window_navigate_and_wait(nint app, nuint windowId, string url, int timeoutMs);
+
     // -----------------------------------------------------------------------
     // Tray menu building
     // -----------------------------------------------------------------------
@@ -9,6 +9,12 @@ namespace Wry.NET;
 /// </summary>
 public sealed class WryApp : IDisposable
 {
+    /// <summary>
+    /// Native ABI revision this binding was built against. Must match <c>WRY_NATIVE_ABI_VERSION</c>
+    /// in wry-native; bump both together whenever exported signatures or struct layouts change.
+    /// </summary>
+    internal const int NativeAbiVersion = 2;
+
     internal nint Handle { get; private set; }
     private bool _disposed;
     private readonly List<WryWindow> _windows = [];
@@ -42,9 +48,11 @@ public sealed class WryApp : IDisposable
     /// <summary>
     /// Initializes a new instance of the <see cref="WryApp"/> class.
     /// </summary>
-    /// <exception cref="InvalidOperationException">Thrown if the native handle creation fails.</exception>
+    /// <exception cref="InvalidOperationException">Thrown if the native library does not match this binding
+    /// or the native handle creation fails.</exception>
     public WryApp()
     {
+        EnsureNativeAbi();
         Handle = NativeMethods.wry_app_new();
         if (Handle == 0)
             throw new InvalidOperationException("Failed to create WryApp native handle.");
@@ -216,6 +224,7 @@ public sealed class WryApp : IDisposable
                     Theme = options.Theme,
                     HttpsScheme = options.HttpsScheme ? 1 : 0,
                     BrowserAcceleratorKeys = options.BrowserAcceleratorKeys ? 1 : 0,
+                    BlockDevToolsShortcut = options.DevToolsShortcut ? 0 : 1,
                     ScrollBarStyle = options.ScrollBarStyle,
                     SkipTaskbar = options.SkipTaskbar ? 1 : 0,
                     ContentProtected = options.ContentProtected ? 1 : 0,
@@ -228,6 +237,11 @@ public sealed class WryApp : IDisposable
                     WindowClassname = windowClassnamePtr,
                     OwnerWindowId = owner?.Id ?? 0u,
                     ParentWindowId = 0,
+                    ShowWhenReady = options.ShowWhenReady ? 1 : 0,
+                    AppRegionDrag = options.AppRegionDrag ? 1 : 0,
+                    AutoTitle = options.AutoTitle ? 1 : 0,
+                    ZoomPersistence = options.ZoomPersistence ? 1 : 0,
+                    Spellcheck = options.Spellcheck,
                 };
                 WryWindow.PopulateCallbacks(ref config, window.GCHandlePtr);
                 id = NativeMethods.wry_window_create(Handle, 0, 0, (nint)(&config));
@@ -424,6 +438,35 @@ public sealed class WryApp : IDisposable
         NativeMethods.wry_app_exit(Handle, exitCode);
     }
 
+    /// <summary>
+    /// Get the version of the loaded wry_native library (its crate version).
+    /// </summary>
+    public static string? GetNativeVersion()
+    {
+        return NativeMethods.ReadAndFreeNativeString(NativeMethods.wry_native_version());
+    }
+
+    /// <summary>
+    /// Fail fast with a clear message when the wry_native library on disk was built for a different
+    /// binding version, instead of crashing later on a mismatched signature or struct layout.
+    /// </summary>
+    private static void EnsureNativeAbi()
+    {
+        int actual;
+        try
+        {
+            actual = NativeMethods.wry_native_abi_version();
+        }
+        catch (EntryPointNotFoundException)
+        {
+            throw new InvalidOperationException(
+                $"The wry_native library is older than this Wry.NET build (expected native ABI {NativeAbiVersion}). Update the native library.");
+        }
+        if (actual != NativeAbiVersion)
+            throw new InvalidOperationException(
+                $"The wry_native library (version {GetNativeVersion()}, ABI {actual}) does not match this Wry.NET build (ABI {NativeAbiVersion}). Use matching package versions.");
+    }
+
     /// <summary>
     /// Get the WebView engine version string (e.g. Chromium/WebKit version).
     /// </summary>
@@ -15,7 +15,7 @@ public sealed class WryTrayIconCreateOptions
     /// <summary>Tray icon title. macOS only - displayed next to the icon.</summary>
     public string? Title { get; set; }
 
-    /// <summary>Encoded image file bytes (PNG, ICO, JPEG, BMP, GIF) for the tray icon.</summary>
+    /// <summary>Encoded image file bytes (PNG, ICO, JPEG, BMP, GIF, WebP, SVG) for the tray icon.</summary>
     public byte[]? IconData { get; set; }
 
     /// <summary>Context menu. The menu is consumed at creation time - do not reuse.</summary>
@@ -310,7 +310,7 @@ public sealed class WryTrayIcon
     }
 
     /// <summary>
-    /// Set the tray icon from encoded image bytes (PNG, ICO, JPEG, BMP, GIF).
+    /// Set the tray icon from encoded image bytes (PNG, ICO, JPEG, BMP, GIF, WebP, SVG).
     /// The native side decodes the image automatically.
     /// </summary>
     /// <param name="data">Encoded image file bytes.</param>
@@ -354,7 +354,7 @@ public sealed class WryWindow
     }
 
     /// <summary>
-    /// Set the window icon from encoded image file bytes (PNG, ICO, JPEG, BMP, GIF).
+    /// Set the window icon from encoded image file bytes (PNG, ICO, JPEG, BMP, GIF, WebP, SVG).
     /// Pass null or empty to clear. Windows and Linux only; macOS uses the .app bundle icon.
     /// </summary>
     public unsafe void SetIcon(byte[]? imageData)
@@ -374,7 +374,7 @@ public sealed class WryWindow
     }
 
     /// <summary>
-    /// Set the window icon from an image file on disk (PNG, ICO, JPEG, BMP, GIF).
+    /// Set the window icon from an image file on disk (PNG, ICO, JPEG, BMP, GIF, WebP, SVG).
     /// Windows and Linux only; macOS uses the .app bundle icon.
     /// </summary>
     public void SetIconFromFile(string filePath)
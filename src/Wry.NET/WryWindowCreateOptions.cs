@@ -33,7 +33,7 @@ public sealed class WryWindowCreateOptions
     /// <summary>Enable default context menus (e.g. right-click). Default true. Windows only.</summary>
     public bool DefaultContextMenus { get; set; } = true;
 
-    /// <summary>Path to window icon image file (PNG, ICO, JPEG, BMP, GIF). Windows and Linux only; macOS uses .app bundle icon.</summary>
+    /// <summary>Path to window icon image file (PNG, ICO, JPEG, BMP, GIF, WebP, SVG). Windows and Linux only; macOS uses .app bundle icon.</summary>
     public string? IconPath { get; set; }
 
     /// <summary>JavaScript init scripts injected before page load. Add scripts here instead of calling AddInitScript after creation.</summary>
@@ -120,6 +120,9 @@ public sealed class WryWindowCreateOptions
     /// <summary>Enable browser accelerator keys (F5, Ctrl+R, etc.). Default true. Windows only.</summary>
     public bool BrowserAcceleratorKeys { get; set; } = true;
 
+    /// <summary>Allow the devtools shortcuts (F12, Ctrl+Shift+I/J/C) when accelerator keys are enabled. Default true. Windows only.</summary>
+    public bool DevToolsShortcut { get; set; } = true;
+
     /// <summary>Scrollbar style. 0 = default, 1 = fluent overlay, 2 = none. Windows only.</summary>
     public int ScrollBarStyle { get; set; }
 
@@ -150,6 +153,28 @@ public sealed class WryWindowCreateOptions
     /// <summary>Custom window class name. Null = default. Windows only.</summary>
     public string? WindowClassname { get; set; }
 
+    /// <summary>
+    /// Create the window hidden and show it once the first page load finishes, avoiding the white flash.
+    /// A failed load shows it too, and it is shown anyway after 3 seconds if the load hangs or the renderer crashes.
+    /// Ignored if <see cref="Visible"/> is false. Default false.
+    /// </summary>
+    public bool ShowWhenReady { get; set; }
+
+    /// <summary>
+    /// Let elements styled <c>-webkit-app-region: drag</c> move the window (custom title bars).
+    /// Windows uses WebView2's built-in support; other platforms use an injected shim. Default false.
+    /// </summary>
+    public bool AppRegionDrag { get; set; }
+
+    /// <summary>Keep the window title in sync with the page's <c>document.title</c>. Default false.</summary>
+    public bool AutoTitle { get; set; }
+
+    /// <summary>Remember the zoom level per origin (in memory, shared by all windows) and re-apply it on navigation. Default false.</summary>
+    public bool ZoomPersistence { get; set; }
+
+    /// <summary>Spellcheck for editable content: 0 = platform default, 1 = on, 2 = off. Default 0.</summary>
+    public int Spellcheck { get; set; }
+
     /// <summary>
     /// Hooks invoked with the live window when it is materialized, before the user's onCreated callback.
     /// Extensions use this to auto-attach behavior at creation time.
@@ -9,10 +9,11 @@
 #![allow(clippy::missing_safety_doc)]
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::{c_char, c_int, c_void, CStr, CString};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 
 /// Log a wry Result error to stderr if it failed. Used instead of `let _ =`
 /// so that errors are visible in debug output.
@@ -37,8 +38,13 @@ use wry::{webview_version, WebContext, WebView, WebViewBuilder};
 use tao::platform::windows::WindowBuilderExtWindows;
 #[cfg(target_os = "windows")]
 use wry::WebViewBuilderExtWindows;
+#[cfg(target_os = "windows")]
+use windows::Win32::Graphics::Dwm::DWMWINDOWATTRIBUTE;
 
 mod dialog;
+mod monitors;
+mod power;
+mod shell;
 mod tray;
 use tray::{WryTray, TrayDispatchCallback};
 
@@ -82,6 +88,9 @@ type FocusCallback = extern "C" fn(bool, *mut c_void);
 /// Dispatch callback: fn(window: *mut WryWindow, ctx: *mut c_void)
 type DispatchCallback = extern "C" fn(*mut WryWindow, *mut c_void);
 
+/// App dispatch callback: fn(app: *mut WryApp, ctx: *mut c_void)
+type AppDispatchCallback = extern "C" fn(*mut WryApp, *mut c_void);
+
 /// Exit requested callback: fn(has_code: bool, code: c_int, ctx: *mut c_void) -> bool
 /// Called when all windows are closed or when wry_app_exit is called.
 /// - `has_code` false: user-initiated (last window closed)
@@ -101,10 +110,22 @@ type WindowCreationErrorCallback = extern "C" fn(*mut c_void, usize, *const c_ch
 /// Called when a window has been destroyed (platform Destroyed event - e.g. user closed or OS destroyed with owner).
 type WindowDestroyedCallback = extern "C" fn(*mut c_void, usize);
 
+/// App lifecycle callback: fn(ctx: *mut c_void)
+/// Used for tao's Suspended / Resumed events (wry_app_on_suspend / wry_app_on_resume) and for
+/// monitor configuration changes (wry_app_on_monitors_changed).
+type LifecycleCallback = extern "C" fn(*mut c_void);
+
+/// Power event callback: fn(kind: c_int, ctx: *mut c_void)
+/// `kind`: 0 = Suspend (about to sleep), 1 = Resume, 2 = LowPower (battery saver turned on;
+/// Windows, macOS), 3 = ScreenLock, 4 = ScreenUnlock. See power.rs for the platform sources.
+type PowerEventCallback = extern "C" fn(c_int, *mut c_void);
+
 /// Monitor enumeration callback:
 ///   fn(x: c_int, y: c_int, width: c_int, height: c_int, scale: f64, ctx: *mut c_void)
 /// Called once per monitor. Position is the top-left corner in physical pixels.
 /// Size is in physical pixels. Scale is the DPI scale factor.
+/// Also `WryWindowConfig.monitor_changed_handler`: called with the new monitor after the window
+/// moves onto a different one, even when the scale stays the same.
 type MonitorCallback = extern "C" fn(c_int, c_int, c_int, c_int, f64, *mut c_void);
 
 /// Navigation handler callback: fn(url: *const c_char, ctx: *mut c_void) -> bool
@@ -115,6 +136,17 @@ type NavigationCallback = extern "C" fn(*const c_char, *mut c_void) -> bool;
 /// event: 0 = Started, 1 = Finished
 type PageLoadCallback = extern "C" fn(c_int, *const c_char, *mut c_void);
 
+/// First paint (ready) callback: fn(ctx: *mut c_void)
+/// Fired once per window, after the first page load finishes (the first content is ready to show).
+type FirstPaintCallback = extern "C" fn(*mut c_void);
+
+/// Initial load callback: fn(url: *const c_char, ctx: *mut c_void)
+/// Fired once, after the webview's first page load finishes, with that page's URL (valid only
+/// during the call); later navigations do not fire it. Use it for one-time setup instead of
+/// tracking "initialized" in the page-load callback. Unlike FirstPaintCallback it fires again for
+/// the first load of a webview rebuilt by wry_window_reset_webview.
+type InitialLoadCallback = extern "C" fn(*const c_char, *mut c_void);
+
 /// Evaluate-script result callback: fn(result: *const c_char, ctx: *mut c_void)
 /// result is the JSON-encoded return value from the evaluated script.
 type EvalResultCallback = extern "C" fn(*const c_char, *mut c_void);
@@ -132,6 +164,137 @@ type EvalResultCallback = extern "C" fn(*const c_char, *mut c_void);
 type DragDropCallback =
     extern "C" fn(c_int, *const *const c_char, c_int, c_int, c_int, *mut c_void) -> bool;
 
+/// Tray enumeration callback: fn(tray_ids: *const usize, count: c_int, ctx: *mut c_void)
+/// `tray_ids` points to `count` live tray IDs (null if count is 0); valid only during the call.
+type TrayIdsCallback = extern "C" fn(*const usize, c_int, *mut c_void);
+
+/// Interval timer callback: fn(timer_id: usize, ctx: *mut c_void)
+/// Runs on the event loop thread each time the interval elapses.
+type TimerCallback = extern "C" fn(usize, *mut c_void);
+
+/// Window tiled (snapped) callback: fn(edges: c_int, ctx: *mut c_void)
+/// `edges` is a bitmask of the work-area edges the window is snapped to:
+/// 1 = Left, 2 = Top, 4 = Right, 8 = Bottom; 0 = not tiled. Fired only when the value changes.
+type TiledCallback = extern "C" fn(c_int, *mut c_void);
+
+/// Document title changed callback: fn(title: *const c_char, ctx: *mut c_void)
+/// Fired when the page's `document.title` changes. `title` is UTF-8, valid only during the call.
+type TitleChangedCallback = extern "C" fn(*const c_char, *mut c_void);
+
+/// Favicon changed callback: fn(png: *const u8, len: c_int, ctx: *mut c_void)
+/// `png` is the page's favicon re-encoded as PNG (null / 0 if the page has none or it could not
+/// be loaded); valid only during the call.
+type FaviconChangedCallback = extern "C" fn(*const u8, c_int, *mut c_void);
+
+/// Mouse wheel callback:
+///   fn(delta_x: f64, delta_y: f64, delta_mode: c_int, modifiers: c_int, ctx: *mut c_void) -> bool
+/// `delta_mode`: 0 = lines, 1 = logical pixels. `modifiers`: 1 = Shift, 2 = Control, 4 = Alt, 8 = Super.
+/// Return true if handled: the webview then does not scroll or zoom for it (e.g. custom zoom).
+/// - macOS: every wheel event of the window, taken before it is dispatched (NSEvent local monitor).
+/// - Linux: wheel input over the webview before WebKitGTK handles it, and over the rest of the window.
+/// - Windows: only where the webview does not cover the window; WebView2 handles wheel input over
+///   its area in its own process, out of reach of the host, so it is not reported and cannot be
+///   consumed there.
+type MouseWheelCallback = extern "C" fn(f64, f64, c_int, c_int, *mut c_void) -> bool;
+
+/// Cursor moved callback: fn(x: f64, y: f64, ctx: *mut c_void)
+/// Position in logical pixels relative to the client area. Window-level, like MouseWheelCallback.
+type CursorMovedCallback = extern "C" fn(f64, f64, *mut c_void);
+
+/// Mouse button callback:
+///   fn(button: c_int, pressed: bool, x: f64, y: f64, modifiers: c_int, ctx: *mut c_void)
+/// `button`: 0 = Left, 1 = Right, 2 = Middle, 3 + n = other button n. Position is the last
+/// cursor position in logical pixels. `modifiers` as in MouseWheelCallback. Window-level.
+type MouseButtonCallback = extern "C" fn(c_int, bool, f64, f64, c_int, *mut c_void);
+
+/// Touch callback: fn(phase: c_int, id: u64, x: f64, y: f64, ctx: *mut c_void)
+/// `phase`: 0 = Started, 1 = Moved, 2 = Ended, 3 = Cancelled. `id` identifies the finger for the
+/// duration of the touch. Position in logical pixels relative to the client area. Window-level;
+/// tao reports Touch on Windows and Linux (not macOS, where trackpads are not touch screens).
+type TouchCallback = extern "C" fn(c_int, u64, f64, f64, *mut c_void);
+
+/// Webview crash callback: fn(reason: c_int, ctx: *mut c_void)
+/// Fired when a webview process dies and the page goes blank. `reason`: 0 = Other, 1 = Crashed
+/// (render / web content process), 2 = Exceeded memory limit (Linux), 3 = Browser process exited
+/// (Windows; the webview is unusable, rebuild it with wry_window_reset_webview), 4 = Unresponsive
+/// (Windows), 5 = Terminated by API (Linux). Reload the page for 1, 2, 4 and 5.
+type WebviewCrashCallback = extern "C" fn(c_int, *mut c_void);
+
+/// Webview responsiveness callback: fn(responsive: bool, ctx: *mut c_void)
+/// `false` when the web content process hangs (the page stops reacting to input, e.g. a runaway
+/// script), `true` when it recovers. The host can offer to wait or to reload (wry_window_reload); a
+/// process that dies instead is reported to WebviewCrashCallback, including running out of memory.
+/// - Windows: WebView2 ProcessFailed with `RENDER_PROCESS_UNRESPONSIVE` (also reported to the crash
+///   handler as reason 4); recovery is detected by a script queued behind the hang.
+/// - Linux: WebKitGTK's `is-web-process-responsive` property (WebKitGTK 2.34 or later).
+/// - macOS: never fired (WKWebView has no public signal).
+type ResponsivenessCallback = extern "C" fn(bool, *mut c_void);
+
+/// Window hit-test callback: fn(x: c_int, y: c_int, ctx: *mut c_void) -> c_int
+/// `x`, `y` is the cursor position in logical pixels relative to the client area.
+/// Return one of: 0 = Client, 1 = Caption (drag), 2 = ResizeLeft, 3 = ResizeTop, 4 = ResizeRight,
+/// 5 = ResizeBottom, 6 = ResizeTopLeft, 7 = ResizeTopRight, 8 = ResizeBottomLeft, 9 = ResizeBottomRight.
+type HitTestCallback = extern "C" fn(c_int, c_int, *mut c_void) -> c_int;
+
+/// Page dialog callback: fn(kind: c_int, message: *const c_char, default_text: *const c_char,
+///                         responder: *mut c_void, ctx: *mut c_void)
+/// Replaces the native `alert()` / `confirm()` / `prompt()` dialogs. `kind`: 0 = Alert, 1 = Confirm,
+/// 2 = Prompt, 3 = BeforeUnload. `default_text` is the prompt's default (empty otherwise).
+/// The handler must call `wry_script_dialog_respond` with the responder exactly once, now or later
+/// from the UI thread; the page's script is blocked until it does.
+/// Windows (WebView2 ScriptDialogOpening) and Linux (WebKitGTK script-dialog) only. Deliberately not
+/// on macOS: the dialogs are WKUIDelegate methods and wry installs its own delegate (file upload,
+/// media capture, new windows); replacing it would lose those. WKWebView's default applies there:
+/// `alert()` returns at once, `confirm()` returns false and `prompt()` returns null.
+type ScriptDialogCallback = extern "C" fn(c_int, *const c_char, *const c_char, *mut c_void, *mut c_void);
+
+/// Console message callback: fn(level: c_int, message: *const c_char, source: *const c_char,
+///                             line: c_int, ctx: *mut c_void)
+/// `level`: 0 = Log, 1 = Debug, 2 = Info, 3 = Warning, 4 = Error (also uncaught errors and
+/// unhandled rejections). `source` is the calling script's URL (may be empty), `line` is 0 if unknown.
+type ConsoleMessageCallback = extern "C" fn(c_int, *const c_char, *const c_char, c_int, *mut c_void);
+
+/// Redraw requested callback: fn(ctx: *mut c_void)
+/// Fired on tao's RedrawRequested (after `wry_window_request_redraw`, or when the OS asks the window
+/// to repaint) so native content drawn next to the webview can be refreshed.
+type RedrawCallback = extern "C" fn(*mut c_void);
+
+/// Thumbnail toolbar button callback: fn(button_id: c_int, ctx: *mut c_void)
+/// Called on the event loop thread when a taskbar thumbnail button is clicked (Windows only).
+type ThumbnailButtonCallback = extern "C" fn(c_int, *mut c_void);
+
+/// Print-to-PDF result callback: fn(pdf: *const u8, len: c_int, ctx: *mut c_void)
+/// Called once on the event loop thread. `pdf` is the document (null / 0 on failure); valid only
+/// during the call.
+type PdfCallback = extern "C" fn(*const u8, c_int, *mut c_void);
+
+/// HTML fullscreen callback: fn(entered: bool, ctx: *mut c_void)
+/// Fired when page content enters (`element.requestFullscreen()`, e.g. a video player) or leaves the
+/// HTML fullscreen API. Leave it unset and set `WryWindowConfig.html_fullscreen_window` to only
+/// follow with the window.
+type HtmlFullscreenCallback = extern "C" fn(bool, *mut c_void);
+
+/// Frame list callback: fn(json: *const c_char, ctx: *mut c_void)
+/// `json` is an array of `{"id", "name", "url", "sameOrigin"}` objects (see wry_window_get_frames);
+/// valid only during the call.
+type FramesCallback = extern "C" fn(*const c_char, *mut c_void);
+
+/// Background throttle callback: fn(throttled: bool, ctx: *mut c_void)
+/// Fired when the window loses focus and its frame rate is capped, and when focus returns
+/// (see wry_window_set_background_fps). Not fired without a limit.
+type BackgroundThrottleCallback = extern "C" fn(bool, *mut c_void);
+
+/// Occlusion changed callback: fn(visible: bool, ctx: *mut c_void)
+/// `visible` is false while the window is entirely covered by other windows, minimized, hidden or
+/// on another Space, or the screen is locked, and true once any part of it can be seen again, so
+/// the host can pause work nobody sees. Only changes are reported. macOS only
+/// (`NSWindow.occlusionState`, NSWindowDidChangeOcclusionStateNotification); never fired elsewhere.
+type OcclusionCallback = extern "C" fn(bool, *mut c_void);
+
+/// Memory usage callback: fn(bytes: i64, ctx: *mut c_void)
+/// `bytes` is the summed working set of the webview's engine processes, or -1 if unavailable.
+type MemoryUsageCallback = extern "C" fn(i64, *mut c_void);
+
 // ---------------------------------------------------------------------------
 // UserEvent -- messages sent to the event loop from any thread
 // ---------------------------------------------------------------------------
@@ -142,6 +305,60 @@ pub(crate) enum UserEvent {
         window_id: usize,
         callback: DispatchCallback,
         ctx: usize, // *mut c_void stored as usize for Send
+        /// Signalled once the callback has run (wry_window_dispatch_sync); dropped if it never does.
+        done: Option<std::sync::mpsc::Sender<()>>,
+    },
+    /// A window's first page load finished (posted by the page load handler).
+    FirstPaint {
+        window_id: usize,
+    },
+    /// A page finished loading; re-apply per-page settings (persisted zoom, spellcheck) and
+    /// report the webview's initial load.
+    PageLoaded {
+        window_id: usize,
+        url: String,
+    },
+    /// The page's document title changed (posted by the webview's title handler).
+    DocumentTitleChanged {
+        window_id: usize,
+        title: String,
+    },
+    /// The page's favicon changed; `png` is None if there is none.
+    FaviconChanged {
+        window_id: usize,
+        png: Option<Vec<u8>>,
+    },
+    /// The page entered or left HTML fullscreen (posted by watch_html_fullscreen / the shim).
+    HtmlFullscreen {
+        window_id: usize,
+        entered: bool,
+    },
+    /// Rebuild a window's webview (posted by wry_window_reset_webview).
+    ResetWebview {
+        window_id: usize,
+    },
+    /// Left mouse button pressed on an `app-region: drag` element (non-Windows shim).
+    AppRegionDrag {
+        window_id: usize,
+        clicks: c_int,
+    },
+    /// Left mouse button pressed in the webview of a window with a hit-test handler.
+    HitTest {
+        window_id: usize,
+        x: c_int,
+        y: c_int,
+        clicks: c_int,
+    },
+    /// Load a URL and report whether it loaded (wry_window_navigate_and_wait).
+    NavigateAndWait {
+        window_id: usize,
+        url: String,
+        reply: std::sync::mpsc::Sender<bool>,
+    },
+    /// Execute a C callback on the event loop thread, not tied to a window or tray.
+    AppDispatch {
+        callback: AppDispatchCallback,
+        ctx: usize,
     },
     /// Forward a tray icon event from the global handler.
     TrayEvent(tray_icon::TrayIconEvent),
@@ -157,6 +374,34 @@ pub(crate) enum UserEvent {
     TrayRemove {
         tray_id: usize,
     },
+    /// Start a repeating timer on the event loop (wry_app_set_interval).
+    SetInterval {
+        timer_id: usize,
+        interval: Duration,
+        callback: TimerCallback,
+        ctx: usize,
+    },
+    /// Stop a repeating timer (wry_app_clear_interval).
+    ClearInterval {
+        timer_id: usize,
+    },
+    /// No-op; wakes the loop so a new base control flow applies (wry_app_set_control_flow).
+    Wake,
+    /// A system power / session change (posted by the power.rs listeners).
+    PowerEvent {
+        kind: c_int,
+    },
+    /// A monitor was connected, disconnected or rearranged (posted by the monitors.rs listeners).
+    MonitorsChanged,
+    /// Set the badge count on every live window (wry_app_set_badge_count).
+    SetBadgeCount {
+        count: i64,
+    },
+    /// Report the IDs of all live tray icons.
+    GetTrayIds {
+        callback: TrayIdsCallback,
+        ctx: usize,
+    },
     /// Programmatic exit request via wry_app_exit.
     RequestExit {
         code: c_int,
@@ -182,6 +427,82 @@ pub struct WryProtocolEntry {
     pub scheme: *const c_char,
     pub callback: ProtocolHandlerCallback,
     pub ctx: *mut c_void,
+    /// Non-zero = serve the scheme as a secure context (service workers, crypto.subtle).
+    /// - Windows: always the case; wry serves custom schemes from `http(s)://<scheme>.localhost`,
+    ///   which Chromium treats as secure. Recorded only, and independent of `https_scheme`.
+    /// - Linux: registered as secure with the webview's WebKitGTK context (shared by the windows
+    ///   using that context).
+    /// - macOS: WKWebView has no public per-scheme registration; recorded only.
+    pub secure: c_int,
+    /// Non-zero = treat the scheme like http for origins and CORS (fetch / XHR from other origins).
+    /// - Windows: always the case (served from a `*.localhost` origin); recorded only.
+    /// - Linux: registered as CORS-enabled with the webview's WebKitGTK context.
+    /// - macOS: recorded only.
+    pub standard: c_int,
+}
+
+/// Print job settings for wry_window_print_ex. Pointers only need to stay valid for the call.
+#[repr(C)]
+pub struct WryPrintOptions {
+    /// Non-zero = print without a dialog.
+    pub silent: c_int,
+    /// UTF-8 printer name; null or empty = default printer.
+    pub printer: *const c_char,
+    /// Number of copies; 0 = 1.
+    pub copies: c_int,
+    /// UTF-8 page ranges such as "1-3,5" (1-based, inclusive); null or empty = all pages.
+    pub page_ranges: *const c_char,
+    /// 0 = default, 1 = portrait, 2 = landscape.
+    pub orientation: c_int,
+}
+
+/// Page setup for wry_window_print_to_pdf. Lengths are in inches.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct WryPdfOptions {
+    /// Paper size; 0 (either) = platform default (Letter on Windows, the locale's paper on Linux).
+    pub page_width: f64,
+    pub page_height: f64,
+    /// Negative = platform default.
+    pub margin_top: f64,
+    pub margin_right: f64,
+    pub margin_bottom: f64,
+    pub margin_left: f64,
+    /// Non-zero = landscape.
+    pub landscape: c_int,
+    /// Non-zero = print background colors and images; 0 = platform default (Windows: off).
+    pub print_backgrounds: c_int,
+}
+
+impl Default for WryPdfOptions {
+    fn default() -> Self {
+        Self {
+            page_width: 0.0,
+            page_height: 0.0,
+            margin_top: -1.0,
+            margin_right: -1.0,
+            margin_bottom: -1.0,
+            margin_left: -1.0,
+            landscape: 0,
+            print_backgrounds: 0,
+        }
+    }
+}
+
+/// One taskbar thumbnail toolbar button for wry_window_set_thumbnail_toolbar.
+/// Pointers only need to stay valid for the duration of the call.
+#[repr(C)]
+pub struct WryThumbnailButton {
+    /// Reported to the thumbnail button callback when clicked.
+    pub id: c_int,
+    /// Icon as `width` x `height` RGBA pixels; null = no icon.
+    pub rgba: *const u8,
+    pub width: c_int,
+    pub height: c_int,
+    /// UTF-8 tooltip; null = none. Truncated to 259 UTF-16 units.
+    pub tooltip: *const c_char,
+    /// Non-zero = clickable; zero = shown greyed out.
+    pub enabled: c_int,
 }
 
 /// C ABI config for window creation. Pass to wry_window_create; null = use defaults.
@@ -198,7 +519,7 @@ pub struct WryWindowConfig {
     pub protocols: *const WryProtocolEntry,
     /// 0 = false, non-zero = true. Windows only; ignored on other platforms.
     pub default_context_menus: c_int,
-    /// Window icon: pointer to image file bytes (PNG, ICO, JPEG, BMP, GIF). null or len 0 = no icon.
+    /// Window icon: pointer to image file bytes (PNG, ICO, JPEG, BMP, GIF, WebP, SVG). null or len 0 = no icon.
     pub icon_data: *const u8,
     pub icon_data_len: c_int,
     /// Init scripts: array of UTF-8 C strings injected before page load. null or count 0 = none.
@@ -248,6 +569,9 @@ pub struct WryWindowConfig {
     pub https_scheme: c_int,
     /// Windows only.
     pub browser_accelerator_keys: c_int,
+    /// Windows only. Non-zero = block the devtools shortcuts (F12, Ctrl+Shift+I/J/C) while keeping
+    /// the other accelerator keys. WebKit (macOS / Linux) has no built-in devtools shortcut.
+    pub block_devtools_shortcut: c_int,
     /// Windows only. 0 = default, 1 = fluent overlay, 2 = none.
     pub scroll_bar_style: c_int,
     pub skip_taskbar: c_int,
@@ -264,6 +588,27 @@ pub struct WryWindowConfig {
     pub owner_window_id: usize,
     /// 0 = no parent.
     pub parent_window_id: usize,
+    /// Non-zero = create hidden and show automatically once the first page load finishes
+    /// (avoids the white flash). A failed load shows it too, and it is shown anyway after
+    /// 3 seconds if the load hangs or the renderer crashes. Ignored if `visible` is 0.
+    pub show_when_ready: c_int,
+    /// Non-zero = elements styled `-webkit-app-region: drag` (or `app-region: drag`) move the window.
+    /// Windows: WebView2 non-client region support. macOS / Linux: injected mousedown shim; WebKit
+    /// does not implement the property, so the shim reads it from inline styles and `<style>`
+    /// elements only (not `<link>` stylesheets; the last matching rule wins regardless of
+    /// specificity). There a `data-wry-drag-region="drag"` / `"no-drag"` attribute also works and
+    /// takes precedence.
+    pub app_region_drag: c_int,
+    /// Non-zero = keep the window title in sync with the page's `document.title`.
+    pub auto_title: c_int,
+    /// Non-zero = remember zoom set via `wry_window_set_zoom` per origin (shared by all windows of
+    /// the app, in memory) and re-apply it when a page of that origin finishes loading.
+    pub zoom_persistence: c_int,
+    /// 0 = platform default, 1 = spellcheck on, 2 = off. See wry_window_set_spellcheck.
+    pub spellcheck: c_int,
+    /// Non-zero = make the window fullscreen while page content is in HTML fullscreen (Windows,
+    /// Linux; macOS WebKit already shows the element in its own fullscreen window).
+    pub html_fullscreen_window: c_int,
 
     // Event callbacks: function pointer + opaque context. Null function pointer = not set.
     pub ipc_handler: Option<IpcCallback>,
@@ -282,6 +627,44 @@ pub struct WryWindowConfig {
     pub page_load_handler_ctx: *mut c_void,
     pub drag_drop_handler: Option<DragDropCallback>,
     pub drag_drop_handler_ctx: *mut c_void,
+    pub tiled_handler: Option<TiledCallback>,
+    pub tiled_handler_ctx: *mut c_void,
+    pub first_paint_handler: Option<FirstPaintCallback>,
+    pub first_paint_handler_ctx: *mut c_void,
+    pub hit_test_handler: Option<HitTestCallback>,
+    pub hit_test_handler_ctx: *mut c_void,
+    pub title_changed_handler: Option<TitleChangedCallback>,
+    pub title_changed_handler_ctx: *mut c_void,
+    pub favicon_changed_handler: Option<FaviconChangedCallback>,
+    pub favicon_changed_handler_ctx: *mut c_void,
+    pub mouse_wheel_handler: Option<MouseWheelCallback>,
+    pub mouse_wheel_handler_ctx: *mut c_void,
+    pub cursor_moved_handler: Option<CursorMovedCallback>,
+    pub cursor_moved_handler_ctx: *mut c_void,
+    pub mouse_button_handler: Option<MouseButtonCallback>,
+    pub mouse_button_handler_ctx: *mut c_void,
+    pub touch_handler: Option<TouchCallback>,
+    pub touch_handler_ctx: *mut c_void,
+    pub webview_crash_handler: Option<WebviewCrashCallback>,
+    pub webview_crash_handler_ctx: *mut c_void,
+    pub script_dialog_handler: Option<ScriptDialogCallback>,
+    pub script_dialog_handler_ctx: *mut c_void,
+    pub console_message_handler: Option<ConsoleMessageCallback>,
+    pub console_message_handler_ctx: *mut c_void,
+    pub redraw_handler: Option<RedrawCallback>,
+    pub redraw_handler_ctx: *mut c_void,
+    pub html_fullscreen_handler: Option<HtmlFullscreenCallback>,
+    pub html_fullscreen_handler_ctx: *mut c_void,
+    pub initial_load_handler: Option<InitialLoadCallback>,
+    pub initial_load_handler_ctx: *mut c_void,
+    pub monitor_changed_handler: Option<MonitorCallback>,
+    pub monitor_changed_handler_ctx: *mut c_void,
+    pub background_throttle_handler: Option<BackgroundThrottleCallback>,
+    pub background_throttle_handler_ctx: *mut c_void,
+    pub occlusion_handler: Option<OcclusionCallback>,
+    pub occlusion_handler_ctx: *mut c_void,
+    pub responsiveness_handler: Option<ResponsivenessCallback>,
+    pub responsiveness_handler_ctx: *mut c_void,
 }
 
 /// Build a WindowCreatePayload from FFI config. Safe if config is valid; uses defaults for null/zero.
@@ -321,11 +704,18 @@ fn payload_from_config(config: *const WryWindowConfig) -> WindowCreatePayload {
         let slice = unsafe { std::slice::from_raw_parts(c.protocols, c.protocol_count as usize) };
         for entry in slice {
             let scheme = unsafe { c_str_to_string(entry.scheme) };
+            let status = check_protocol_scheme(&scheme, payload.protocols.iter().map(|p| p.scheme.as_str()));
+            // Entries without a scheme are unused slots; skip them silently.
             if !scheme.is_empty() {
+                log_err!(protocol_status_result(&scheme, status), "config protocol");
+            }
+            if status == PROTOCOL_OK {
                 payload.protocols.push(PendingProtocol {
                     scheme,
                     callback: entry.callback,
                     ctx: entry.ctx as usize,
+                    secure: entry.secure != 0,
+                    standard: entry.standard != 0,
                 });
             }
         }
@@ -396,6 +786,7 @@ fn payload_from_config(config: *const WryWindowConfig) -> WindowCreatePayload {
         payload.theme = c.theme;
         payload.https_scheme = c.https_scheme != 0;
         payload.browser_accelerator_keys = c.browser_accelerator_keys != 0;
+        payload.devtools_shortcut = c.block_devtools_shortcut == 0;
         payload.scroll_bar_style = c.scroll_bar_style;
     }
     payload.skip_taskbar = c.skip_taskbar != 0;
@@ -413,6 +804,16 @@ fn payload_from_config(config: *const WryWindowConfig) -> WindowCreatePayload {
             payload.window_classname = Some(s);
         }
     }
+    payload.show_when_ready = c.show_when_ready != 0;
+    payload.app_region_drag = c.app_region_drag != 0;
+    payload.auto_title = c.auto_title != 0;
+    payload.zoom_persistence = c.zoom_persistence != 0;
+    payload.html_fullscreen_window = c.html_fullscreen_window != 0;
+    payload.spellcheck = match c.spellcheck {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    };
     if c.owner_window_id != 0 {
         payload.owner_window_id = Some(c.owner_window_id);
         payload.parent_window_id = None;
@@ -445,31 +846,207 @@ fn payload_from_config(config: *const WryWindowConfig) -> WindowCreatePayload {
     if let Some(cb) = c.drag_drop_handler {
         payload.drag_drop_handler = Some((cb, c.drag_drop_handler_ctx as usize));
     }
+    if let Some(cb) = c.tiled_handler {
+        payload.tiled_handler = Some((cb, c.tiled_handler_ctx as usize));
+    }
+    if let Some(cb) = c.first_paint_handler {
+        payload.first_paint_handler = Some((cb, c.first_paint_handler_ctx as usize));
+    }
+    if let Some(cb) = c.hit_test_handler {
+        payload.hit_test_handler = Some((cb, c.hit_test_handler_ctx as usize));
+    }
+    if let Some(cb) = c.title_changed_handler {
+        payload.title_changed_handler = Some((cb, c.title_changed_handler_ctx as usize));
+    }
+    if let Some(cb) = c.favicon_changed_handler {
+        payload.favicon_changed_handler = Some((cb, c.favicon_changed_handler_ctx as usize));
+    }
+    if let Some(cb) = c.mouse_wheel_handler {
+        payload.mouse_wheel_handler = Some((cb, c.mouse_wheel_handler_ctx as usize));
+    }
+    if let Some(cb) = c.cursor_moved_handler {
+        payload.cursor_moved_handler = Some((cb, c.cursor_moved_handler_ctx as usize));
+    }
+    if let Some(cb) = c.mouse_button_handler {
+        payload.mouse_button_handler = Some((cb, c.mouse_button_handler_ctx as usize));
+    }
+    if let Some(cb) = c.touch_handler {
+        payload.touch_handler = Some((cb, c.touch_handler_ctx as usize));
+    }
+    if let Some(cb) = c.webview_crash_handler {
+        payload.webview_crash_handler = Some((cb, c.webview_crash_handler_ctx as usize));
+    }
+    if let Some(cb) = c.script_dialog_handler {
+        payload.script_dialog_handler = Some((cb, c.script_dialog_handler_ctx as usize));
+    }
+    if let Some(cb) = c.console_message_handler {
+        payload.console_message_handler = Some((cb, c.console_message_handler_ctx as usize));
+    }
+    if let Some(cb) = c.redraw_handler {
+        payload.redraw_handler = Some((cb, c.redraw_handler_ctx as usize));
+    }
+    if let Some(cb) = c.html_fullscreen_handler {
+        payload.html_fullscreen_handler = Some((cb, c.html_fullscreen_handler_ctx as usize));
+    }
+    if let Some(cb) = c.initial_load_handler {
+        payload.initial_load_handler = Some((cb, c.initial_load_handler_ctx as usize));
+    }
+    if let Some(cb) = c.monitor_changed_handler {
+        payload.monitor_changed_handler = Some((cb, c.monitor_changed_handler_ctx as usize));
+    }
+    if let Some(cb) = c.background_throttle_handler {
+        payload.background_throttle_handler = Some((cb, c.background_throttle_handler_ctx as usize));
+    }
+    if let Some(cb) = c.occlusion_handler {
+        payload.occlusion_handler = Some((cb, c.occlusion_handler_ctx as usize));
+    }
+    if let Some(cb) = c.responsiveness_handler {
+        payload.responsiveness_handler = Some((cb, c.responsiveness_handler_ctx as usize));
+    }
     payload
 }
 
-/// Decode image file bytes (PNG, ICO, JPEG, BMP, GIF) into a window Icon. Used for create-time icon.
-fn decode_icon_from_bytes(data: &[u8]) -> Option<Icon> {
+/// Edge length in pixels SVG icons are rasterized at when no size is given. Large enough for
+/// alt-tab / dock use; the OS scales it down for title bars and trays.
+pub(crate) const DEFAULT_SVG_ICON_SIZE: u32 = 256;
+
+/// True if `data` looks like an SVG document (optionally after whitespace, a BOM or an XML declaration).
+fn is_svg(data: &[u8]) -> bool {
+    let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+    let start = data.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(data.len());
+    let head = &data[start..data.len().min(start + 1024)];
+    head.starts_with(b"<svg")
+        || (head.starts_with(b"<?xml") || head.starts_with(b"<!")) && head.windows(4).any(|w| w == b"<svg")
+}
+
+/// Rasterize SVG (or gzip-compressed SVGZ) bytes onto a transparent `size` x `size` canvas,
+/// scaled to fit and centered. Returns straight (non-premultiplied) RGBA.
+pub(crate) fn rasterize_svg(data: &[u8], size: u32) -> Result<(Vec<u8>, u32, u32), String> {
+    use resvg::{tiny_skia, usvg};
+    if size == 0 {
+        return Err("size must be positive".into());
+    }
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default()).map_err(|e| e.to_string())?;
+    let mut pixmap = tiny_skia::Pixmap::new(size, size).ok_or("could not allocate pixmap")?;
+    let (w, h) = (tree.size().width(), tree.size().height());
+    let scale = size as f32 / w.max(h);
+    let transform = tiny_skia::Transform::from_scale(scale, scale)
+        .post_translate((size as f32 - w * scale) / 2.0, (size as f32 - h * scale) / 2.0);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    let rgba = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+    Ok((rgba, size, size))
+}
+
+/// Decode encoded icon bytes (PNG, ICO, JPEG, BMP, GIF, WebP, or SVG at DEFAULT_SVG_ICON_SIZE)
+/// into straight RGBA. Shared by the window and tray icon paths.
+pub(crate) fn decode_icon_rgba(data: &[u8]) -> Result<(Vec<u8>, u32, u32), String> {
     use image::GenericImageView;
-    match image::load_from_memory(data) {
-        Ok(img) => {
-            let rgba = img.to_rgba8();
-            let (w, h) = img.dimensions();
-            match Icon::from_rgba(rgba.into_raw(), w, h) {
-                Ok(icon) => Some(icon),
-                Err(e) => {
-                    eprintln!("[wry-native] decode_icon_from_bytes: Icon::from_rgba failed: {}", e);
-                    None
-                }
-            }
+    if is_svg(data) {
+        return rasterize_svg(data, DEFAULT_SVG_ICON_SIZE).map_err(|e| format!("SVG render failed: {}", e));
+    }
+    let img = image::load_from_memory(data).map_err(|e| format!("image decode failed: {}", e))?;
+    let (w, h) = img.dimensions();
+    Ok((img.to_rgba8().into_raw(), w, h))
+}
+
+/// Decode image file bytes (PNG, ICO, JPEG, BMP, GIF, WebP, SVG) into a window Icon. Used for create-time icon.
+fn decode_icon_from_bytes(data: &[u8]) -> Option<Icon> {
+    let (rgba, w, h) = match decode_icon_rgba(data) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            eprintln!("[wry-native] decode_icon_from_bytes: {}", e);
+            return None;
+        }
+    };
+    match Icon::from_rgba(rgba, w, h) {
+        Ok(icon) => Some(icon),
+        Err(e) => {
+            eprintln!("[wry-native] decode_icon_from_bytes: Icon::from_rgba failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Comma-separated, lowercase names of the icon formats this build can decode: the `image`
+/// crate's enabled decoders in `ImageFormat::all()` order, then "svg" (resvg).
+fn supported_image_formats() -> String {
+    image::ImageFormat::all()
+        .filter(|f| f.reading_enabled())
+        .map(|f| format!("{:?}", f).to_ascii_lowercase())
+        .chain(std::iter::once("svg".to_string()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Re-encode favicon bytes (PNG, ICO, JPEG, BMP, GIF, WebP) as PNG. None if empty or undecodable.
+fn favicon_to_png(data: &[u8]) -> Option<Vec<u8>> {
+    if data.is_empty() {
+        return None;
+    }
+    let img = match image::load_from_memory(data) {
+        Ok(img) => img,
+        Err(e) => {
+            eprintln!("[wry-native] favicon_to_png: image decode failed: {}", e);
+            return None;
         }
+    };
+    let mut out = std::io::Cursor::new(Vec::new());
+    match img.write_to(&mut out, image::ImageFormat::Png) {
+        Ok(()) => Some(out.into_inner()),
         Err(e) => {
-            eprintln!("[wry-native] decode_icon_from_bytes: image decode failed: {}", e);
+            eprintln!("[wry-native] favicon_to_png: png encode failed: {}", e);
             None
         }
     }
 }
 
+/// Encode cairo ARGB32 pixels (premultiplied alpha, one native-endian 0xAARRGGBB word per pixel,
+/// `stride` bytes per row) as PNG. None if the buffer is too small for the size.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn argb32_to_png(data: &[u8], width: u32, height: u32, stride: usize) -> Option<Vec<u8>> {
+    let row = width as usize * 4;
+    if width == 0 || height == 0 || stride < row || data.len() < stride * (height as usize - 1) + row {
+        return None;
+    }
+    let mut rgba = Vec::with_capacity(row * height as usize);
+    for y in 0..height as usize {
+        for px in data[y * stride..y * stride + row].chunks_exact(4) {
+            let argb = u32::from_ne_bytes([px[0], px[1], px[2], px[3]]);
+            let a = argb >> 24;
+            let straight = |c: u32| if a == 0 { 0 } else { ((c * 255 + a / 2) / a).min(255) as u8 };
+            rgba.extend_from_slice(&[
+                straight((argb >> 16) & 0xff),
+                straight((argb >> 8) & 0xff),
+                straight(argb & 0xff),
+                a as u8,
+            ]);
+        }
+    }
+    let img = image::RgbaImage::from_raw(width, height, rgba)?;
+    let mut out = std::io::Cursor::new(Vec::new());
+    let result = img.write_to(&mut out, image::ImageFormat::Png);
+    log_err!(result.as_ref(), "argb32_to_png: png encode");
+    result.ok().map(|()| out.into_inner())
+}
+
+/// Decode the payload of a base64 `data:` URL. None for empty, non-base64 or malformed URLs.
+fn decode_data_url(url: &str) -> Option<Vec<u8>> {
+    use base64::Engine;
+    let rest = url.strip_prefix("data:")?;
+    let (meta, data) = rest.split_once(',')?;
+    if !meta.ends_with(";base64") {
+        return None;
+    }
+    base64::engine::general_purpose::STANDARD.decode(data).ok()
+}
+
 // ---------------------------------------------------------------------------
 // Pending protocol registration
 // ---------------------------------------------------------------------------
@@ -479,6 +1056,10 @@ struct PendingProtocol {
     scheme: String,
     callback: ProtocolHandlerCallback,
     ctx: usize,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    secure: bool,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    standard: bool,
 }
 
 /// Owned configuration for a window, passed at creation time via wry_window_create.
@@ -520,6 +1101,8 @@ pub(crate) struct WindowCreatePayload {
     #[cfg(target_os = "windows")]
     pub browser_accelerator_keys: bool,
     #[cfg(target_os = "windows")]
+    pub devtools_shortcut: bool,
+    #[cfg(target_os = "windows")]
     pub default_context_menus: bool,
     #[cfg(target_os = "windows")]
     pub scroll_bar_style: i32,
@@ -535,7 +1118,24 @@ pub(crate) struct WindowCreatePayload {
     pub window_classname: Option<String>,
     pub owner_window_id: Option<usize>,
     pub parent_window_id: Option<usize>,
+    pub show_when_ready: bool,
+    pub app_region_drag: bool,
+    pub auto_title: bool,
+    pub zoom_persistence: bool,
+    pub spellcheck: Option<bool>,
+    /// App-wide origin -> zoom map, set by wry_window_create when zoom_persistence is on.
+    pub zoom_store: Option<ZoomStore>,
+    /// Load state registered with the app by wry_window_create; adopted by the window on create.
+    pub load_state: Arc<Mutex<LoadState>>,
     pub init_scripts: Vec<String>,
+    /// Script defining `window.__WRY_DATA` (wry_window_set_initial_data); runs before all others.
+    pub initial_data_script: Option<String>,
+    /// Scripts run on DOMContentLoaded (wry_window_add_dom_ready_script).
+    pub dom_ready_scripts: Vec<String>,
+    /// CSS injected at document start (wry_window_add_user_stylesheet).
+    pub user_stylesheets: Vec<String>,
+    /// Cookies set before the first navigation (wry_window_add_initial_cookie).
+    pub initial_cookies: Vec<wry::cookie::Cookie<'static>>,
     pub protocols: Vec<PendingProtocol>,
     pub data_directory: Option<String>,
     pub icon: Option<Icon>,
@@ -547,6 +1147,43 @@ pub(crate) struct WindowCreatePayload {
     pub navigation_handler: Option<(NavigationCallback, usize)>,
     pub page_load_handler: Option<(PageLoadCallback, usize)>,
     pub drag_drop_handler: Option<(DragDropCallback, usize)>,
+    pub tiled_handler: Option<(TiledCallback, usize)>,
+    pub first_paint_handler: Option<(FirstPaintCallback, usize)>,
+    pub hit_test_handler: Option<(HitTestCallback, usize)>,
+    pub title_changed_handler: Option<(TitleChangedCallback, usize)>,
+    pub favicon_changed_handler: Option<(FaviconChangedCallback, usize)>,
+    pub mouse_wheel_handler: Option<(MouseWheelCallback, usize)>,
+    pub cursor_moved_handler: Option<(CursorMovedCallback, usize)>,
+    pub mouse_button_handler: Option<(MouseButtonCallback, usize)>,
+    pub touch_handler: Option<(TouchCallback, usize)>,
+    pub webview_crash_handler: Option<(WebviewCrashCallback, usize)>,
+    /// Windows / Linux only; ignored on macOS.
+    pub responsiveness_handler: Option<(ResponsivenessCallback, usize)>,
+    pub script_dialog_handler: Option<(ScriptDialogCallback, usize)>,
+    pub console_message_handler: Option<(ConsoleMessageCallback, usize)>,
+    pub redraw_handler: Option<(RedrawCallback, usize)>,
+    pub html_fullscreen_handler: Option<(HtmlFullscreenCallback, usize)>,
+    pub initial_load_handler: Option<(InitialLoadCallback, usize)>,
+    pub monitor_changed_handler: Option<(MonitorCallback, usize)>,
+    /// Frame rate cap while unfocused (wry_window_set_background_fps); None = no cap.
+    pub background_fps: Option<c_int>,
+    pub background_throttle_handler: Option<(BackgroundThrottleCallback, usize)>,
+    /// Fullscreen the window along with the page's fullscreen element.
+    pub html_fullscreen_window: bool,
+    /// AUTOPLAY_* policy (wry_window_set_autoplay_policy); overrides `autoplay` when set.
+    pub autoplay_policy: Option<c_int>,
+    /// Chromium remote-debugging port (wry_window_set_remote_debugging_port; Windows only).
+    pub remote_debugging_port: Option<u16>,
+    /// BCP 47 language tag (wry_window_set_language; Windows / Linux).
+    pub language: Option<String>,
+    /// HTTP cache on (wry_window_set_cache_enabled).
+    pub cache_enabled: bool,
+    /// Hide the close / minimize / zoom buttons (wry_window_set_titlebar_buttons_hidden; macOS).
+    pub titlebar_buttons_hidden: bool,
+    /// Tool-palette window that does not take activation (wry_window_set_panel_style).
+    pub panel_style: bool,
+    /// macOS only; ignored elsewhere.
+    pub occlusion_handler: Option<(OcclusionCallback, usize)>,
 }
 
 impl Default for WindowCreatePayload {
@@ -587,6 +1224,8 @@ impl Default for WindowCreatePayload {
             #[cfg(target_os = "windows")]
             browser_accelerator_keys: true,
             #[cfg(target_os = "windows")]
+            devtools_shortcut: true,
+            #[cfg(target_os = "windows")]
             default_context_menus: true,
             #[cfg(target_os = "windows")]
             scroll_bar_style: 0,
@@ -602,7 +1241,18 @@ impl Default for WindowCreatePayload {
             window_classname: None,
             owner_window_id: None,
             parent_window_id: None,
+            show_when_ready: false,
+            app_region_drag: false,
+            auto_title: false,
+            zoom_persistence: false,
+            spellcheck: None,
+            zoom_store: None,
+            load_state: Arc::new(Mutex::new(LoadState::default())),
             init_scripts: Vec::new(),
+            initial_data_script: None,
+            dom_ready_scripts: Vec::new(),
+            user_stylesheets: Vec::new(),
+            initial_cookies: Vec::new(),
             protocols: Vec::new(),
             data_directory: None,
             icon: None,
@@ -614,6 +1264,33 @@ impl Default for WindowCreatePayload {
             navigation_handler: None,
             page_load_handler: None,
             drag_drop_handler: None,
+            tiled_handler: None,
+            first_paint_handler: None,
+            hit_test_handler: None,
+            title_changed_handler: None,
+            favicon_changed_handler: None,
+            mouse_wheel_handler: None,
+            cursor_moved_handler: None,
+            mouse_button_handler: None,
+            touch_handler: None,
+            webview_crash_handler: None,
+            responsiveness_handler: None,
+            script_dialog_handler: None,
+            console_message_handler: None,
+            redraw_handler: None,
+            html_fullscreen_handler: None,
+            html_fullscreen_window: false,
+            initial_load_handler: None,
+            monitor_changed_handler: None,
+            background_fps: None,
+            background_throttle_handler: None,
+            autoplay_policy: None,
+            remote_debugging_port: None,
+            language: None,
+            cache_enabled: true,
+            titlebar_buttons_hidden: false,
+            panel_style: false,
+            occlusion_handler: None,
         }
     }
 }
@@ -624,6 +1301,60 @@ unsafe impl Send for WindowCreatePayload {}
 // WryWindow -- per-window state
 // ---------------------------------------------------------------------------
 
+/// Zoom level per origin ("scheme://host[:port]"), shared by the windows of an app.
+pub(crate) type ZoomStore = Arc<Mutex<HashMap<String, f64>>>;
+
+/// Page-load state shared with threads blocked in wry_window_wait_for_load /
+/// wry_window_navigate_and_wait. Updated on the event loop thread, by the platform load hooks
+/// (watch_load_outcome) where installed and by the page load handler otherwise.
+#[derive(Default)]
+struct LoadState {
+    /// A navigation has started and its Finished event has not arrived yet.
+    loading: bool,
+    /// watch_load_outcome is installed and reports success / failure of each load.
+    native_outcome: bool,
+    /// The current load failed (Linux `load-failed`, reported before its Finished).
+    failed: bool,
+    /// Woken with the load's success by the next Finished; dropped senders (window destroyed)
+    /// wake with an error. Unarmed waiters (navigate_and_wait) arm at the next Started, so the
+    /// end of a load already in progress does not wake them.
+    waiters: Vec<(std::sync::mpsc::Sender<bool>, bool)>,
+}
+
+impl LoadState {
+    fn started(&mut self) {
+        self.loading = true;
+        self.failed = false;
+        for (_, armed) in &mut self.waiters {
+            *armed = true;
+        }
+    }
+
+    fn finished(&mut self, success: bool) {
+        self.loading = false;
+        self.waiters.retain(|(waiter, armed)| {
+            if *armed {
+                let _ = waiter.send(success);
+            }
+            !*armed
+        });
+    }
+}
+
+/// Window / webview settings saved by wry_window_set_kiosk_mode.
+struct KioskSaved {
+    fullscreen: bool,
+    decorations: bool,
+    topmost: bool,
+    resizable: bool,
+    minimizable: bool,
+    maximizable: bool,
+    closable: bool,
+    /// Windows: (accelerator keys, default context menus, devtools) from the WebView2 settings.
+    #[cfg(target_os = "windows")]
+    webview_settings: Option<(bool, bool, bool)>,
+}
+
 pub struct WryWindow {
     id: usize,
 
@@ -632,10 +1363,95 @@ pub struct WryWindow {
     resize_handler: Option<(ResizeCallback, usize)>,
     move_handler: Option<(MoveCallback, usize)>,
     focus_handler: Option<(FocusCallback, usize)>,
+    tiled_handler: Option<(TiledCallback, usize)>,
+    first_paint_handler: Option<(FirstPaintCallback, usize)>,
+    hit_test_handler: Option<(HitTestCallback, usize)>,
+    title_changed_handler: Option<(TitleChangedCallback, usize)>,
+    favicon_changed_handler: Option<(FaviconChangedCallback, usize)>,
+    mouse_wheel_handler: Option<(MouseWheelCallback, usize)>,
+    cursor_moved_handler: Option<(CursorMovedCallback, usize)>,
+    mouse_button_handler: Option<(MouseButtonCallback, usize)>,
+    touch_handler: Option<(TouchCallback, usize)>,
+    redraw_handler: Option<(RedrawCallback, usize)>,
+    html_fullscreen_handler: Option<(HtmlFullscreenCallback, usize)>,
+    /// Follow the page's HTML fullscreen state with the window (Windows / Linux).
+    html_fullscreen_window: bool,
+    initial_load_handler: Option<(InitialLoadCallback, usize)>,
+    /// The current webview's first page load has finished (cleared when it is rebuilt).
+    initial_load_done: bool,
+    /// Current keyboard modifiers (MODIFIER_* bitmask), tracked from ModifiersChanged.
+    modifiers: c_int,
+    /// Last cursor position in logical pixels, tracked from CursorMoved.
+    cursor_position: (f64, f64),
+    /// Window title follows document.title.
+    auto_title: bool,
+    /// Last tiled edges reported to tiled_handler.
+    tiled_edges: c_int,
+    monitor_changed_handler: Option<(MonitorCallback, usize)>,
+    /// Monitor the window was last seen on, tracked from Moved for monitor_changed_handler.
+    monitor: Option<tao::monitor::MonitorHandle>,
+    /// Scripts from wry_window_eval_js_batched, run together at the end of the loop iteration.
+    eval_batch: Vec<String>,
+    /// Frame rate cap applied while unfocused (wry_window_set_background_fps).
+    background_fps: Option<c_int>,
+    background_throttle_handler: Option<(BackgroundThrottleCallback, usize)>,
+    /// The cap is in effect (the window is unfocused).
+    background_throttled: bool,
+    /// Edge snapping distance in logical pixels (wry_window_set_edge_snapping); None = off.
+    snap_threshold: Option<f64>,
+    /// Created hidden; show on FirstPaint, or at this deadline if the first load never ends,
+    /// unless visibility was set explicitly before then. None = nothing pending.
+    show_when_ready: Option<Instant>,
+
+    /// Settings replaced by kiosk mode, restored when it is turned off. None = not in kiosk mode.
+    kiosk: Option<KioskSaved>,
+    /// Per-origin zoom (zoom_persistence); None when disabled.
+    zoom_store: Option<ZoomStore>,
+    /// Zoom applied to origins without a remembered level.
+    default_zoom: f64,
+    /// Spellcheck override re-applied after each page load; None = platform default.
+    spellcheck: Option<bool>,
+
+    /// Last requested skip-taskbar state (reported by wry_window_get_skip_taskbar).
+    skip_taskbar: bool,
+    /// Opaque host value (wry_window_set_user_data). Never dereferenced here.
+    user_data: usize,
+    /// Read from other threads by wry_window_wait_for_load / wry_window_is_loading through the
+    /// app's load_states map. Taken from the payload on create, never replaced afterwards.
+    load_state: Arc<Mutex<LoadState>>,
+    /// Same-origin frame IDs from the last wry_window_get_frames listing of the current page; the
+    /// only IDs wry_window_eval_in_frame accepts. Cleared when a navigation starts.
+    reachable_frames: Arc<Mutex<HashSet<u32>>>,
+    /// Linux: the skip-taskbar hint must be re-applied once the GTK window is mapped,
+    /// since some window managers ignore a hint set before that.
+    #[cfg(target_os = "linux")]
+    skip_taskbar_reapply: bool,
+    /// macOS: blur view inserted behind the webview by wry_window_set_vibrancy.
+    #[cfg(target_os = "macos")]
+    vibrancy_view: Option<objc2::rc::Retained<objc2_app_kit::NSVisualEffectView>>,
+    /// macOS: occlusion notification observer for occlusion_handler. Declared before
+    /// `window` so it is removed before the NSWindow goes away.
+    #[cfg(target_os = "macos")]
+    occlusion_observer: Option<OcclusionObserver>,
+    /// macOS: wheel event monitor for mouse_wheel_handler; removed on drop.
+    #[cfg(target_os = "macos")]
+    wheel_monitor: Option<WheelMonitor>,
+    /// Windows: small / big HICONs installed by wry_window_set_icon_multi; destroyed when replaced.
+    #[cfg(target_os = "windows")]
+    multi_icons: Vec<windows::Win32::UI::WindowsAndMessaging::HICON>,
+    /// Windows: button IDs fixed by the first ThumbBarAddButtons (None = no toolbar yet) and the
+    /// button icons currently shown, destroyed when replaced.
+    #[cfg(target_os = "windows")]
+    thumbnail_ids: Option<Vec<u32>>,
+    #[cfg(target_os = "windows")]
+    thumbnail_icons: Vec<windows::Win32::UI::WindowsAndMessaging::HICON>,
 
     // --- Live objects (populated during create()) ---
     pub(crate) window: Option<Window>,
     webview: Option<WebView>,
+    /// Creation payload, kept to rebuild the webview (wry_window_reset_webview).
+    config: Option<WindowCreatePayload>,
+    proxy: Option<EventLoopProxy<UserEvent>>,
     web_context: Option<WebContext>,
     window_id: Option<WindowId>,
 }
@@ -644,6 +1460,23 @@ pub struct WryWindow {
 // WryWindow is only sent to the main thread when pending (window and webview are None).
 unsafe impl Send for WryWindow {}
 
+// Windows: icons the window owns outlive it unless destroyed here; destroy the window first so
+// none is in use when it goes.
+#[cfg(target_os = "windows")]
+impl Drop for WryWindow {
+    fn drop(&mut self) {
+        use windows::Win32::UI::WindowsAndMessaging::DestroyIcon;
+        self.webview = None;
+        self.window = None;
+        for icon in self.multi_icons.drain(..) {
+            log_err!(unsafe { DestroyIcon(icon) }, "set_icon_multi DestroyIcon");
+        }
+        for icon in self.thumbnail_icons.drain(..) {
+            log_err!(unsafe { DestroyIcon(icon) }, "thumbnail toolbar DestroyIcon");
+        }
+    }
+}
+
 impl WryWindow {
     fn new(id: usize) -> Self {
         Self {
@@ -652,8 +1485,58 @@ impl WryWindow {
             resize_handler: None,
             move_handler: None,
             focus_handler: None,
+            tiled_handler: None,
+            first_paint_handler: None,
+            hit_test_handler: None,
+            title_changed_handler: None,
+            favicon_changed_handler: None,
+            mouse_wheel_handler: None,
+            cursor_moved_handler: None,
+            mouse_button_handler: None,
+            touch_handler: None,
+            redraw_handler: None,
+            html_fullscreen_handler: None,
+            html_fullscreen_window: false,
+            initial_load_handler: None,
+            initial_load_done: false,
+            modifiers: 0,
+            cursor_position: (0.0, 0.0),
+            auto_title: false,
+            tiled_edges: 0,
+            monitor_changed_handler: None,
+            monitor: None,
+            eval_batch: Vec::new(),
+            background_fps: None,
+            background_throttle_handler: None,
+            background_throttled: false,
+            snap_threshold: None,
+            show_when_ready: None,
+            kiosk: None,
+            zoom_store: None,
+            default_zoom: 1.0,
+            spellcheck: None,
+            skip_taskbar: false,
+            user_data: 0,
+            load_state: Arc::new(Mutex::new(LoadState::default())),
+            reachable_frames: Arc::new(Mutex::new(HashSet::new())),
+            #[cfg(target_os = "linux")]
+            skip_taskbar_reapply: false,
+            #[cfg(target_os = "macos")]
+            vibrancy_view: None,
+            #[cfg(target_os = "macos")]
+            occlusion_observer: None,
+            #[cfg(target_os = "macos")]
+            wheel_monitor: None,
+            #[cfg(target_os = "windows")]
+            multi_icons: Vec::new(),
+            #[cfg(target_os = "windows")]
+            thumbnail_ids: None,
+            #[cfg(target_os = "windows")]
+            thumbnail_icons: Vec::new(),
             window: None,
             webview: None,
+            config: None,
+            proxy: None,
             web_context: None,
             window_id: None,
         }
@@ -661,20 +1544,24 @@ impl WryWindow {
 
     /// Materialize the tao Window + wry WebView from a creation payload.
     /// owner_window / parent_window: resolved parent tao Window; owner takes precedence if both set.
+    /// proxy: used by webview handlers to post events back to the loop (e.g. FirstPaint).
     fn create(
         &mut self,
         payload: &WindowCreatePayload,
         event_loop: &EventLoopWindowTarget<UserEvent>,
+        proxy: &EventLoopProxy<UserEvent>,
         owner_window: Option<&Window>,
         parent_window: Option<&Window>,
     ) -> Result<(), String> {
+        self.load_state = payload.load_state.clone();
         let (w, h) = payload.size;
+        let show_when_ready = payload.show_when_ready && payload.visible;
         let mut wb = TaoWindowBuilder::new()
             .with_title(&payload.title)
             .with_inner_size(LogicalSize::new(w, h))
             .with_resizable(payload.resizable)
             .with_always_on_top(payload.topmost)
-            .with_visible(payload.visible)
+            .with_visible(payload.visible && !show_when_ready)
             .with_maximized(payload.maximized)
             .with_decorations(payload.decorations)
             .with_content_protection(payload.content_protected)
@@ -683,10 +1570,13 @@ impl WryWindow {
             .with_minimizable(payload.minimizable)
             .with_closable(payload.closable)
             .with_focusable(payload.focusable);
+        if payload.panel_style {
+            wb = wb.with_focused(false);
+        }
 
         #[cfg(target_os = "windows")]
         {
-            wb = wb.with_skip_taskbar(payload.skip_taskbar);
+            wb = wb.with_skip_taskbar(payload.skip_taskbar || payload.panel_style);
             wb = wb.with_undecorated_shadow(payload.shadow);
             if let Some(ref class_name) = payload.window_classname {
                 if !class_name.is_empty() {
@@ -716,6 +1606,11 @@ impl WryWindow {
         if payload.fullscreen {
             wb = wb.with_fullscreen(Some(Fullscreen::Borderless(None)));
         }
+        // Paint the window itself in the background color too, so nothing white shows before the
+        // webview is attached.
+        if let Some(color) = payload.background_color {
+            wb = wb.with_background_color(color);
+        }
         if let Some(ref icon) = payload.icon {
             wb = wb.with_window_icon(Some(icon.clone()));
         }
@@ -738,6 +1633,11 @@ impl WryWindow {
                 wb = wb.with_parent_window(w.ns_window());
             }
         }
+        #[cfg(target_os = "macos")]
+        if payload.titlebar_buttons_hidden {
+            use tao::platform::macos::WindowBuilderExtMacOS;
+            wb = wb.with_titlebar_buttons_hidden(true);
+        }
         #[cfg(target_os = "linux")]
         {
             if let Some(w) = owner_window.or(parent_window) {
@@ -746,9 +1646,90 @@ impl WryWindow {
             }
         }
 
-        let window = wb.build(event_loop).map_err(|e| e.to_string())?;
+        self.window = Some(wb.build(event_loop).map_err(|e| e.to_string())?);
+        let notify_first_paint = payload.first_paint_handler.is_some() || show_when_ready;
+        let webview = match self.build_webview(payload, proxy, notify_first_paint) {
+            Ok(webview) => webview,
+            Err(e) => {
+                self.window = None;
+                return Err(e);
+            }
+        };
+
+        // Windows: also answer WM_NCHITTEST for the parts of the window not covered by the webview.
+        #[cfg(target_os = "windows")]
+        if let (Some(handler), Some(window)) = (payload.hit_test_handler, &self.window) {
+            install_hit_test_subclass(window, handler);
+        }
+        #[cfg(target_os = "windows")]
+        if let (true, Some(window)) = (payload.panel_style, &self.window) {
+            apply_panel_style(window);
+        }
+        #[cfg(target_os = "macos")]
+        if let (Some(handler), Some(window)) = (payload.occlusion_handler, &self.window) {
+            self.occlusion_observer = Some(OcclusionObserver::new(window, handler));
+        }
+        #[cfg(target_os = "macos")]
+        if let (Some(handler), Some(window)) = (payload.mouse_wheel_handler, &self.window) {
+            self.wheel_monitor = WheelMonitor::new(window, handler);
+        }
+
+        self.window_id = self.window.as_ref().map(|w| w.id());
+        self.webview = Some(webview);
+        self.config = Some(payload.clone());
+        self.proxy = Some(proxy.clone());
+        self.close_handler = payload.close_handler;
+        self.resize_handler = payload.resize_handler;
+        self.move_handler = payload.move_handler;
+        self.focus_handler = payload.focus_handler;
+        self.tiled_handler = payload.tiled_handler;
+        self.monitor_changed_handler = payload.monitor_changed_handler;
+        self.background_fps = payload.background_fps;
+        self.background_throttle_handler = payload.background_throttle_handler;
+        if self.monitor_changed_handler.is_some() {
+            self.monitor = self.window.as_ref().and_then(|w| w.current_monitor());
+        }
+        self.first_paint_handler = payload.first_paint_handler;
+        self.hit_test_handler = payload.hit_test_handler;
+        self.title_changed_handler = payload.title_changed_handler;
+        self.favicon_changed_handler = payload.favicon_changed_handler;
+        self.mouse_wheel_handler = payload.mouse_wheel_handler;
+        self.cursor_moved_handler = payload.cursor_moved_handler;
+        self.mouse_button_handler = payload.mouse_button_handler;
+        self.touch_handler = payload.touch_handler;
+        self.redraw_handler = payload.redraw_handler;
+        self.html_fullscreen_handler = payload.html_fullscreen_handler;
+        self.html_fullscreen_window = payload.html_fullscreen_window;
+        self.initial_load_handler = payload.initial_load_handler;
+        self.auto_title = payload.auto_title;
+        self.zoom_store = payload.zoom_store.clone();
+        self.default_zoom = payload.zoom;
+        self.spellcheck = payload.spellcheck;
+        self.show_when_ready = show_when_ready.then(|| Instant::now() + SHOW_WHEN_READY_TIMEOUT);
+        self.skip_taskbar = payload.skip_taskbar;
+        #[cfg(target_os = "linux")]
+        {
+            self.skip_taskbar_reapply = payload.skip_taskbar;
+        }
+
+        if payload.minimized {
+            if let Some(ref w) = self.window {
+                w.set_minimized(true);
+            }
+        }
+        Ok(())
+    }
 
-        if let Some(ref dir) = payload.data_directory {
+    /// Build the webview for `payload` into the (already created) tao window, with all of its
+    /// handlers and watchers. Used by create() and reset_webview(); `notify_first_paint` posts
+    /// FirstPaint when the first page load finishes.
+    fn build_webview(
+        &mut self,
+        payload: &WindowCreatePayload,
+        proxy: &EventLoopProxy<UserEvent>,
+        notify_first_paint: bool,
+    ) -> Result<WebView, String> {
+        if let (Some(dir), None) = (&payload.data_directory, &self.web_context) {
             self.web_context = Some(WebContext::new(Some(std::path::PathBuf::from(dir))));
         }
 
@@ -758,10 +1739,23 @@ impl WryWindow {
             WebViewBuilder::new()
         };
 
-        if let Some(ref url) = payload.url {
-            wvb = wvb.with_url(url);
-        } else if let Some(ref html) = payload.html {
-            wvb = wvb.with_html(html);
+        // With initial cookies the first load waits until they are in the cookie store; on Linux a
+        // language is set on the built webview's context, before the first request.
+        let seed_cookies = !payload.initial_cookies.is_empty();
+        // Linux: context settings (language, cache model, favicon database, scheme security) apply
+        // from the next load.
+        let defer_load = seed_cookies
+            || (cfg!(target_os = "linux")
+                && (payload.language.is_some()
+                    || !payload.cache_enabled
+                    || payload.favicon_changed_handler.is_some()
+                    || payload.protocols.iter().any(|p| p.secure || p.standard)));
+        if !defer_load {
+            if let Some(ref url) = payload.url {
+                wvb = wvb.with_url(url);
+            } else if let Some(ref html) = payload.html {
+                wvb = wvb.with_html(html);
+            }
         }
 
         if let Some(ref ua) = payload.user_agent {
@@ -772,8 +1766,8 @@ impl WryWindow {
             wvb = wvb.with_transparent(true);
         }
 
-        if let Some((r, g, b, a)) = payload.background_color {
-            wvb = wvb.with_background_color((r, g, b, a));
+        if let Some(color) = payload.background_color {
+            wvb = wvb.with_background_color(color);
         }
 
         #[cfg(any(debug_assertions, feature = "devtools"))]
@@ -783,11 +1777,29 @@ impl WryWindow {
         let _ = payload.devtools;
 
         wvb = wvb.with_back_forward_navigation_gestures(payload.back_forward_gestures);
-        wvb = wvb.with_autoplay(payload.autoplay);
+        // An autoplay policy overrides the autoplay flag; Windows passes it to Chromium as-is.
+        let autoplay = payload
+            .autoplay_policy
+            .map_or(payload.autoplay, |p| p == AUTOPLAY_NO_USER_GESTURE_REQUIRED);
+        wvb = wvb.with_autoplay(autoplay);
+        #[cfg(target_os = "windows")]
+        if let Some(args) = additional_browser_args(
+            payload.autoplay_policy,
+            autoplay,
+            payload.remote_debugging_port,
+            payload.language.as_deref(),
+            payload.cache_enabled,
+        ) {
+            use wry::WebViewBuilderExtWindows;
+            wvb = wvb.with_additional_browser_args(args);
+        }
+        #[cfg(not(target_os = "windows"))]
+        let _ = payload.remote_debugging_port;
         wvb = wvb.with_hotkeys_zoom(payload.hotkeys_zoom);
         wvb = wvb.with_clipboard(payload.clipboard);
         wvb = wvb.with_accept_first_mouse(payload.accept_first_mouse);
-        wvb = wvb.with_incognito(payload.incognito);
+        // macOS: the only way to keep WKWebView off its disk cache is a non-persistent data store.
+        wvb = wvb.with_incognito(payload.incognito || (cfg!(target_os = "macos") && !payload.cache_enabled));
         wvb = wvb.with_focused(payload.focused);
 
         if payload.javascript_disabled {
@@ -825,17 +1837,128 @@ impl WryWindow {
             wvb = wvb.with_scroll_bar_style(style);
         }
 
+        if let Some(ref script) = payload.initial_data_script {
+            wvb = wvb.with_initialization_script(script);
+        }
         for script in &payload.init_scripts {
             wvb = wvb.with_initialization_script(script);
         }
+        for script in &payload.dom_ready_scripts {
+            wvb = wvb.with_initialization_script(&dom_ready_script(script));
+        }
+        for css in &payload.user_stylesheets {
+            wvb = wvb.with_initialization_script(&user_stylesheet_script(css));
+        }
+
+        // Hit testing: the webview covers the client area, so mouse downs are reported from JS
+        // and answered with drag_window / drag_resize_window on the loop.
+        let hit_test = payload.hit_test_handler.is_some();
+        if hit_test {
+            wvb = wvb.with_initialization_script(HIT_TEST_SCRIPT);
+        }
+
+        // App-region drag: WebView2 handles it natively (enabled after build); elsewhere use the shim.
+        let app_region_shim = payload.app_region_drag && !cfg!(target_os = "windows");
+        if app_region_shim {
+            wvb = wvb.with_initialization_script(&app_region_drag_script());
+        }
+
+        // Favicon: WebView2 and WebKitGTK report it natively (hooked after build); macOS uses the shim.
+        let favicon_shim = payload.favicon_changed_handler.is_some() && cfg!(target_os = "macos");
+        if favicon_shim {
+            wvb = wvb.with_initialization_script(FAVICON_SCRIPT);
+        }
+
+        let console_handler = payload.console_message_handler;
+        if console_handler.is_some() {
+            wvb = wvb.with_initialization_script(CONSOLE_SCRIPT);
+        }
+
+        // HTML fullscreen: WebView2 / WebKitGTK report it natively (hooked after build); macOS uses the shim.
+        let html_fullscreen = payload.html_fullscreen_handler.is_some() || payload.html_fullscreen_window;
+        let html_fullscreen_shim = html_fullscreen && cfg!(target_os = "macos");
+        if html_fullscreen_shim {
+            wvb = wvb.with_initialization_script(HTML_FULLSCREEN_SCRIPT);
+        }
+
+        if let Some(enabled) = payload.spellcheck {
+            wvb = wvb.with_initialization_script(&spellcheck_script(enabled));
+        }
+
+        if payload.background_fps.is_some() {
+            wvb = wvb.with_initialization_script(BACKGROUND_FPS_SCRIPT);
+        }
 
         // IPC handler (from payload - baked into webview at creation)
-        if let Some((cb, ctx)) = payload.ipc_handler {
+        if payload.ipc_handler.is_some()
+            || hit_test
+            || app_region_shim
+            || favicon_shim
+            || console_handler.is_some()
+            || html_fullscreen_shim
+        {
+            let user_handler = payload.ipc_handler;
+            let proxy = proxy.clone();
+            let our_id = self.id;
             wvb = wvb.with_ipc_handler(move |req| {
-                let url = req.uri().to_string();
                 let body = req.body();
-                if let (Ok(c_body), Ok(c_url)) = (CString::new(body.as_str()), CString::new(url)) {
-                    cb(c_body.as_ptr(), c_url.as_ptr(), ctx as *mut c_void);
+                if hit_test {
+                    if let Some(rest) = body.strip_prefix(HIT_TEST_IPC_PREFIX) {
+                        if let Some((x, y, clicks)) = parse_hit_test_message(rest) {
+                            log_err!(
+                                proxy.send_event(UserEvent::HitTest { window_id: our_id, x, y, clicks }),
+                                "hit test"
+                            );
+                        }
+                        return;
+                    }
+                }
+                if app_region_shim {
+                    if let Some(rest) = body.strip_prefix(APP_REGION_DRAG_IPC_PREFIX) {
+                        if let Ok(clicks) = rest.trim().parse::<c_int>() {
+                            log_err!(
+                                proxy.send_event(UserEvent::AppRegionDrag { window_id: our_id, clicks }),
+                                "app region drag"
+                            );
+                        }
+                        return;
+                    }
+                }
+                if favicon_shim {
+                    if let Some(rest) = body.strip_prefix(FAVICON_IPC_PREFIX) {
+                        let png = decode_data_url(rest).and_then(|data| favicon_to_png(&data));
+                        log_err!(
+                            proxy.send_event(UserEvent::FaviconChanged { window_id: our_id, png }),
+                            "favicon changed"
+                        );
+                        return;
+                    }
+                }
+                if let Some((cb, ctx)) = console_handler {
+                    if let Some(rest) = body.strip_prefix(CONSOLE_IPC_PREFIX) {
+                        if let Some((level, message, source, line)) = parse_console_message(rest) {
+                            let message = CString::new(message).unwrap_or_default();
+                            let source = CString::new(source).unwrap_or_default();
+                            cb(level, message.as_ptr(), source.as_ptr(), line, ctx as *mut c_void);
+                        }
+                        return;
+                    }
+                }
+                if html_fullscreen_shim {
+                    if let Some(rest) = body.strip_prefix(HTML_FULLSCREEN_IPC_PREFIX) {
+                        let entered = rest.trim() == "1";
+                        log_err!(
+                            proxy.send_event(UserEvent::HtmlFullscreen { window_id: our_id, entered }),
+                            "html fullscreen"
+                        );
+                        return;
+                    }
+                }
+                if let Some((cb, ctx)) = user_handler {
+                    let url = req.uri().to_string();
+                    if let (Ok(c_body), Ok(c_url)) = (CString::new(body.as_str()), CString::new(url)) {
+                        cb(c_body.as_ptr(), c_url.as_ptr(), ctx as *mut c_void);
+                    }
                 }
             });
         }
@@ -851,17 +1974,74 @@ impl WryWindow {
             });
         }
 
-        // Page load handler (from payload - baked into webview at creation)
-        if let Some((cb, ctx)) = payload.page_load_handler {
+        // Page load handler (from payload - baked into webview at creation).
+        // Also posts FirstPaint to the loop when the first page load finishes.
+        // Page load handler: always installed, the loop re-applies per-page settings on Finished.
+        if let Ok(mut state) = self.load_state.lock() {
+            state.loading = payload.url.is_some() || payload.html.is_some();
+        }
+        {
             use wry::PageLoadEvent;
+            let user_handler = payload.page_load_handler;
+            let load_state = self.load_state.clone();
+            let reachable_frames = self.reachable_frames.clone();
+            let first_paint_sent = std::cell::Cell::new(!notify_first_paint);
+            let proxy = proxy.clone();
+            let our_id = self.id;
             wvb = wvb.with_on_page_load_handler(move |event, url| {
                 let event_code: c_int = match event {
                     PageLoadEvent::Started => 0,
                     PageLoadEvent::Finished => 1,
                 };
-                if let Ok(c_url) = CString::new(url.as_str()) {
-                    cb(event_code, c_url.as_ptr(), ctx as *mut c_void);
+                if let Ok(mut state) = load_state.lock() {
+                    if !state.native_outcome {
+                        if event_code == 0 {
+                            state.started();
+                        } else {
+                            state.finished(true);
+                        }
+                    }
+                }
+                if event_code == 0 {
+                    if let Ok(mut frames) = reachable_frames.lock() {
+                        frames.clear();
+                    }
+                }
+                if event_code == 1 && !first_paint_sent.replace(true) {
+                    log_err!(proxy.send_event(UserEvent::FirstPaint { window_id: our_id }), "first paint");
                 }
+                if event_code == 1 {
+                    log_err!(
+                        proxy.send_event(UserEvent::PageLoaded { window_id: our_id, url: url.clone() }),
+                        "page loaded"
+                    );
+                }
+                if let Some((cb, ctx)) = user_handler {
+                    if let Ok(c_url) = CString::new(url.as_str()) {
+                        cb(event_code, c_url.as_ptr(), ctx as *mut c_void);
+                    }
+                }
+            });
+        }
+
+        // Document title handler: forwarded to the loop, which applies auto_title and notifies.
+        if payload.title_changed_handler.is_some() || payload.auto_title {
+            let proxy = proxy.clone();
+            let our_id = self.id;
+            wvb = wvb.with_document_title_changed_handler(move |title| {
+                log_err!(
+                    proxy.send_event(UserEvent::DocumentTitleChanged { window_id: our_id, title }),
+                    "document title changed"
+                );
+            });
+        }
+
+        // macOS: web content process termination (Windows / Linux are hooked after build).
+        #[cfg(target_os = "macos")]
+        if let Some((cb, ctx)) = payload.webview_crash_handler {
+            use wry::WebViewBuilderExtDarwin;
+            wvb = wvb.with_on_web_content_process_terminate_handler(move || {
+                cb(1, ctx as *mut c_void);
             });
         }
 
@@ -943,1882 +2123,8316 @@ impl WryWindow {
             });
         }
 
+        let window = self.window.as_ref().ok_or("window not created")?;
         let webview = wvb
-            .build(&window)
+            .build(window)
             .map_err(|e| e.to_string())?;
 
-        // Apply zoom if not default
-        if (payload.zoom - 1.0).abs() > f64::EPSILON {
-            log_err!(webview.zoom(payload.zoom), "zoom (init)");
+        #[cfg(target_os = "linux")]
+        register_scheme_security(&webview, &payload.protocols);
+        #[cfg(target_os = "linux")]
+        if let Some(ref language) = payload.language {
+            use webkit2gtk::{WebContextExt, WebViewExt};
+            use wry::WebViewExtUnix;
+            if let Some(context) = webview.webview().context() {
+                context.set_preferred_languages(&[language.as_str()]);
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if !payload.cache_enabled {
+            use webkit2gtk::{CacheModel, WebContextExt, WebViewExt};
+            use wry::WebViewExtUnix;
+            if let Some(context) = webview.webview().context() {
+                context.set_cache_model(CacheModel::DocumentViewer);
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if payload.favicon_changed_handler.is_some() {
+            watch_favicon(&webview, proxy.clone(), self.id);
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(handler) = payload.mouse_wheel_handler {
+            watch_wheel(&webview, handler);
+        }
+        if defer_load {
+            for cookie in &payload.initial_cookies {
+                log_err!(webview.set_cookie(cookie), "initial cookie");
+            }
+            if let Some(ref url) = payload.url {
+                log_err!(webview.load_url(url), "load_url");
+            } else if let Some(ref html) = payload.html {
+                log_err!(webview.load_html(html), "load_html");
+            }
         }
 
-        self.window_id = Some(window.id());
-        self.window = Some(window);
-        self.webview = Some(webview);
-        self.close_handler = payload.close_handler;
-        self.resize_handler = payload.resize_handler;
-        self.move_handler = payload.move_handler;
-        self.focus_handler = payload.focus_handler;
-
-        if payload.minimized {
-            if let Some(ref w) = self.window {
-                w.set_minimized(true);
+        match watch_load_outcome(&webview, self.load_state.clone()) {
+            Ok(native) => {
+                if let Ok(mut state) = self.load_state.lock() {
+                    state.native_outcome = native;
+                }
             }
+            Err(e) => eprintln!("[wry-native] load outcome: {}", e),
         }
-        Ok(())
-    }
-}
 
-// ---------------------------------------------------------------------------
-// WryApp -- application-level state
-// ---------------------------------------------------------------------------
+        #[cfg(target_os = "windows")]
+        if payload.app_region_drag {
+            log_err!(enable_non_client_region_support(&webview), "app region drag");
+        }
 
-pub struct WryApp {
-    event_loop: Option<EventLoop<UserEvent>>,
-    pub(crate) proxy: EventLoopProxy<UserEvent>,
-    windows: HashMap<usize, WryWindow>,
-    payloads: HashMap<usize, WindowCreatePayload>,
-    next_window_id: usize,
-    pub(crate) trays: HashMap<usize, WryTray>,
-    pub(crate) tray_payloads: HashMap<usize, tray::TrayCreatePayload>,
-    pub(crate) next_tray_id: usize,
-    exit_requested_handler: Option<(ExitRequestedCallback, usize)>,
-    /// Set to true when the event loop is running (inside run_return). Used to decide initial vs dynamic window creation.
-    run_started: Arc<AtomicBool>,
-    /// Called when a window is materialized and live (initial or dynamic).
-    window_created_handler: Option<(WindowCreatedCallback, usize)>,
-    /// Called when dynamic window creation fails (async path only).
-    window_creation_error_handler: Option<(WindowCreationErrorCallback, usize)>,
-    window_destroyed_handler: Option<(WindowDestroyedCallback, usize)>,
-}
+        #[cfg(target_os = "windows")]
+        if payload.favicon_changed_handler.is_some() {
+            log_err!(watch_favicon(&webview, proxy.clone(), self.id), "favicon changed");
+        }
 
-// Safety: WryApp is only accessed from the main thread. The proxy field is
-// Send by design (it's the whole point of EventLoopProxy). We need this
-// because the raw pointer in wry_window_dispatch comes from any thread but
-// only accesses the proxy.
-unsafe impl Send for WryApp {}
-unsafe impl Sync for WryApp {}
+        if html_fullscreen {
+            watch_html_fullscreen(&webview, proxy.clone(), self.id);
+        }
 
-// ---------------------------------------------------------------------------
-// Helper: read a C string into a Rust String, returning empty on null.
-// ---------------------------------------------------------------------------
+        #[cfg(target_os = "windows")]
+        if let Some(handler) = payload.webview_crash_handler {
+            log_err!(watch_process_failed(&webview, handler), "webview crash handler");
+        }
+        #[cfg(target_os = "linux")]
+        if let Some((cb, ctx)) = payload.webview_crash_handler {
+            use webkit2gtk::{WebProcessTerminationReason, WebViewExt};
+            use wry::WebViewExtUnix;
+            webview.webview().connect_web_process_terminated(move |_, reason| {
+                let code = match reason {
+                    WebProcessTerminationReason::Crashed => 1,
+                    WebProcessTerminationReason::ExceededMemoryLimit => 2,
+                    WebProcessTerminationReason::TerminatedByApi => 5,
+                    _ => 0,
+                };
+                cb(code, ctx as *mut c_void);
+            });
+        }
 
-pub(crate) unsafe fn c_str_to_string(s: *const c_char) -> String {
-    if s.is_null() {
-        return String::new();
-    }
-    CStr::from_ptr(s)
-        .to_str()
-        .unwrap_or("")
-        .to_string()
-}
+        // Windows: the controller's default background, shown until the page paints (the window
+        // behind it already has the color, see create()).
+        #[cfg(target_os = "windows")]
+        if let Some(color) = payload.background_color {
+            log_err!(set_default_background_color(&webview, color), "default background color");
+        }
 
-// ---------------------------------------------------------------------------
-// ===========================================================================
-// EXPORTED C API
-// ===========================================================================
+        #[cfg(target_os = "windows")]
+        if let Some(handler) = payload.responsiveness_handler {
+            log_err!(watch_unresponsive(&webview, handler), "unresponsive handler");
+        }
+        #[cfg(target_os = "linux")]
+        if let Some((cb, ctx)) = payload.responsiveness_handler {
+            use webkit2gtk::glib::prelude::ObjectExt;
+            use wry::WebViewExtUnix;
+            // WebKitGTK 2.34+; looked up by name since the bindings target an older version.
+            const RESPONSIVE: &str = "is-web-process-responsive";
+            let view = webview.webview();
+            if view.find_property(RESPONSIVE).is_some() {
+                view.connect_notify_local(Some(RESPONSIVE), move |view, _| {
+                    cb(view.property::<bool>(RESPONSIVE), ctx as *mut c_void);
+                });
+            } else {
+                eprintln!("[wry-native] unresponsive handler: needs WebKitGTK 2.34 or later");
+            }
+        }
 
-// ---------------------------------------------------------------------------
-// App lifecycle
-// ---------------------------------------------------------------------------
+        #[cfg(target_os = "windows")]
+        if let Some(handler) = payload.script_dialog_handler {
+            log_err!(watch_script_dialogs(&webview, handler), "script dialog handler");
+        }
+        #[cfg(target_os = "linux")]
+        if let Some((cb, ctx)) = payload.script_dialog_handler {
+            use webkit2gtk::{ScriptDialogType, WebViewExt};
+            use wry::WebViewExtUnix;
+            webview.webview().connect_script_dialog(move |_, dialog| {
+                let mut dialog = dialog.clone();
+                let kind = match dialog.dialog_type() {
+                    ScriptDialogType::Confirm => 1,
+                    ScriptDialogType::Prompt => 2,
+                    ScriptDialogType::BeforeUnloadConfirm => 3,
+                    _ => 0,
+                };
+                let message = dialog.message().map(|m| m.to_string()).unwrap_or_default();
+                let default_text = if kind == 2 { dialog.prompt_get_default_text() } else { None };
+                let default_text = default_text.map(|t| t.to_string()).unwrap_or_default();
+                let message = CString::new(message).unwrap_or_default();
+                let default_text = CString::new(default_text).unwrap_or_default();
+                let responder = Box::into_raw(Box::new(ScriptDialogResponder { dialog })) as *mut c_void;
+                cb(kind, message.as_ptr(), default_text.as_ptr(), responder, ctx as *mut c_void);
+                true
+            });
+        }
 
-/// Create a new application. Returns an opaque handle.
-#[no_mangle]
-pub extern "C" fn wry_app_new() -> *mut WryApp {
-    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
-    let proxy = event_loop.create_proxy();
-    let app = WryApp {
-        event_loop: Some(event_loop),
-        proxy,
-        windows: HashMap::new(),
-        payloads: HashMap::new(),
-        next_window_id: 1,
-        trays: HashMap::new(),
-        tray_payloads: HashMap::new(),
-        next_tray_id: 1,
-        exit_requested_handler: None,
-        run_started: Arc::new(AtomicBool::new(false)),
-        window_created_handler: None,
-        window_creation_error_handler: None,
-        window_destroyed_handler: None,
-    };
-    Box::into_raw(Box::new(app))
-}
+        #[cfg(target_os = "windows")]
+        if !payload.devtools_shortcut && payload.browser_accelerator_keys {
+            log_err!(block_devtools_shortcut(&webview), "devtools shortcut");
+        }
 
-/// Run the application event loop. This blocks the calling thread until all
-/// windows are closed. Must be called on the main thread.
-#[no_mangle]
-pub extern "C" fn wry_app_run(app: *mut WryApp) {
-    if app.is_null() {
-        return;
+        // Apply zoom if not default
+        if (payload.zoom - 1.0).abs() > f64::EPSILON {
+            log_err!(webview.zoom(payload.zoom), "zoom (init)");
+        }
+
+        if let Some(enabled) = payload.spellcheck {
+            set_native_spellcheck(&webview, enabled);
+        }
+
+        Ok(webview)
     }
-    let app = unsafe { &mut *app };
 
-    let mut event_loop = match app.event_loop.take() {
-        Some(el) => el,
-        None => return, // already consumed
-    };
+    /// Replace the webview with a fresh one built from the creation payload (wry_window_reset_webview).
+    /// The tao window, its position and state are kept; the new webview loads the configured URL / HTML.
+    fn reset_webview(&mut self) -> Result<(), String> {
+        let (Some(config), Some(proxy)) = (self.config.clone(), self.proxy.clone()) else {
+            return Err("window not created".into());
+        };
+        // Drop the old webview first so the platform detaches its controller / widget.
+        self.webview = None;
+        self.webview = Some(self.build_webview(&config, &proxy, false)?);
+        self.initial_load_done = false;
+        Ok(())
+    }
 
-    let mut pending_windows: Vec<WryWindow> = app.windows.drain().map(|(_, w)| w).collect();
-    let mut pending_payloads: HashMap<usize, WindowCreatePayload> = app.payloads.drain().collect();
-    let mut live_windows: HashMap<WindowId, WryWindow> = HashMap::new();
-    let mut id_to_window_id: HashMap<usize, WindowId> = HashMap::new();
+    /// Show a window created with show_when_ready: on FirstPaint (its first load finished, or
+    /// failed, which also ends in Finished on Windows and Linux) or at the fallback deadline.
+    /// No-op once shown or after an explicit wry_window_set_visible.
+    fn show_when_ready_now(&mut self) {
+        if self.show_when_ready.take().is_none() {
+            return;
+        }
+        if let Some(ref w) = self.window {
+            w.set_visible(true);
+        }
+        #[cfg(target_os = "linux")]
+        self.reapply_skip_taskbar();
+    }
 
-    // Move trays out of the app struct.
-    let mut pending_trays: Vec<WryTray> = app.trays.drain().map(|(_, t)| t).collect();
-    let mut pending_tray_payloads: HashMap<usize, tray::TrayCreatePayload> = app.tray_payloads.drain().collect();
-    let mut live_trays: HashMap<usize, WryTray> = HashMap::new();
+    /// Recompute the tiled edges after a move/resize and notify tiled_handler if they changed.
+    fn update_tiled(&mut self) {
+        let Some((cb, ctx)) = self.tiled_handler else { return; };
+        let Some(ref w) = self.window else { return; };
+        let edges = if w.is_maximized() || w.fullscreen().is_some() || w.is_minimized() {
+            0
+        } else {
+            #[cfg(target_os = "linux")]
+            let reported = gdk_tiled_edges(w);
+            #[cfg(not(target_os = "linux"))]
+            let reported = None;
+            reported.unwrap_or_else(|| match (w.outer_position(), work_area(w)) {
+                (Ok(pos), Some(area)) => {
+                    let size = w.outer_size();
+                    let rect = (pos.x, pos.y, size.width as i32, size.height as i32);
+                    tiled_edges(rect, area, TILED_TOLERANCE)
+                }
+                _ => 0,
+            })
+        };
+        if edges != self.tiled_edges {
+            self.tiled_edges = edges;
+            cb(edges, ctx as *mut c_void);
+        }
+    }
 
-    // Exit-requested callback (fired when all windows are closed).
-    let exit_requested_handler = app.exit_requested_handler.take();
-    let window_created_handler = app.window_created_handler.take();
-    let window_creation_error_handler = app.window_creation_error_handler.take();
-    let window_destroyed_handler = app.window_destroyed_handler.take();
+    /// Cap (or uncap) the page's frame rate when the window loses (or regains) focus, if a
+    /// background frame rate is set, and notify background_throttle_handler.
+    fn set_background_throttled(&mut self, throttled: bool) {
+        if self.background_fps.is_none() || throttled == self.background_throttled {
+            return;
+        }
+        self.background_throttled = throttled;
+        self.apply_background_fps();
+        if let Some((cb, ctx)) = self.background_throttle_handler {
+            cb(throttled, ctx as *mut c_void);
+        }
+    }
 
-    let run_started = app.run_started.clone();
+    /// Tell the page's BACKGROUND_FPS_SCRIPT the current cap.
+    fn apply_background_fps(&self) {
+        let (Some(fps), Some(wv)) = (self.background_fps, self.webview.as_ref()) else { return; };
+        let arg = if self.background_throttled { fps.to_string() } else { "null".to_owned() };
+        let js = format!("window.__wryBackgroundFps && window.__wryBackgroundFps({});", arg);
+        log_err!(wv.evaluate_script(&js), "background fps");
+    }
 
-    // Wire up tray icon / menu event handlers to forward into the event loop.
-    tray::setup_tray_event_handlers(&app.proxy);
+    /// Run the scripts queued by wry_window_eval_js_batched as one evaluate_script call.
+    fn flush_eval_batch(&mut self) {
+        if self.eval_batch.is_empty() {
+            return;
+        }
+        let scripts = std::mem::take(&mut self.eval_batch);
+        if let Some(ref wv) = self.webview {
+            log_err!(wv.evaluate_script(&eval_batch_script(&scripts)), "evaluate_script (batched)");
+        }
+    }
 
-    // Use run_return so we return to the caller instead of calling process::exit.
-    event_loop.run_return(move |event, event_loop_target, control_flow| {
-        *control_flow = ControlFlow::Wait;
-        run_started.store(true, Ordering::SeqCst);
+    /// After a move, notify monitor_changed_handler with the new monitor's bounds if the window's
+    /// current monitor (the one holding most of it) differs from the last one seen.
+    fn update_monitor(&mut self) {
+        let Some((cb, ctx)) = self.monitor_changed_handler else { return; };
+        let Some(monitor) = self.window.as_ref().and_then(|w| w.current_monitor()) else { return; };
+        if self.monitor.as_ref() == Some(&monitor) {
+            return;
+        }
+        let pos = monitor.position();
+        let size = monitor.size();
+        cb(
+            pos.x as c_int,
+            pos.y as c_int,
+            size.width as c_int,
+            size.height as c_int,
+            monitor.scale_factor(),
+            ctx as *mut c_void,
+        );
+        self.monitor = Some(monitor);
+    }
+
+    /// Edge snapping: after a move, pull the window flush with work-area edges or the edges of
+    /// `siblings` (other windows' outer rects) within the threshold. The corrected position
+    /// produces another Moved event, which is then a no-op.
+    fn apply_edge_snapping(&self, siblings: &[(i32, i32, i32, i32)]) {
+        let Some(threshold) = self.snap_threshold else { return; };
+        let Some(ref w) = self.window else { return; };
+        if w.is_maximized() || w.fullscreen().is_some() || w.is_minimized() {
+            return;
+        }
+        let (Ok(pos), Some(area)) = (w.outer_position(), work_area(w)) else { return; };
+        let size = w.outer_size();
+        let rect = (pos.x, pos.y, size.width as i32, size.height as i32);
+        let threshold = (threshold * w.scale_factor()).round() as i32;
+        let (x, y) = compute_snap_position(rect, siblings, area, threshold);
+        if (x, y) != (pos.x, pos.y) {
+            w.set_outer_position(tao::dpi::PhysicalPosition::new(x, y));
+        }
+    }
 
-        match event {
-            Event::NewEvents(StartCause::Init) => {
-                pending_windows.sort_by_key(|w| w.id);
-                for mut win in pending_windows.drain(..) {
-                    let payload = match pending_payloads.remove(&win.id) {
-                        Some(p) => p,
-                        None => continue,
-                    };
-                    let owner_window = payload.owner_window_id.and_then(|oid| {
-                        id_to_window_id.get(&oid).and_then(|tid| live_windows.get(tid))
-                            .and_then(|w| w.window.as_ref())
-                    });
-                    let parent_window = payload.parent_window_id.and_then(|pid| {
-                        id_to_window_id.get(&pid).and_then(|tid| live_windows.get(tid))
-                            .and_then(|w| w.window.as_ref())
-                    });
-                    match win.create(&payload, event_loop_target, owner_window, parent_window) {
-                        Ok(()) => {
-                            if let Some(wid) = win.window_id {
-                                let our_id = win.id;
-                                id_to_window_id.insert(our_id, wid);
-                                live_windows.insert(wid, win);
-                                if let Some((cb, ctx)) = window_created_handler.as_ref() {
-                                    if let Some(win_ref) = live_windows.get_mut(&wid) {
-                                        cb(*ctx as *mut c_void, our_id, win_ref as *mut WryWindow);
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            let our_id = win.id;
-                            if let Some((cb, ctx)) = window_creation_error_handler.as_ref() {
-                                if let Ok(c_msg) = CString::new(e.as_str()) {
-                                    cb(*ctx as *mut c_void, our_id, c_msg.as_ptr());
-                                }
-                            }
-                        }
-                    }
+    /// Outer rect (x, y, width, height) in physical pixels of a window other windows can snap to:
+    /// visible, not minimized, maximized or fullscreen.
+    fn snap_target_rect(&self) -> Option<(i32, i32, i32, i32)> {
+        let w = self.window.as_ref()?;
+        if !w.is_visible() || w.is_minimized() || w.is_maximized() || w.fullscreen().is_some() {
+            return None;
+        }
+        let pos = w.outer_position().ok()?;
+        let size = w.outer_size();
+        Some((pos.x, pos.y, size.width as i32, size.height as i32))
+    }
+
+    /// Ask hit_test_handler what lies under a mouse down in the webview and start the
+    /// matching move / resize. Double-clicking the caption toggles maximize.
+    fn apply_hit_test(&self, x: c_int, y: c_int, clicks: c_int) {
+        let Some((cb, ctx)) = self.hit_test_handler else { return; };
+        let Some(ref w) = self.window else { return; };
+        let result = cb(x, y, ctx as *mut c_void);
+        if result == HIT_TEST_CAPTION {
+            self.caption_mouse_down(clicks);
+        } else if let Some(direction) = hit_test_resize_direction(result) {
+            log_err!(w.drag_resize_window(direction), "drag resize window");
+        }
+    }
+
+    /// Mouse down on a caption / drag region: start moving the window, or toggle maximize
+    /// on a double click.
+    fn caption_mouse_down(&self, clicks: c_int) {
+        let Some(ref w) = self.window else { return; };
+        if clicks >= 2 && clicks % 2 == 0 {
+            if w.is_resizable() && w.is_maximizable() {
+                w.set_maximized(!w.is_maximized());
+            }
+        } else {
+            log_err!(w.drag_window(), "drag window");
+        }
+    }
+
+    /// Show `count` as a badge (0 clears): macOS dock badge, Linux launcher count (Unity API),
+    /// Windows taskbar overlay icon with the number rendered into it.
+    fn apply_badge_count(&self, count: i64) {
+        let Some(ref w) = self.window else { return; };
+        let count = count.max(0);
+        #[cfg(target_os = "windows")]
+        {
+            use tao::platform::windows::WindowExtWindows;
+            if count == 0 {
+                w.set_overlay_icon(None);
+            } else {
+                match Icon::from_rgba(render_badge_icon(count), BADGE_ICON_SIZE, BADGE_ICON_SIZE) {
+                    Ok(icon) => w.set_overlay_icon(Some(&icon)),
+                    Err(e) => eprintln!("[wry-native] badge count: Icon::from_rgba failed: {}", e),
                 }
-                // Materialize all pending tray icons.
-                for mut tray in pending_trays.drain(..) {
-                    let our_id = tray.id;
-                    if let Some(payload) = pending_tray_payloads.remove(&our_id) {
-                        tray.create(&payload);
-                    }
-                    live_trays.insert(our_id, tray);
+            }
+        }
+        #[cfg(target_os = "macos")]
+        {
+            use tao::platform::macos::WindowExtMacOS;
+            w.set_badge_label(if count == 0 { None } else { Some(count.to_string()) });
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use tao::platform::unix::WindowExtUnix;
+            w.set_badge_count(if count == 0 { None } else { Some(count) }, None);
+        }
+    }
+
+    /// A page finished loading: restore persisted zoom and re-apply the spellcheck override.
+    fn page_loaded(&mut self, url: &str) {
+        self.restore_zoom(url);
+        if let (Some(enabled), Some(wv)) = (self.spellcheck, self.webview.as_ref()) {
+            log_err!(wv.evaluate_script(&spellcheck_script(enabled)), "spellcheck");
+        }
+        // A new document starts unthrottled.
+        if self.background_throttled {
+            self.apply_background_fps();
+        }
+        if !std::mem::replace(&mut self.initial_load_done, true) {
+            if let Some((cb, ctx)) = self.initial_load_handler {
+                if let Ok(c_url) = CString::new(url) {
+                    cb(c_url.as_ptr(), ctx as *mut c_void);
                 }
             }
+        }
+    }
 
-            Event::WindowEvent {
-                event: ref win_event,
-                window_id,
-                ..
-            } => {
-                if let Some(win) = live_windows.get_mut(&window_id) {
-                    match win_event {
-                        WindowEvent::CloseRequested => {
-                            let allow = if let Some((cb, ctx)) = win.close_handler {
-                                cb(ctx as *mut c_void)
-                            } else {
-                                true
-                            };
-                            if allow {
-                                let our_id = win.id;
-                                id_to_window_id.remove(&our_id);
-                                live_windows.remove(&window_id);
-                                if live_windows.is_empty() {
-                                    let should_exit = if let Some((cb, ctx)) = exit_requested_handler {
-                                        cb(false, 0, ctx as *mut c_void)
-                                    } else {
-                                        true
-                                    };
-                                    if should_exit {
-                                        live_trays.clear();
-                                        *control_flow = ControlFlow::Exit;
-                                    }
-                                }
-                            }
-                        }
-                        WindowEvent::Destroyed => {
-                            // Window was destroyed (e.g. by OS when owner closed). Notify C#, then remove from state like Tauri.
-                            let our_id = live_windows.get(&window_id).map(|w| w.id);
-                            if let Some(oid) = our_id {
-                                if let Some((cb, ctx)) = window_destroyed_handler.as_ref() {
-                                    cb(*ctx as *mut c_void, oid);
-                                }
-                                id_to_window_id.remove(&oid);
-                                live_windows.remove(&window_id);
-                                if live_windows.is_empty() {
-                                    let should_exit = if let Some((cb, ctx)) = exit_requested_handler {
-                                        cb(false, 0, ctx as *mut c_void)
-                                    } else {
-                                        true
-                                    };
-                                    if should_exit {
-                                        live_trays.clear();
-                                        *control_flow = ControlFlow::Exit;
-                                    }
-                                }
-                            }
-                        }
-                        WindowEvent::Resized(size) => {
-                            if let Some((cb, ctx)) = win.resize_handler {
-                                cb(
-                                    size.width as c_int,
-                                    size.height as c_int,
-                                    ctx as *mut c_void,
-                                );
-                            }
-                        }
-                        WindowEvent::Moved(pos) => {
-                            if let Some((cb, ctx)) = win.move_handler {
-                                cb(pos.x as c_int, pos.y as c_int, ctx as *mut c_void);
-                            }
-                        }
-                        WindowEvent::Focused(focused) => {
-                            if let Some((cb, ctx)) = win.focus_handler {
-                                cb(*focused, ctx as *mut c_void);
-                            }
-                        }
-                        _ => {}
-                    }
-                }
+    /// The page entered or left HTML fullscreen: follow with the window if asked, then notify.
+    fn html_fullscreen_changed(&self, entered: bool) {
+        if self.html_fullscreen_window && !cfg!(target_os = "macos") {
+            if let Some(ref w) = self.window {
+                w.set_fullscreen(if entered { Some(Fullscreen::Borderless(None)) } else { None });
             }
+        }
+        if let Some((cb, ctx)) = self.html_fullscreen_handler {
+            cb(entered, ctx as *mut c_void);
+        }
+    }
 
-            Event::UserEvent(user_event) => match user_event {
-                UserEvent::Dispatch {
-                    window_id: our_id,
-                    callback,
-                    ctx,
-                } => {
-                    let mut destroyed_wid = None;
-                    if let Some(wid) = id_to_window_id.get(&our_id).copied() {
-                        if let Some(win) = live_windows.get_mut(&wid) {
-                            let win_ptr = win as *mut WryWindow;
-                            callback(win_ptr, ctx as *mut c_void);
-                            // If the callback destroyed the window (e.g. wry_window_close),
-                            // clean up live_windows so the exit check works.
-                            if win.window.is_none() {
-                                destroyed_wid = Some(wid);
-                            }
-                        }
-                    }
-                    if let Some(wid) = destroyed_wid {
-                        live_windows.remove(&wid);
-                        if live_windows.is_empty() {
-                            let should_exit = if let Some((cb, ctx)) = exit_requested_handler {
-                                cb(false, 0, ctx as *mut c_void)
-                            } else {
-                                true
-                            };
-                            if should_exit {
-                                live_trays.clear();
-                                *control_flow = ControlFlow::Exit;
-                            }
-                        }
-                    }
-                }
+    /// Load `url` and report its outcome to `reply` (see wry_window_navigate_and_wait).
+    fn navigate_and_notify(&self, url: &str, reply: std::sync::mpsc::Sender<bool>) {
+        let Some(ref wv) = self.webview else { return; };
+        let Ok(mut state) = self.load_state.lock() else { return; };
+        state.waiters.push((reply, false));
+        drop(state);
+        if let Err(e) = wv.load_url(url) {
+            eprintln!("[wry-native] navigate_and_wait: load_url failed: {}", e);
+            if let Some((reply, _)) = self.load_state.lock().ok().and_then(|mut s| s.waiters.pop()) {
+                let _ = reply.send(false);
+            }
+        }
+    }
 
-                UserEvent::TrayEvent(ref event) => {
-                    if let Ok(our_id) = event.id().as_ref().parse::<usize>() {
-                        if let Some(t) = live_trays.get(&our_id) {
-                            t.handle_tray_event(event);
+    /// Zoom persistence: apply the remembered zoom for `url`'s origin, or the default zoom.
+    fn restore_zoom(&self, url: &str) {
+        let (Some(store), Some(wv)) = (self.zoom_store.as_ref(), self.webview.as_ref()) else { return; };
+        let Some(origin) = url_origin(url) else { return; };
+        let zoom = store.lock().ok().and_then(|m| m.get(&origin).copied()).unwrap_or(self.default_zoom);
+        log_err!(wv.zoom(zoom), "restore zoom");
+    }
+
+    /// macOS: replace the vibrancy view (see wry_window_set_vibrancy). 0 removes it.
+    #[cfg(target_os = "macos")]
+    fn set_vibrancy(&mut self, material: c_int) {
+        use objc2::MainThreadMarker;
+        use objc2_app_kit::{
+            NSAutoresizingMaskOptions, NSView, NSVisualEffectBlendingMode, NSVisualEffectMaterial,
+            NSVisualEffectState, NSVisualEffectView, NSWindowOrderingMode,
+        };
+        use tao::platform::macos::WindowExtMacOS;
+
+        if let Some(view) = self.vibrancy_view.take() {
+            view.removeFromSuperview();
+        }
+        let material = match material {
+            1 => NSVisualEffectMaterial::Titlebar,
+            2 => NSVisualEffectMaterial::Selection,
+            3 => NSVisualEffectMaterial::Menu,
+            4 => NSVisualEffectMaterial::Popover,
+            5 => NSVisualEffectMaterial::Sidebar,
+            6 => NSVisualEffectMaterial::HeaderView,
+            7 => NSVisualEffectMaterial::Sheet,
+            8 => NSVisualEffectMaterial::WindowBackground,
+            9 => NSVisualEffectMaterial::HUDWindow,
+            10 => NSVisualEffectMaterial::FullScreenUI,
+            11 => NSVisualEffectMaterial::ToolTip,
+            12 => NSVisualEffectMaterial::ContentBackground,
+            13 => NSVisualEffectMaterial::UnderWindowBackground,
+            14 => NSVisualEffectMaterial::UnderPageBackground,
+            _ => return,
+        };
+        let (Some(w), Some(mtm)) = (self.window.as_ref(), MainThreadMarker::new()) else { return; };
+        let content = unsafe { &*(w.ns_view() as *const NSView) };
+        let effect = NSVisualEffectView::initWithFrame(NSVisualEffectView::alloc(mtm), content.bounds());
+        effect.setMaterial(material);
+        effect.setBlendingMode(NSVisualEffectBlendingMode::BehindWindow);
+        effect.setState(NSVisualEffectState::Active);
+        effect.setAutoresizingMask(
+            NSAutoresizingMaskOptions::ViewWidthSizable | NSAutoresizingMaskOptions::ViewHeightSizable,
+        );
+        content.addSubview_positioned_relativeTo(&effect, NSWindowOrderingMode::Below, None);
+        self.vibrancy_view = Some(effect);
+    }
+
+    /// Apply or undo the kiosk preset (see wry_window_set_kiosk_mode).
+    fn set_kiosk_mode(&mut self, enabled: bool) {
+        let Some(ref w) = self.window else { return; };
+        if enabled {
+            if self.kiosk.is_some() {
+                return;
+            }
+            self.kiosk = Some(KioskSaved {
+                fullscreen: w.fullscreen().is_some(),
+                decorations: w.is_decorated(),
+                topmost: w.is_always_on_top(),
+                resizable: w.is_resizable(),
+                minimizable: w.is_minimizable(),
+                maximizable: w.is_maximizable(),
+                closable: w.is_closable(),
+                #[cfg(target_os = "windows")]
+                webview_settings: self.webview.as_ref().and_then(|wv| {
+                    let prev = webview_lockdown(wv, None);
+                    log_err!(prev.as_ref(), "kiosk mode: webview settings");
+                    prev.ok()
+                }),
+            });
+            w.set_decorations(false);
+            w.set_fullscreen(Some(Fullscreen::Borderless(None)));
+            w.set_always_on_top(true);
+            w.set_resizable(false);
+            w.set_minimizable(false);
+            w.set_maximizable(false);
+            w.set_closable(false);
+            #[cfg(any(debug_assertions, feature = "devtools"))]
+            if let Some(ref wv) = self.webview {
+                wv.close_devtools();
+            }
+        } else if let Some(saved) = self.kiosk.take() {
+            w.set_fullscreen(if saved.fullscreen { Some(Fullscreen::Borderless(None)) } else { None });
+            w.set_decorations(saved.decorations);
+            w.set_always_on_top(saved.topmost);
+            w.set_resizable(saved.resizable);
+            w.set_minimizable(saved.minimizable);
+            w.set_maximizable(saved.maximizable);
+            w.set_closable(saved.closable);
+            #[cfg(target_os = "windows")]
+            if let (Some(wv), Some(prev)) = (self.webview.as_ref(), saved.webview_settings) {
+                log_err!(webview_lockdown(wv, Some(prev)), "kiosk mode: restore webview settings");
+            }
+        }
+    }
+
+    /// Make the window visible, restore it if minimized and give it focus.
+    fn show_and_focus(&mut self) {
+        if let Some(ref w) = self.window {
+            w.set_visible(true);
+            if w.is_minimized() {
+                w.set_minimized(false);
+            }
+            w.set_focus();
+        }
+        #[cfg(target_os = "linux")]
+        self.reapply_skip_taskbar();
+    }
+
+    /// Linux: re-apply a pending skip-taskbar hint. Called on the first window event after
+    /// creation (the GTK window is mapped by then) and when the window is shown.
+    #[cfg(target_os = "linux")]
+    fn reapply_skip_taskbar(&mut self) {
+        if !self.skip_taskbar_reapply {
+            return;
+        }
+        self.skip_taskbar_reapply = false;
+        if let Some(ref w) = self.window {
+            use tao::platform::unix::WindowExtUnix;
+            let _ = w.set_skip_taskbar(self.skip_taskbar);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Event-loop timers
+// ---------------------------------------------------------------------------
+
+/// A repeating timer owned by the event loop (see wry_app_set_interval).
+struct AppTimer {
+    interval: Duration,
+    next_due: Instant,
+    callback: TimerCallback,
+    ctx: usize,
+}
+
+// ---------------------------------------------------------------------------
+// Tiled (snapped) window detection
+// ---------------------------------------------------------------------------
+
+/// How long a show_when_ready window waits for its first page load before it is shown anyway,
+/// so a load that hangs or a renderer that dies (macOS also reports no failed loads) does not
+/// leave the app without a window.
+const SHOW_WHEN_READY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Edge slack in physical pixels. Covers the invisible resize borders Windows 10/11 add
+/// around the outer frame (~7px at 100%) and rounding from fractional scale factors.
+const TILED_TOLERANCE: i32 = 16;
+
+/// Usable area of the monitor the window is on, in physical pixels: (x, y, width, height).
+/// Windows: monitor work area (excludes the taskbar). Other platforms: full monitor bounds,
+/// since tao does not expose the work area there.
+fn work_area(w: &Window) -> Option<(i32, i32, i32, i32)> {
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::WindowExtWindows;
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+        unsafe {
+            let hmon = MonitorFromWindow(HWND(w.hwnd() as _), MONITOR_DEFAULTTONEAREST);
+            let mut mi = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            if GetMonitorInfoW(hmon, &mut mi).as_bool() {
+                let r = mi.rcWork;
+                return Some((r.left, r.top, r.right - r.left, r.bottom - r.top));
+            }
+        }
+    }
+    let monitor = w.current_monitor()?;
+    let pos = monitor.position();
+    let size = monitor.size();
+    Some((pos.x, pos.y, size.width as i32, size.height as i32))
+}
+
+/// Linux: tiled edges as reported by the window manager in the GDK window state. None when the
+/// window is not realized yet, or is tiled without per-edge detail (plain `TILED`), in which
+/// case the geometry heuristic decides.
+#[cfg(target_os = "linux")]
+fn gdk_tiled_edges(w: &Window) -> Option<c_int> {
+    use gtk::gdk::WindowState;
+    use gtk::prelude::WidgetExt;
+    use tao::platform::unix::WindowExtUnix;
+    let state = w.gtk_window().window()?.state();
+    let mut edges = 0;
+    if state.contains(WindowState::LEFT_TILED) { edges |= 1; }
+    if state.contains(WindowState::TOP_TILED) { edges |= 2; }
+    if state.contains(WindowState::RIGHT_TILED) { edges |= 4; }
+    if state.contains(WindowState::BOTTOM_TILED) { edges |= 8; }
+    if edges == 0 && state.contains(WindowState::TILED) {
+        return None;
+    }
+    Some(edges)
+}
+
+/// Best-effort snap detection from geometry alone. Returns the bitmask of work-area edges
+/// (1 = Left, 2 = Top, 4 = Right, 8 = Bottom) the window is flush with, but only when the
+/// layout looks like a snap: spanning the full height or width of the area (half snap), or
+/// sitting in a corner at half width and half height (quarter snap). Otherwise returns 0.
+fn tiled_edges(win: (i32, i32, i32, i32), area: (i32, i32, i32, i32), tol: i32) -> c_int {
+    let (wx, wy, ww, wh) = win;
+    let (ax, ay, aw, ah) = area;
+    let near = |a: i32, b: i32| (a - b).abs() <= tol;
+
+    let left = near(wx, ax);
+    let top = near(wy, ay);
+    let right = near(wx + ww, ax + aw);
+    let bottom = near(wy + wh, ay + ah);
+
+    let full_height = top && bottom;
+    let full_width = left && right;
+    if full_height && full_width {
+        return 0; // covers the whole area: maximized-like, not a snap
+    }
+    let half_w = near(ww, aw / 2);
+    let half_h = near(wh, ah / 2);
+    let quarter = (left || right) && (top || bottom) && half_w && half_h;
+    if !(full_height && (left || right) || full_width && (top || bottom) || quarter) {
+        return 0;
+    }
+
+    let mut edges = 0;
+    if left { edges |= 1; }
+    if top { edges |= 2; }
+    if right { edges |= 4; }
+    if bottom { edges |= 8; }
+    edges
+}
+
+/// Edge snapping: the (x, y) for `moving` (x, y, width, height) after snapping each axis to the
+/// nearest candidate within `threshold`, or unchanged if there is none. Candidates are the edges
+/// of `monitor_bounds` and, for each of `other_rects` alongside the window on the other axis
+/// (within `threshold`), docking against it or aligning with its edges. Ties go to the monitor.
+fn compute_snap_position(
+    moving: (i32, i32, i32, i32),
+    other_rects: &[(i32, i32, i32, i32)],
+    monitor_bounds: (i32, i32, i32, i32),
+    threshold: i32,
+) -> (i32, i32) {
+    let (x, y, w, h) = moving;
+    let (bx, by, bw, bh) = monitor_bounds;
+    let alongside = |pos: i32, len: i32, other: i32, other_len: i32| {
+        pos < other + other_len + threshold && other < pos + len + threshold
+    };
+    let mut xs = vec![bx, bx + bw - w];
+    let mut ys = vec![by, by + bh - h];
+    for &(ox, oy, ow, oh) in other_rects {
+        if alongside(y, h, oy, oh) {
+            xs.extend([ox + ow, ox - w, ox, ox + ow - w]);
+        }
+        if alongside(x, w, ox, ow) {
+            ys.extend([oy + oh, oy - h, oy, oy + oh - h]);
+        }
+    }
+    let nearest = |pos: i32, candidates: &[i32]| {
+        candidates
+            .iter()
+            .copied()
+            .filter(|c| (c - pos).abs() <= threshold)
+            .min_by_key(|c| (c - pos).abs())
+            .unwrap_or(pos)
+    };
+    (nearest(x, &xs), nearest(y, &ys))
+}
+
+/// What made the event loop check whether to exit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ExitTrigger {
+    /// A window was closed or destroyed.
+    WindowClosed,
+    /// A tray was removed (wry_tray_remove).
+    TrayRemoved,
+}
+
+/// Exit decision of the event loop, given the windows and trays still alive. Nothing alive
+/// always exits. Closing the last window also exits while trays remain if
+/// exit_on_last_window_closed is set (the default). Removing a tray never exits while windows or
+/// other trays remain, whatever that setting, so a tray-only app ends with its last tray.
+fn should_exit(trigger: ExitTrigger, window_count: usize, tray_count: usize, exit_on_last_window_closed: bool) -> bool {
+    if window_count > 0 {
+        return false;
+    }
+    tray_count == 0 || (trigger == ExitTrigger::WindowClosed && exit_on_last_window_closed)
+}
+
+/// Side length of the Windows taskbar overlay badge icon.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const BADGE_ICON_SIZE: u32 = 16;
+
+/// 3x5 bitmap glyphs for badge digits (one row per entry, bit 2 = leftmost column) and '+'.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const BADGE_GLYPHS: [[u8; 5]; 11] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+    [0b000, 0b010, 0b111, 0b010, 0b000],
+];
+
+/// Badge text fitting two glyphs: "1".."99", "9+" above that.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn badge_text(count: i64) -> String {
+    if count > 99 { "9+".to_string() } else { count.max(0).to_string() }
+}
+
+/// Render a badge count as BADGE_ICON_SIZE² RGBA: white glyphs (2x scale) on a red disc.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn render_badge_icon(count: i64) -> Vec<u8> {
+    const SCALE: usize = 2;
+    let size = BADGE_ICON_SIZE as usize;
+    let mut rgba = vec![0u8; size * size * 4];
+    let center = (size as f64 - 1.0) / 2.0;
+    let radius = size as f64 / 2.0;
+    for y in 0..size {
+        for x in 0..size {
+            let (dx, dy) = (x as f64 - center, y as f64 - center);
+            if dx * dx + dy * dy <= radius * radius {
+                rgba[(y * size + x) * 4..][..4].copy_from_slice(&[220, 38, 38, 255]);
+            }
+        }
+    }
+    let glyphs: Vec<usize> = badge_text(count)
+        .chars()
+        .map(|c| c.to_digit(10).map(|d| d as usize).unwrap_or(10))
+        .collect();
+    let width = glyphs.len() * 3 * SCALE + glyphs.len().saturating_sub(1);
+    let x0 = size.saturating_sub(width) / 2;
+    let y0 = (size - 5 * SCALE) / 2;
+    for (i, glyph) in glyphs.iter().enumerate() {
+        let gx = x0 + i * (3 * SCALE + 1);
+        for (row, bits) in BADGE_GLYPHS[*glyph].iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for sy in 0..SCALE {
+                    for sx in 0..SCALE {
+                        let (px, py) = (gx + col * SCALE + sx, y0 + row * SCALE + sy);
+                        if px < size && py < size {
+                            rgba[(py * size + px) * 4..][..4].copy_from_slice(&[255, 255, 255, 255]);
                         }
                     }
                 }
+            }
+        }
+    }
+    rgba
+}
+
+/// "scheme://host[:port]" of a hierarchical URL (lower-cased scheme and host), or None for
+/// URLs without an authority such as `about:blank` or `data:`.
+fn url_origin(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    if scheme.is_empty() || host_port.is_empty() {
+        return None;
+    }
+    Some(format!("{}://{}", scheme.to_ascii_lowercase(), host_port.to_ascii_lowercase()))
+}
+
+/// Query parameter added by the hard-reload fallback.
+const CACHE_BUST_PARAM: &str = "_wry_reload";
+
+/// `url` with CACHE_BUST_PARAM set to `stamp` (replacing a previous one), keeping the fragment.
+fn cache_bust_url(url: &str, stamp: u128) -> String {
+    let (rest, fragment) = match url.split_once('#') {
+        Some((r, f)) => (r, Some(f)),
+        None => (url, None),
+    };
+    let (base, query) = match rest.split_once('?') {
+        Some((b, q)) => (b, q),
+        None => (rest, ""),
+    };
+    let mut params: Vec<&str> = query
+        .split('&')
+        .filter(|p| !p.is_empty() && p.split('=').next() != Some(CACHE_BUST_PARAM))
+        .collect();
+    let bust = format!("{}={}", CACHE_BUST_PARAM, stamp);
+    params.push(&bust);
+    let mut out = format!("{}?{}", base, params.join("&"));
+    if let Some(f) = fragment {
+        out.push('#');
+        out.push_str(f);
+    }
+    out
+}
+
+/// `html` with `<base href="base_url">` inserted as the first element of `<head>` (after the
+/// `<head>` or `<html>` start tag, or at the very start when neither is present). The first
+/// `<base>` in a document wins, so this overrides one already in the page.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn inject_base_href(html: &str, base_url: &str) -> String {
+    let escaped = base_url.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;");
+    let tag = format!("<base href=\"{}\">", escaped);
+    // ASCII lowercase keeps byte offsets identical to `html`.
+    let lower = html.to_ascii_lowercase();
+    let after_start_tag = |name: &str| {
+        let open = format!("<{}", name);
+        lower.match_indices(&open).find_map(|(i, _)| {
+            // Skip e.g. `<header>` when looking for `<head`.
+            let next = lower.as_bytes().get(i + open.len()).copied();
+            if !matches!(next, Some(b'>') | Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') | Some(b'/')) {
+                return None;
+            }
+            lower[i..].find('>').map(|end| i + end + 1)
+        })
+    };
+    let at = after_start_tag("head").or_else(|| after_start_tag("html")).unwrap_or(0);
+    format!("{}{}{}", &html[..at], tag, &html[at..])
+}
+
+// Keyboard modifier bitmask passed to input callbacks.
+const MODIFIER_SHIFT: c_int = 1;
+const MODIFIER_CONTROL: c_int = 2;
+const MODIFIER_ALT: c_int = 4;
+const MODIFIER_SUPER: c_int = 8;
+
+fn modifier_flags(state: tao::keyboard::ModifiersState) -> c_int {
+    let mut flags = 0;
+    if state.shift_key() {
+        flags |= MODIFIER_SHIFT;
+    }
+    if state.control_key() {
+        flags |= MODIFIER_CONTROL;
+    }
+    if state.alt_key() {
+        flags |= MODIFIER_ALT;
+    }
+    if state.super_key() {
+        flags |= MODIFIER_SUPER;
+    }
+    flags
+}
+
+// Hit-test results returned by HitTestCallback.
+const HIT_TEST_CAPTION: c_int = 1;
+
+/// IPC body prefix used by HIT_TEST_SCRIPT; such messages never reach the user's ipc_handler.
+const HIT_TEST_IPC_PREFIX: &str = "__wry_hit_test:";
+
+const HIT_TEST_SCRIPT: &str = r#"window.addEventListener('mousedown', function (e) {
+    if (e.button === 0) {
+        window.ipc.postMessage('__wry_hit_test:' + Math.round(e.clientX) + ',' + Math.round(e.clientY) + ',' + e.detail);
+    }
+}, true);"#;
+
+/// Parse "x,y,clicks" from a hit-test IPC message.
+fn parse_hit_test_message(s: &str) -> Option<(c_int, c_int, c_int)> {
+    let mut parts = s.split(',').map(|p| p.trim().parse::<c_int>());
+    let x = parts.next()?.ok()?;
+    let y = parts.next()?.ok()?;
+    let clicks = parts.next()?.ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((x, y, clicks))
+}
+
+/// Map a resize hit-test result to a tao resize direction. None for Client / Caption / unknown.
+fn hit_test_resize_direction(result: c_int) -> Option<tao::window::ResizeDirection> {
+    use tao::window::ResizeDirection;
+    match result {
+        2 => Some(ResizeDirection::West),
+        3 => Some(ResizeDirection::North),
+        4 => Some(ResizeDirection::East),
+        5 => Some(ResizeDirection::South),
+        6 => Some(ResizeDirection::NorthWest),
+        7 => Some(ResizeDirection::NorthEast),
+        8 => Some(ResizeDirection::SouthWest),
+        9 => Some(ResizeDirection::SouthEast),
+        _ => None,
+    }
+}
+
+/// IPC body prefix used by app_region_drag_script; such messages never reach the user's ipc_handler.
+const APP_REGION_DRAG_IPC_PREFIX: &str = "__wry_drag_region:";
+
+/// App-region parsing for app_region_drag_script. WebKit does not implement `app-region`: its
+/// computed style is empty and its CSS parser drops the declaration, so stylesheet rules are read
+/// from the text of `<style>` elements (later rules win; specificity and `<link>` sheets are not
+/// considered) and inline `style` attributes from their raw text.
+const APP_REGION_PARSE_JS: &str = r#"var APP_REGION_DECL = /(?:^|[;{\s])(?:-webkit-)?app-region\s*:\s*(no-drag|drag)\b/i;
+function appRegionDeclared(text) {
+    var m = APP_REGION_DECL.exec(text || '');
+    return m ? m[1].toLowerCase() : '';
+}
+function appRegionRules(css) {
+    var out = [], re = /([^{}]+)\{([^{}]*)\}/g, m;
+    while ((m = re.exec(css || ''))) {
+        var region = appRegionDeclared(m[2]);
+        if (region) out.push([m[1].trim(), region]);
+    }
+    return out;
+}
+function appRegionOf(attr, inline, computed, matchedRegions) {
+    if (attr === 'drag' || attr === 'no-drag') return attr;
+    var region = appRegionDeclared(inline);
+    if (region) return region;
+    if (computed === 'drag' || computed === 'no-drag') return computed;
+    return matchedRegions.length ? matchedRegions[matchedRegions.length - 1] : '';
+}"#;
+
+/// Walks up from the mousedown target; the nearest element with a `drag` / `no-drag` region
+/// decides: its `data-wry-drag-region` attribute, inline style, computed style (engines that
+/// implement the property), then `<style>` rules (see APP_REGION_PARSE_JS).
+const APP_REGION_LISTENER_JS: &str = r#"window.addEventListener('mousedown', function (e) {
+    if (e.button !== 0) return;
+    var rules = [];
+    document.querySelectorAll('style').forEach(function (s) { rules = rules.concat(appRegionRules(s.textContent)); });
+    for (var el = e.target; el && el.nodeType === 1; el = el.parentElement) {
+        var style = getComputedStyle(el);
+        var computed = style.getPropertyValue('-webkit-app-region') || style.getPropertyValue('app-region');
+        var matched = rules.filter(function (r) { try { return el.matches(r[0]); } catch (err) { return false; } })
+            .map(function (r) { return r[1]; });
+        var region = appRegionOf(el.getAttribute('data-wry-drag-region'), el.getAttribute('style'), computed, matched);
+        if (region === 'no-drag') return;
+        if (region === 'drag') {
+            e.preventDefault();
+            window.ipc.postMessage('__wry_drag_region:' + e.detail);
+            return;
+        }
+    }
+}, true);"#;
+
+/// Mousedown shim moving the window from app-region drag areas (macOS, Linux).
+fn app_region_drag_script() -> String {
+    format!("(function () {{\n{}\n{}\n}})();", APP_REGION_PARSE_JS, APP_REGION_LISTENER_JS)
+}
+
+/// IPC body prefix used by FAVICON_SCRIPT; such messages never reach the user's ipc_handler.
+const FAVICON_IPC_PREFIX: &str = "__wry_favicon:";
+
+/// Reports the current favicon as a PNG data URL (empty if none) on load and whenever the
+/// `<link rel="icon">` set changes. Cross-origin icons without CORS taint the canvas and are skipped.
+const FAVICON_SCRIPT: &str = r#"(function () {
+    var last = null;
+    function report() {
+        var link = document.querySelector('link[rel~="icon"]');
+        var href = link ? link.href : (location.origin !== 'null' ? location.origin + '/favicon.ico' : '');
+        if (href === last) return;
+        last = href;
+        if (!href) { window.ipc.postMessage('__wry_favicon:'); return; }
+        var img = new Image();
+        img.onload = function () {
+            try {
+                var c = document.createElement('canvas');
+                c.width = img.naturalWidth || 32;
+                c.height = img.naturalHeight || 32;
+                c.getContext('2d').drawImage(img, 0, 0, c.width, c.height);
+                window.ipc.postMessage('__wry_favicon:' + c.toDataURL('image/png'));
+            } catch (e) {}
+        };
+        img.onerror = function () { window.ipc.postMessage('__wry_favicon:'); };
+        img.src = href;
+    }
+    function start() {
+        report();
+        new MutationObserver(report).observe(document.head || document.documentElement,
+            { childList: true, subtree: true, attributes: true, attributeFilter: ['href', 'rel'] });
+    }
+    if (document.readyState === 'loading') document.addEventListener('DOMContentLoaded', start);
+    else start();
+})();"#;
+
+/// IPC body prefix used by CONSOLE_SCRIPT; such messages never reach the user's ipc_handler.
+const CONSOLE_IPC_PREFIX: &str = "__wry_console:";
+
+/// Wraps `console.*` (the original still runs, so devtools keep working) and reports uncaught
+/// errors as `[level, message, source, line]`. The source is the first stack frame outside the shim.
+const CONSOLE_SCRIPT: &str = r#"(function () {
+    var levels = { log: 0, debug: 1, info: 2, warn: 3, error: 4 };
+    function frames() {
+        return (new Error().stack || '').split('\n').map(function (l) {
+            return /\(?(\S+):(\d+):\d+\)?\s*$/.exec(l);
+        }).filter(Boolean);
+    }
+    function text(v) {
+        if (typeof v === 'string') return v;
+        if (v instanceof Error) return v.stack || String(v);
+        try { var j = JSON.stringify(v); return j === undefined ? String(v) : j; } catch (e) { return String(v); }
+    }
+    function send(level, message, source, line) {
+        try {
+            window.ipc.postMessage('__wry_console:' + JSON.stringify([level, message, source || '', line || 0]));
+        } catch (e) {}
+    }
+    Object.keys(levels).forEach(function (name) {
+        var original = console[name];
+        console[name] = function () {
+            var f = frames(), self = f.length ? f[0][1] : null, at = null;
+            for (var i = 1; i < f.length && !at; i++) if (f[i][1] !== self) at = f[i];
+            send(levels[name], Array.prototype.map.call(arguments, text).join(' '),
+                at ? at[1] : '', at ? +at[2] : 0);
+            if (original) return original.apply(console, arguments);
+        };
+    });
+    window.addEventListener('error', function (e) {
+        send(4, e.error ? text(e.error) : e.message, e.filename, e.lineno);
+    });
+    window.addEventListener('unhandledrejection', function (e) {
+        send(4, 'Unhandled rejection: ' + text(e.reason), '', 0);
+    });
+})();"#;
+
+const HTML_FULLSCREEN_IPC_PREFIX: &str = "__wry_html_fullscreen:";
+
+/// Reports HTML fullscreen changes as 1 (entered) / 0 (left). Used where no native event exists (macOS).
+const HTML_FULLSCREEN_SCRIPT: &str = r#"(function () {
+    function report() {
+        var el = document.fullscreenElement || document.webkitFullscreenElement;
+        try { window.ipc.postMessage('__wry_html_fullscreen:' + (el ? 1 : 0)); } catch (e) {}
+    }
+    document.addEventListener('fullscreenchange', report);
+    document.addEventListener('webkitfullscreenchange', report);
+})();"#;
+
+/// Caps requestAnimationFrame while the window is in the background: `__wryBackgroundFps(n)` runs
+/// frame callbacks at most n times a second, 0 holds them, null restores normal frames (and runs
+/// the held ones). Throttled frames get negative IDs so cancelAnimationFrame can tell them apart.
+const BACKGROUND_FPS_SCRIPT: &str = r#"(function () {
+    var raf = window.requestAnimationFrame.bind(window), caf = window.cancelAnimationFrame.bind(window);
+    var interval = 0, held = new Map(), nextId = -1, timer = 0, last = 0;
+    function flush(t) {
+        var cbs = held;
+        held = new Map();
+        cbs.forEach(function (cb) { cb(t); });
+    }
+    function schedule() {
+        if (timer || !held.size || interval < 0) return;
+        if (interval === 0) {
+            timer = -1;
+            raf(function (t) { timer = 0; flush(t); });
+            return;
+        }
+        timer = setTimeout(function () {
+            timer = 0;
+            last = performance.now();
+            raf(flush);
+        }, Math.max(0, last + interval - performance.now()));
+    }
+    window.requestAnimationFrame = function (cb) {
+        if (!interval) return raf(cb);
+        var id = nextId--;
+        held.set(id, cb);
+        schedule();
+        return id;
+    };
+    window.cancelAnimationFrame = function (id) {
+        if (id < 0) held.delete(id); else caf(id);
+    };
+    window.__wryBackgroundFps = function (fps) {
+        interval = fps === null ? 0 : fps > 0 ? 1000 / fps : -1;
+        if (timer > 0) clearTimeout(timer);
+        timer = 0;
+        schedule();
+    };
+})();"#;
+
+/// Lists the iframes of the page depth-first as `{id, name, url, sameOrigin}`. Each iframe element
+/// gets an ID the first time it is listed, kept in `window.__wryFrames` for as long as the element
+/// stays in the page, so inserting or removing other frames does not shift it. Frames of
+/// cross-origin frames are not visible; a cross-origin frame's `url` is its `src` attribute.
+const FRAMES_SCRIPT: &str = r#"(function () {
+    var reg = window.__wryFrames || (window.__wryFrames = { next: 1, byId: new Map(), ids: new WeakMap() });
+    reg.byId.forEach(function (el, id) { if (!el.isConnected) reg.byId.delete(id); });
+    var out = [];
+    function walk(doc) {
+        var els = doc.querySelectorAll('iframe, frame');
+        for (var i = 0; i < els.length; i++) {
+            var el = els[i], url = el.src || '', child = null, id = reg.ids.get(el);
+            if (id === undefined) { id = reg.next++; reg.ids.set(el, id); }
+            reg.byId.set(id, el);
+            try { child = el.contentWindow.document; url = el.contentWindow.location.href; } catch (e) { child = null; }
+            out.push({ id: String(id), name: el.name || el.id || '', url: url, sameOrigin: !!child });
+            if (child) walk(child);
+        }
+    }
+    walk(document);
+    return out;
+})()"#;
+
+/// Script evaluating `js` in the frame with FRAMES_SCRIPT ID `frame_id`; None if the ID is malformed.
+/// Reaching the frame throws (in the page console) if it is gone or became cross-origin.
+fn frame_eval_script(frame_id: &str, js: &str) -> Option<String> {
+    let id = parse_frame_id(frame_id)?;
+    Some(format!(
+        "(function () {{ var reg = window.__wryFrames, el = reg && reg.byId.get({id}); \
+         if (!el || !el.isConnected) throw new Error('wry: no frame {id}'); \
+         el.contentWindow.eval({}); }})();",
+        serde_json::to_string(js).ok()?
+    ))
+}
+
+/// A FRAMES_SCRIPT frame ID: a positive decimal number.
+fn parse_frame_id(frame_id: &str) -> Option<u32> {
+    if !frame_id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    frame_id.parse().ok().filter(|id| *id > 0)
+}
+
+/// IDs of the same-origin frames in a FRAMES_SCRIPT result.
+fn same_origin_frame_ids(json: &str) -> HashSet<u32> {
+    let frames: Vec<serde_json::Value> = serde_json::from_str(json).unwrap_or_default();
+    frames
+        .iter()
+        .filter(|f| f["sameOrigin"].as_bool() == Some(true))
+        .filter_map(|f| parse_frame_id(f["id"].as_str()?))
+        .collect()
+}
+
+/// Parse a CONSOLE_SCRIPT message body (after the prefix) into (level, message, source, line).
+fn parse_console_message(body: &str) -> Option<(c_int, String, String, c_int)> {
+    let (level, message, source, line): (c_int, String, String, c_int) = serde_json::from_str(body).ok()?;
+    Some((level.clamp(0, 4), message, source, line.max(0)))
+}
+
+/// Sets the `spellcheck` attribute on the root element (inherited by editable content that does
+/// not set its own) now and again once the document is parsed.
+fn spellcheck_script(enabled: bool) -> String {
+    format!(
+        "(function () {{ function apply() {{ if (document.documentElement) document.documentElement.spellcheck = {}; }} \
+         apply(); document.addEventListener('DOMContentLoaded', apply); }})();",
+        enabled
+    )
+}
+
+/// Script defining `window.__WRY_DATA` from the JSON text `json`; None if it is not valid JSON.
+/// The value is re-serialized by serde_json, so the page only ever sees a plain JSON literal.
+fn initial_data_script(json: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    Some(format!("window.__WRY_DATA = {};", serde_json::to_string(&value).ok()?))
+}
+
+/// Wraps `js` to run once the document is parsed: on DOMContentLoaded, or immediately if that has
+/// already fired. The newline keeps a trailing `//` comment in `js` from swallowing the wrapper.
+fn dom_ready_script(js: &str) -> String {
+    format!(
+        "(function () {{ function run() {{\n{}\n}} \
+         if (document.readyState === 'loading') document.addEventListener('DOMContentLoaded', run, {{ once: true }}); \
+         else run(); }})();",
+        js
+    )
+}
+
+/// Adds a `<style>` with `css` to the root element at document start, or as soon as the root
+/// element exists. Style elements outside `<head>` still apply.
+fn user_stylesheet_script(css: &str) -> String {
+    format!(
+        "(function () {{ var css = {}; function add() {{ var s = document.createElement('style'); \
+         s.setAttribute('data-wry-user-stylesheet', ''); s.textContent = css; document.documentElement.appendChild(s); }} \
+         if (document.documentElement) {{ add(); return; }} \
+         new MutationObserver(function (_, o) {{ if (document.documentElement) {{ o.disconnect(); add(); }} }}) \
+         .observe(document, {{ childList: true }}); }})();",
+        serde_json::to_string(css).unwrap_or_else(|_| "''".to_owned())
+    )
+}
+
+/// Linux: WebKitGTK only checks spelling when enabled on the web context (shared by the webviews
+/// of that context). Windows and macOS spellcheck editable content by default, so the
+/// `spellcheck` attribute (spellcheck_script) is all they need.
+fn set_native_spellcheck(webview: &WebView, enabled: bool) {
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{WebContextExt, WebViewExt};
+        use wry::WebViewExtUnix;
+        if let Some(context) = webview.webview().context() {
+            context.set_spell_checking_enabled(enabled);
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (webview, enabled);
+}
+
+/// Windows: forward WebView2 FaviconChanged (ICoreWebView2_15, runtime 1.0.1185+) to the loop.
+/// Fails on older runtimes, which lack the interface.
+#[cfg(target_os = "windows")]
+fn watch_favicon(
+    webview: &WebView,
+    proxy: EventLoopProxy<UserEvent>,
+    window_id: usize,
+) -> windows::core::Result<()> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2_15, COREWEBVIEW2_FAVICON_IMAGE_FORMAT_PNG,
+    };
+    use webview2_com::{FaviconChangedEventHandler, GetFaviconCompletedHandler};
+    use windows::core::Interface;
+    use wry::WebViewExtWindows;
+
+    let webview15: ICoreWebView2_15 = webview.webview().cast()?;
+    let handler = FaviconChangedEventHandler::create(Box::new(move |sender, _| {
+        let Some(sender) = sender else { return Ok(()); };
+        let sender: ICoreWebView2_15 = sender.cast()?;
+        let proxy = proxy.clone();
+        let completed = GetFaviconCompletedHandler::create(Box::new(move |_, stream| {
+            let png = stream.map(|s| read_stream(&s)).and_then(|data| favicon_to_png(&data));
+            log_err!(proxy.send_event(UserEvent::FaviconChanged { window_id, png }), "favicon changed");
+            Ok(())
+        }));
+        unsafe { sender.GetFavicon(COREWEBVIEW2_FAVICON_IMAGE_FORMAT_PNG, &completed) }
+    }));
+    let mut token = 0i64;
+    unsafe { webview15.add_FaviconChanged(&handler, &mut token) }
+}
+
+/// Linux: forward WebKitGTK's `favicon` property to the loop. The engine resolves the icon itself
+/// (`<link rel="icon">`, the default /favicon.ico, icons changed after load), but only with the
+/// context's favicon database enabled, which WebKitGTK leaves off by default.
+#[cfg(target_os = "linux")]
+fn watch_favicon(webview: &WebView, proxy: EventLoopProxy<UserEvent>, window_id: usize) {
+    use gtk::cairo::ImageSurface;
+    use webkit2gtk::{WebContextExt, WebViewExt};
+    use wry::WebViewExtUnix;
+
+    let view = webview.webview();
+    if let Some(context) = view.context() {
+        if context.favicon_database_directory().is_none() {
+            context.set_favicon_database_directory(None);
+        }
+    }
+    view.connect_favicon_notify(move |view| {
+        let surface = view.favicon().and_then(|surface| ImageSurface::try_from(surface).ok());
+        let png = surface.and_then(|surface| {
+            surface.flush();
+            let (width, height) = (surface.width() as u32, surface.height() as u32);
+            let stride = surface.stride() as usize;
+            let mut png = None;
+            log_err!(
+                surface.with_data(|data| png = argb32_to_png(data, width, height, stride)),
+                "favicon surface"
+            );
+            png
+        });
+        log_err!(proxy.send_event(UserEvent::FaviconChanged { window_id, png }), "favicon changed");
+    });
+}
+
+/// Post UserEvent::HtmlFullscreen when the page enters or leaves HTML fullscreen.
+///
+/// - Windows: WebView2 ContainsFullScreenElementChanged (the element fills the webview; the window
+///   is left alone unless html_fullscreen_window).
+/// - Linux: WebKitGTK enter-fullscreen / leave-fullscreen. WebKitGTK's own handling, which
+///   fullscreens the toplevel behind tao's back, is suppressed; html_fullscreen_window does it instead.
+/// - macOS: turns on the WKWebView fullscreen API (off by default; macOS 12.3+); changes come from
+///   HTML_FULLSCREEN_SCRIPT. WebKit presents the element in its own fullscreen window.
+fn watch_html_fullscreen(webview: &WebView, proxy: EventLoopProxy<UserEvent>, window_id: usize) {
+    #[cfg(target_os = "windows")]
+    {
+        use webview2_com::ContainsFullScreenElementChangedEventHandler;
+        use windows::core::BOOL;
+        use wry::WebViewExtWindows;
+
+        let handler = ContainsFullScreenElementChangedEventHandler::create(Box::new(move |sender, _| {
+            let Some(sender) = sender else { return Ok(()); };
+            let mut entered = BOOL::default();
+            unsafe { sender.ContainsFullScreenElement(&mut entered)? };
+            let entered = entered.as_bool();
+            log_err!(proxy.send_event(UserEvent::HtmlFullscreen { window_id, entered }), "html fullscreen");
+            Ok(())
+        }));
+        let mut token = 0i64;
+        log_err!(
+            unsafe { webview.webview().add_ContainsFullScreenElementChanged(&handler, &mut token) },
+            "html fullscreen"
+        );
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+
+        let enter = proxy.clone();
+        webview.webview().connect_enter_fullscreen(move |_| {
+            log_err!(enter.send_event(UserEvent::HtmlFullscreen { window_id, entered: true }), "html fullscreen");
+            true
+        });
+        webview.webview().connect_leave_fullscreen(move |_| {
+            log_err!(proxy.send_event(UserEvent::HtmlFullscreen { window_id, entered: false }), "html fullscreen");
+            true
+        });
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::runtime::NSObjectProtocol;
+        use objc2::sel;
+        use wry::WebViewExtMacOS;
+
+        let _ = (proxy, window_id);
+        unsafe {
+            let preferences = webview.webview().configuration().preferences();
+            if preferences.respondsToSelector(sel!(setElementFullscreenEnabled:)) {
+                preferences.setElementFullscreenEnabled(true);
+            }
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    let _ = (webview, proxy, window_id);
+}
+
+/// Report the outcome of each main-frame load to `state` (see LoadState). Returns Ok(false) where
+/// the platform has no hook, leaving it to the page load handler, which always reports success.
+///
+/// - Windows: NavigationStarting / NavigationCompleted. Failed = `IsSuccess` false (network, DNS,
+///   TLS and HTTP 4xx/5xx errors); loads cancelled by a newer navigation are not reported.
+/// - Linux: `load-changed` / `load-failed`. Failed = `load-failed` or a main resource status >= 400.
+/// - macOS: no hook (wry owns the navigation delegate); every load reports success.
+fn watch_load_outcome(webview: &WebView, state: Arc<Mutex<LoadState>>) -> Result<bool, String> {
+    #[cfg(target_os = "windows")]
+    {
+        use webview2_com::Microsoft::Web::WebView2::Win32::*;
+        use webview2_com::{NavigationCompletedEventHandler, NavigationStartingEventHandler};
+        use windows::core::BOOL;
+        use wry::WebViewExtWindows;
+
+        let starting_state = state.clone();
+        let starting = NavigationStartingEventHandler::create(Box::new(move |_, _| {
+            if let Ok(mut s) = starting_state.lock() {
+                s.started();
+            }
+            Ok(())
+        }));
+        let completed = NavigationCompletedEventHandler::create(Box::new(move |_, args| {
+            let Some(args) = args else { return Ok(()); };
+            let mut success = BOOL::default();
+            let mut status = COREWEBVIEW2_WEB_ERROR_STATUS::default();
+            unsafe {
+                args.IsSuccess(&mut success)?;
+                args.WebErrorStatus(&mut status)?;
+            }
+            if status == COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED {
+                return Ok(());
+            }
+            if let Ok(mut s) = state.lock() {
+                s.finished(success.as_bool());
+            }
+            Ok(())
+        }));
+        let mut token = 0i64;
+        unsafe {
+            webview.webview().add_NavigationStarting(&starting, &mut token).map_err(|e| e.to_string())?;
+            webview.webview().add_NavigationCompleted(&completed, &mut token).map_err(|e| e.to_string())?;
+        }
+        Ok(true)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{LoadEvent, URIResponseExt, WebResourceExt, WebViewExt};
+        use wry::WebViewExtUnix;
+
+        let failed_state = state.clone();
+        webview.webview().connect_load_failed(move |_, _, _, _| {
+            if let Ok(mut s) = failed_state.lock() {
+                s.failed = true;
+            }
+            false
+        });
+        webview.webview().connect_load_changed(move |view, event| {
+            let Ok(mut s) = state.lock() else { return; };
+            match event {
+                LoadEvent::Started => s.started(),
+                LoadEvent::Finished => {
+                    let status = view
+                        .main_resource()
+                        .and_then(|r| r.response())
+                        .map(|r| r.status_code())
+                        .unwrap_or(0);
+                    let success = !s.failed && status < 400;
+                    s.finished(success);
+                }
+                _ => {}
+            }
+        });
+        Ok(true)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = (webview, state);
+        Ok(false)
+    }
+}
+
+/// Windows: report WebView2 ProcessFailed to the crash handler (see WebviewCrashCallback).
+#[cfg(target_os = "windows")]
+fn watch_process_failed(
+    webview: &WebView,
+    handler: (WebviewCrashCallback, usize),
+) -> windows::core::Result<()> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::*;
+    use webview2_com::ProcessFailedEventHandler;
+    use wry::WebViewExtWindows;
+
+    let (cb, ctx) = handler;
+    let failed = ProcessFailedEventHandler::create(Box::new(move |_, args| {
+        let mut kind = COREWEBVIEW2_PROCESS_FAILED_KIND::default();
+        if let Some(args) = args {
+            unsafe { args.ProcessFailedKind(&mut kind)? };
+        }
+        let reason = match kind {
+            COREWEBVIEW2_PROCESS_FAILED_KIND_BROWSER_PROCESS_EXITED => 3,
+            COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_EXITED
+            | COREWEBVIEW2_PROCESS_FAILED_KIND_FRAME_RENDER_PROCESS_EXITED => 1,
+            COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE => 4,
+            _ => 0,
+        };
+        cb(reason, ctx as *mut c_void);
+        Ok(())
+    }));
+    let mut token = 0i64;
+    unsafe { webview.webview().add_ProcessFailed(&failed, &mut token) }
+}
+
+/// Windows: report WebView2 ProcessFailed(RENDER_PROCESS_UNRESPONSIVE) to the responsiveness
+/// handler. WebView2 has no event for the recovery, so a no-op script is queued when the renderer
+/// hangs; it completes once the renderer runs again.
+#[cfg(target_os = "windows")]
+fn watch_unresponsive(
+    webview: &WebView,
+    handler: (ResponsivenessCallback, usize),
+) -> windows::core::Result<()> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::*;
+    use webview2_com::{ExecuteScriptCompletedHandler, ProcessFailedEventHandler};
+    use windows::core::w;
+    use wry::WebViewExtWindows;
+
+    let (cb, ctx) = handler;
+    // Unresponsive is raised repeatedly while the hang lasts; report it once.
+    let hung = Arc::new(AtomicBool::new(false));
+    let failed = ProcessFailedEventHandler::create(Box::new(move |sender, args| {
+        let mut kind = COREWEBVIEW2_PROCESS_FAILED_KIND::default();
+        if let Some(args) = args {
+            unsafe { args.ProcessFailedKind(&mut kind)? };
+        }
+        if kind != COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE {
+            // Any other failure replaces the renderer; the crash handler reports that.
+            hung.store(false, Ordering::SeqCst);
+            return Ok(());
+        }
+        if hung.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        cb(false, ctx as *mut c_void);
+        let hung = hung.clone();
+        let probe = ExecuteScriptCompletedHandler::create(Box::new(move |result, _| {
+            if hung.swap(false, Ordering::SeqCst) && result.is_ok() {
+                cb(true, ctx as *mut c_void);
+            }
+            Ok(())
+        }));
+        if let Some(sender) = sender {
+            unsafe { sender.ExecuteScript(w!("0"), &probe)? };
+        }
+        Ok(())
+    }));
+    let mut token = 0i64;
+    unsafe { webview.webview().add_ProcessFailed(&failed, &mut token) }
+}
+
+/// Whether a virtual key (with Ctrl+Shift held or not) is a WebView2 devtools shortcut:
+/// F12, Ctrl+Shift+I (devtools), Ctrl+Shift+J (console), Ctrl+Shift+C (inspect element).
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_devtools_shortcut(virtual_key: u32, ctrl_shift: bool) -> bool {
+    const VK_F12: u32 = 0x7B;
+    virtual_key == VK_F12 || (ctrl_shift && matches!(virtual_key, 0x49 | 0x4A | 0x43))
+}
+
+/// Windows: swallow the devtools accelerators via the controller's AcceleratorKeyPressed.
+#[cfg(target_os = "windows")]
+fn block_devtools_shortcut(webview: &WebView) -> windows::core::Result<()> {
+    use webview2_com::AcceleratorKeyPressedEventHandler;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_SHIFT};
+    use wry::WebViewExtWindows;
+
+    let handler = AcceleratorKeyPressedEventHandler::create(Box::new(|_, args| {
+        let Some(args) = args else { return Ok(()); };
+        let mut key = 0u32;
+        unsafe { args.VirtualKey(&mut key)? };
+        let ctrl_shift = unsafe { GetKeyState(VK_CONTROL.0 as i32) < 0 && GetKeyState(VK_SHIFT.0 as i32) < 0 };
+        if is_devtools_shortcut(key, ctrl_shift) {
+            unsafe { args.SetHandled(true)? };
+        }
+        Ok(())
+    }));
+    let mut token = 0i64;
+    unsafe { webview.controller().add_AcceleratorKeyPressed(&handler, &mut token) }
+}
+
+/// Windows: route page dialogs to the script dialog handler (see ScriptDialogCallback). Turning
+/// off the default dialogs is what makes WebView2 raise ScriptDialogOpening.
+#[cfg(target_os = "windows")]
+fn watch_script_dialogs(
+    webview: &WebView,
+    handler: (ScriptDialogCallback, usize),
+) -> windows::core::Result<()> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::*;
+    use webview2_com::{take_pwstr, ScriptDialogOpeningEventHandler};
+    use windows::core::PWSTR;
+    use wry::WebViewExtWindows;
+
+    let (cb, ctx) = handler;
+    let opening = ScriptDialogOpeningEventHandler::create(Box::new(move |_, args| {
+        let Some(args) = args else { return Ok(()); };
+        let mut kind = COREWEBVIEW2_SCRIPT_DIALOG_KIND::default();
+        let (mut message, mut default_text) = (PWSTR::null(), PWSTR::null());
+        unsafe {
+            args.Kind(&mut kind)?;
+            args.Message(&mut message)?;
+            args.DefaultText(&mut default_text)?;
+        }
+        let kind = match kind {
+            COREWEBVIEW2_SCRIPT_DIALOG_KIND_CONFIRM => 1,
+            COREWEBVIEW2_SCRIPT_DIALOG_KIND_PROMPT => 2,
+            COREWEBVIEW2_SCRIPT_DIALOG_KIND_BEFOREUNLOAD => 3,
+            _ => 0,
+        };
+        let message = CString::new(take_pwstr(message)).unwrap_or_default();
+        let default_text = CString::new(take_pwstr(default_text)).unwrap_or_default();
+        let deferral = unsafe { args.GetDeferral()? };
+        let responder = Box::into_raw(Box::new(ScriptDialogResponder { args, deferral })) as *mut c_void;
+        cb(kind, message.as_ptr(), default_text.as_ptr(), responder, ctx as *mut c_void);
+        Ok(())
+    }));
+    let mut token = 0i64;
+    unsafe {
+        webview.webview().Settings()?.SetAreDefaultScriptDialogsEnabled(false)?;
+        webview.webview().add_ScriptDialogOpening(&opening, &mut token)
+    }
+}
+
+/// Windows: set (accelerator keys, default context menus, devtools) in the WebView2 settings to
+/// `restore`, or turn all three off if None. Returns the previous values.
+#[cfg(target_os = "windows")]
+fn webview_lockdown(
+    webview: &WebView,
+    restore: Option<(bool, bool, bool)>,
+) -> windows::core::Result<(bool, bool, bool)> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings3;
+    use windows::core::{Interface, BOOL};
+    use wry::WebViewExtWindows;
+
+    unsafe {
+        let settings: ICoreWebView2Settings3 = webview.webview().Settings()?.cast()?;
+        let (mut accel, mut menus, mut devtools) = (BOOL::default(), BOOL::default(), BOOL::default());
+        settings.AreBrowserAcceleratorKeysEnabled(&mut accel)?;
+        settings.AreDefaultContextMenusEnabled(&mut menus)?;
+        settings.AreDevToolsEnabled(&mut devtools)?;
+        let (a, m, d) = restore.unwrap_or((false, false, false));
+        settings.SetAreBrowserAcceleratorKeysEnabled(a)?;
+        settings.SetAreDefaultContextMenusEnabled(m)?;
+        settings.SetAreDevToolsEnabled(d)?;
+        Ok((accel.as_bool(), menus.as_bool(), devtools.as_bool()))
+    }
+}
+
+/// Windows: read an IStream to the end.
+#[cfg(target_os = "windows")]
+fn read_stream(stream: &windows::Win32::System::Com::IStream) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let mut read = 0u32;
+        let hr = unsafe { stream.Read(buf.as_mut_ptr() as *mut c_void, buf.len() as u32, Some(&mut read)) };
+        if hr.is_err() || read == 0 {
+            break;
+        }
+        out.extend_from_slice(&buf[..read as usize]);
+    }
+    out
+}
+
+/// Windows: turn on WebView2's own `app-region` handling (ICoreWebView2Settings9).
+#[cfg(target_os = "windows")]
+fn enable_non_client_region_support(webview: &WebView) -> windows::core::Result<()> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings9;
+    use windows::core::Interface;
+    use wry::WebViewExtWindows;
+    unsafe {
+        let settings: ICoreWebView2Settings9 = webview.webview().Settings()?.cast()?;
+        settings.SetIsNonClientRegionSupportEnabled(true)
+    }
+}
+
+/// Windows: turn a freshly built window into a tool palette (wry_window_set_panel_style):
+/// WS_EX_TOOLWINDOW (no taskbar button, not in Alt-Tab, small caption) and WS_EX_NOACTIVATE
+/// (clicks do not activate it).
+#[cfg(target_os = "windows")]
+fn apply_panel_style(window: &Window) {
+    use tao::platform::windows::WindowExtWindows;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongW, SetWindowLongW, SetWindowPos, GWL_EXSTYLE, SWP_FRAMECHANGED, SWP_NOACTIVATE,
+        SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, WS_EX_APPWINDOW, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
+    };
+    let hwnd = HWND(window.hwnd() as _);
+    unsafe {
+        let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32;
+        let ex_style = (ex_style & !WS_EX_APPWINDOW.0) | WS_EX_TOOLWINDOW.0 | WS_EX_NOACTIVATE.0;
+        SetWindowLongW(hwnd, GWL_EXSTYLE, ex_style as i32);
+        let flags = SWP_FRAMECHANGED | SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER;
+        log_err!(SetWindowPos(hwnd, None, 0, 0, 0, 0, flags), "panel style");
+    }
+}
+
+/// macOS: NSWindowDidChangeOcclusionStateNotification observer that reports changes in the
+/// window's visible bit to an occlusion handler. The observer is removed on drop.
+#[cfg(target_os = "macos")]
+struct OcclusionObserver(
+    objc2::rc::Retained<objc2::runtime::ProtocolObject<dyn objc2::runtime::NSObjectProtocol>>,
+);
+
+#[cfg(target_os = "macos")]
+impl OcclusionObserver {
+    fn new(window: &Window, handler: (OcclusionCallback, usize)) -> Self {
+        use objc2::runtime::AnyObject;
+        use objc2_app_kit::{NSWindow, NSWindowDidChangeOcclusionStateNotification, NSWindowOcclusionState};
+        use objc2_foundation::{NSNotification, NSNotificationCenter};
+        use tao::platform::macos::WindowExtMacOS;
+
+        let ns_window = window.ns_window() as *const NSWindow;
+        let (callback, ctx) = handler;
+        let last_visible = std::cell::Cell::new(None);
+        let block = block2::RcBlock::new(move |_: std::ptr::NonNull<NSNotification>| {
+            // Only posted while registered, and the observer is removed before the window is freed.
+            let visible = unsafe { &*ns_window }.occlusionState().contains(NSWindowOcclusionState::Visible);
+            if last_visible.replace(Some(visible)) != Some(visible) {
+                callback(visible, ctx as *mut c_void);
+            }
+        });
+        let object: &AnyObject = unsafe { &*ns_window };
+        let token = unsafe {
+            NSNotificationCenter::defaultCenter().addObserverForName_object_queue_usingBlock(
+                Some(NSWindowDidChangeOcclusionStateNotification),
+                Some(object),
+                None,
+                &block,
+            )
+        };
+        Self(token)
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for OcclusionObserver {
+    fn drop(&mut self) {
+        use objc2_foundation::NSNotificationCenter;
+        unsafe { NSNotificationCenter::defaultCenter().removeObserver(&self.0) };
+    }
+}
+
+/// macOS: local NSEvent monitor that offers the window's scroll wheel events to a mouse wheel
+/// handler before they are dispatched; handled events are dropped. Removed on drop.
+#[cfg(target_os = "macos")]
+struct WheelMonitor(objc2::rc::Retained<objc2::runtime::AnyObject>);
+
+#[cfg(target_os = "macos")]
+impl WheelMonitor {
+    fn new(window: &Window, handler: (MouseWheelCallback, usize)) -> Option<Self> {
+        use objc2_app_kit::{NSEvent, NSEventMask, NSEventModifierFlags, NSWindow};
+        use tao::platform::macos::WindowExtMacOS;
+
+        let window_number = unsafe { &*(window.ns_window() as *const NSWindow) }.windowNumber();
+        let (callback, ctx) = handler;
+        let block = block2::RcBlock::new(move |event: std::ptr::NonNull<NSEvent>| -> *mut NSEvent {
+            let e = unsafe { event.as_ref() };
+            if e.windowNumber() != window_number {
+                return event.as_ptr();
+            }
+            let flags = e.modifierFlags();
+            let mut modifiers = 0;
+            if flags.contains(NSEventModifierFlags::Shift) {
+                modifiers |= MODIFIER_SHIFT;
+            }
+            if flags.contains(NSEventModifierFlags::Control) {
+                modifiers |= MODIFIER_CONTROL;
+            }
+            if flags.contains(NSEventModifierFlags::Option) {
+                modifiers |= MODIFIER_ALT;
+            }
+            if flags.contains(NSEventModifierFlags::Command) {
+                modifiers |= MODIFIER_SUPER;
+            }
+            // Trackpads report points (logical pixels), wheels report lines.
+            let mode = if e.hasPreciseScrollingDeltas() { 1 } else { 0 };
+            let (dx, dy) = unsafe { (e.scrollingDeltaX(), e.scrollingDeltaY()) };
+            if callback(dx, dy, mode, modifiers, ctx as *mut c_void) {
+                std::ptr::null_mut()
+            } else {
+                event.as_ptr()
+            }
+        });
+        let monitor =
+            unsafe { NSEvent::addLocalMonitorForEventsMatchingMask_handler(NSEventMask::ScrollWheel, &block) };
+        if monitor.is_none() {
+            eprintln!("[wry-native] mouse wheel handler: event monitor not installed");
+        }
+        monitor.map(Self)
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for WheelMonitor {
+    fn drop(&mut self) {
+        unsafe { objc2_app_kit::NSEvent::removeMonitor(&self.0) };
+    }
+}
+
+/// Linux: offer wheel input over the webview to the mouse wheel handler before WebKitGTK handles
+/// it; handled events stop there. Deltas follow tao's convention (positive = up / left).
+#[cfg(target_os = "linux")]
+fn watch_wheel(webview: &WebView, handler: (MouseWheelCallback, usize)) {
+    use gtk::gdk::{ModifierType, ScrollDirection};
+    use gtk::glib::Propagation;
+    use gtk::prelude::WidgetExt;
+    use wry::WebViewExtUnix;
+
+    let (cb, ctx) = handler;
+    webview.webview().connect_scroll_event(move |_, event| {
+        let (dx, dy) = match event.direction() {
+            ScrollDirection::Up => (0.0, 1.0),
+            ScrollDirection::Down => (0.0, -1.0),
+            ScrollDirection::Left => (1.0, 0.0),
+            ScrollDirection::Right => (-1.0, 0.0),
+            ScrollDirection::Smooth => {
+                let (x, y) = event.delta();
+                (-x, -y)
+            }
+            _ => return Propagation::Proceed,
+        };
+        let state = event.state();
+        let mut modifiers = 0;
+        if state.contains(ModifierType::SHIFT_MASK) {
+            modifiers |= MODIFIER_SHIFT;
+        }
+        if state.contains(ModifierType::CONTROL_MASK) {
+            modifiers |= MODIFIER_CONTROL;
+        }
+        if state.contains(ModifierType::MOD1_MASK) {
+            modifiers |= MODIFIER_ALT;
+        }
+        if state.contains(ModifierType::SUPER_MASK) {
+            modifiers |= MODIFIER_SUPER;
+        }
+        if cb(dx, dy, 0, modifiers, ctx as *mut c_void) {
+            Propagation::Stop
+        } else {
+            Propagation::Proceed
+        }
+    });
+}
+
+/// Windows: hook on the shared system listener window (see listen_system_messages). Receives
+/// every message sent to it as `(msg, wparam, lparam)`.
+#[cfg(target_os = "windows")]
+pub(crate) type SystemMessageHook =
+    unsafe fn(u32, windows::Win32::Foundation::WPARAM, windows::Win32::Foundation::LPARAM);
+
+#[cfg(target_os = "windows")]
+struct SystemListener {
+    /// The hidden window once created (the raw HWND; HWND itself is not Send).
+    hwnd: Option<isize>,
+    hooks: Vec<SystemMessageHook>,
+    /// `on_registered` functions not yet run on the listener thread.
+    pending: Vec<fn(windows::Win32::Foundation::HWND)>,
+}
+
+#[cfg(target_os = "windows")]
+static SYSTEM_LISTENER: Mutex<SystemListener> =
+    Mutex::new(SystemListener { hwnd: None, hooks: Vec::new(), pending: Vec::new() });
+
+#[cfg(target_os = "windows")]
+static SYSTEM_LISTENER_STARTED: std::sync::Once = std::sync::Once::new();
+
+/// Posted to the listener window to run newly queued `on_registered` functions on its thread.
+#[cfg(target_os = "windows")]
+const WM_WRY_RUN_PENDING: u32 = windows::Win32::UI::WindowsAndMessaging::WM_APP + 1;
+
+/// Windows: route system broadcasts to `hook`. All listeners (power events, monitor changes)
+/// share one hidden top-level window, pumped by one helper thread that the first call starts.
+/// Top-level, not message-only: broadcasts such as WM_DISPLAYCHANGE and WM_POWERBROADCAST skip
+/// message-only windows. `on_registered` runs on the listener thread with the window handle
+/// (e.g. to register for session or power setting notifications).
+#[cfg(target_os = "windows")]
+pub(crate) fn listen_system_messages(hook: SystemMessageHook, on_registered: fn(windows::Win32::Foundation::HWND)) {
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::PostMessageW;
+
+    let Ok(mut listener) = SYSTEM_LISTENER.lock() else { return; };
+    listener.hooks.push(hook);
+    listener.pending.push(on_registered);
+    if let Some(hwnd) = listener.hwnd {
+        let posted = unsafe { PostMessageW(Some(HWND(hwnd as _)), WM_WRY_RUN_PENDING, WPARAM(0), LPARAM(0)) };
+        log_err!(posted, "system listener: PostMessageW");
+    }
+    drop(listener);
+    SYSTEM_LISTENER_STARTED.call_once(|| {
+        std::thread::spawn(run_system_listener);
+    });
+}
+
+/// Listener thread: create the hidden window, run the queued `on_registered` functions and pump
+/// its messages for the life of the process.
+#[cfg(target_os = "windows")]
+fn run_system_listener() {
+    use windows::core::{w, HSTRING};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DispatchMessageW, GetMessageW, RegisterClassW, TranslateMessage, MSG,
+        WINDOW_EX_STYLE, WNDCLASSW, WS_OVERLAPPED,
+    };
+
+    unsafe {
+        let instance = GetModuleHandleW(None).unwrap_or_default();
+        let class_name = w!("WryNativeSystemListener");
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(system_listener_wnd_proc),
+            hInstance: instance.into(),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        RegisterClassW(&class);
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            &HSTRING::new(),
+            WS_OVERLAPPED,
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            Some(instance.into()),
+            None,
+        );
+        log_err!(hwnd.as_ref(), "system listener: CreateWindowExW");
+        let Ok(hwnd) = hwnd else { return; };
+        // Registrations queued before the window existed; later ones arrive as WM_WRY_RUN_PENDING.
+        let pending = match SYSTEM_LISTENER.lock() {
+            Ok(mut listener) => {
+                listener.hwnd = Some(hwnd.0 as isize);
+                std::mem::take(&mut listener.pending)
+            }
+            Err(_) => return,
+        };
+        pending.into_iter().for_each(|f| f(hwnd));
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn system_listener_wnd_proc(
+    hwnd: windows::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::LRESULT;
+    use windows::Win32::UI::WindowsAndMessaging::DefWindowProcW;
+
+    // Copy out under the lock; hooks and registrations run without it.
+    let Ok(mut listener) = SYSTEM_LISTENER.lock() else {
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    };
+    if msg == WM_WRY_RUN_PENDING {
+        let pending = std::mem::take(&mut listener.pending);
+        drop(listener);
+        pending.into_iter().for_each(|f| f(hwnd));
+        return LRESULT(0);
+    }
+    let hooks = listener.hooks.clone();
+    drop(listener);
+    for hook in hooks {
+        hook(msg, wparam, lparam);
+    }
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+#[cfg(target_os = "windows")]
+const HIT_TEST_SUBCLASS_ID: usize = 0x5752_5948; // "WRYH"
+
+/// Windows: subclass the top-level window to answer WM_NCHITTEST from the hit-test handler.
+/// The boxed handler is freed on WM_NCDESTROY.
+#[cfg(target_os = "windows")]
+fn install_hit_test_subclass(window: &Window, handler: (HitTestCallback, usize)) {
+    use tao::platform::windows::WindowExtWindows;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::SetWindowSubclass;
+    let data = Box::into_raw(Box::new(handler));
+    let ok = unsafe {
+        SetWindowSubclass(
+            HWND(window.hwnd() as _),
+            Some(hit_test_subclass_proc),
+            HIT_TEST_SUBCLASS_ID,
+            data as usize,
+        )
+    };
+    if !ok.as_bool() {
+        drop(unsafe { Box::from_raw(data) });
+        eprintln!("[wry-native] hit test: SetWindowSubclass failed");
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn hit_test_subclass_proc(
+    hwnd: windows::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+    _id: usize,
+    data: usize,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::{LRESULT, POINT};
+    use windows::Win32::Graphics::Gdi::ScreenToClient;
+    use windows::Win32::UI::HiDpi::GetDpiForWindow;
+    use windows::Win32::UI::Shell::{DefSubclassProc, RemoveWindowSubclass};
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    match msg {
+        WM_NCHITTEST => {
+            let (cb, ctx) = *(data as *const (HitTestCallback, usize));
+            let mut pt = POINT {
+                x: (lparam.0 & 0xFFFF) as i16 as i32,
+                y: ((lparam.0 >> 16) & 0xFFFF) as i16 as i32,
+            };
+            if ScreenToClient(hwnd, &mut pt).as_bool() {
+                let dpi = GetDpiForWindow(hwnd);
+                let scale = if dpi == 0 { 1.0 } else { dpi as f64 / 96.0 };
+                let x = (pt.x as f64 / scale).round() as c_int;
+                let y = (pt.y as f64 / scale).round() as c_int;
+                let ht = match cb(x, y, ctx as *mut c_void) {
+                    HIT_TEST_CAPTION => Some(HTCAPTION),
+                    2 => Some(HTLEFT),
+                    3 => Some(HTTOP),
+                    4 => Some(HTRIGHT),
+                    5 => Some(HTBOTTOM),
+                    6 => Some(HTTOPLEFT),
+                    7 => Some(HTTOPRIGHT),
+                    8 => Some(HTBOTTOMLEFT),
+                    9 => Some(HTBOTTOMRIGHT),
+                    _ => None,
+                };
+                if let Some(ht) = ht {
+                    return LRESULT(ht as isize);
+                }
+            }
+        }
+        WM_NCDESTROY => {
+            let _ = RemoveWindowSubclass(hwnd, Some(hit_test_subclass_proc), HIT_TEST_SUBCLASS_ID);
+            drop(Box::from_raw(data as *mut (HitTestCallback, usize)));
+        }
+        _ => {}
+    }
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+}
+
+/// Grow or shrink the window rect being dragged by `edge` (WM_SIZING's WMSZ_* value) so its client
+/// area keeps `ratio` (width / height). `frame` is the (width, height) the frame adds to the client.
+/// Side edges set the height and top / bottom edges the width; corners follow the width.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn constrain_aspect(
+    edge: u32,
+    rect: (i32, i32, i32, i32),
+    frame: (i32, i32),
+    ratio: f64,
+) -> (i32, i32, i32, i32) {
+    const TOP: u32 = 3;
+    const TOP_LEFT: u32 = 4;
+    const TOP_RIGHT: u32 = 5;
+    const BOTTOM: u32 = 6;
+    let (left, mut top, mut right, mut bottom) = rect;
+    let client_w = (right - left - frame.0).max(1);
+    let client_h = (bottom - top - frame.1).max(1);
+    match edge {
+        TOP | BOTTOM => right = left + (client_h as f64 * ratio).round() as i32 + frame.0,
+        TOP_LEFT | TOP_RIGHT => top = bottom - (client_w as f64 / ratio).round() as i32 - frame.1,
+        // Left / right edges and the bottom corners.
+        _ => bottom = top + (client_w as f64 / ratio).round() as i32 + frame.1,
+    }
+    (left, top, right, bottom)
+}
+
+/// Subclass ID for the aspect-ratio WM_SIZING handler.
+#[cfg(target_os = "windows")]
+const ASPECT_SUBCLASS_ID: usize = 0x5752_5941; // "WRYA"
+
+/// Windows: install (or update, or with None remove) the WM_SIZING subclass. The ratio travels as
+/// f32 bits in the subclass reference data, so there is nothing to free.
+#[cfg(target_os = "windows")]
+fn set_aspect_ratio_subclass(window: &Window, ratio: Option<f64>) {
+    use tao::platform::windows::WindowExtWindows;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::{RemoveWindowSubclass, SetWindowSubclass};
+    let hwnd = HWND(window.hwnd() as _);
+    unsafe {
+        match ratio {
+            Some(ratio) => {
+                let data = (ratio as f32).to_bits() as usize;
+                if !SetWindowSubclass(hwnd, Some(aspect_subclass_proc), ASPECT_SUBCLASS_ID, data).as_bool() {
+                    eprintln!("[wry-native] aspect ratio: SetWindowSubclass failed");
+                }
+            }
+            None => {
+                let _ = RemoveWindowSubclass(hwnd, Some(aspect_subclass_proc), ASPECT_SUBCLASS_ID);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn aspect_subclass_proc(
+    hwnd: windows::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+    _id: usize,
+    data: usize,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::{LRESULT, RECT};
+    use windows::Win32::UI::Shell::{DefSubclassProc, RemoveWindowSubclass};
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    match msg {
+        WM_SIZING => {
+            let ratio = f32::from_bits(data as u32) as f64;
+            let rect = &mut *(lparam.0 as *mut RECT);
+            let (mut outer, mut client) = (RECT::default(), RECT::default());
+            if GetWindowRect(hwnd, &mut outer).is_ok() && GetClientRect(hwnd, &mut client).is_ok() {
+                let frame = (
+                    (outer.right - outer.left) - (client.right - client.left),
+                    (outer.bottom - outer.top) - (client.bottom - client.top),
+                );
+                let (left, top, right, bottom) = constrain_aspect(
+                    wparam.0 as u32,
+                    (rect.left, rect.top, rect.right, rect.bottom),
+                    frame,
+                    ratio,
+                );
+                *rect = RECT { left, top, right, bottom };
+                return LRESULT(1);
+            }
+        }
+        WM_NCDESTROY => {
+            let _ = RemoveWindowSubclass(hwnd, Some(aspect_subclass_proc), ASPECT_SUBCLASS_ID);
+        }
+        _ => {}
+    }
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+}
+
+/// `s` as a NUL-terminated UTF-16 buffer of fixed size, truncated to N - 1 units (never splitting
+/// a surrogate pair). For Win32 structs with inline string fields such as THUMBBUTTON::szTip.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn to_wide_fixed<const N: usize>(s: &str) -> [u16; N] {
+    let mut out = [0u16; N];
+    let mut len = 0;
+    for c in s.chars() {
+        let mut units = [0u16; 2];
+        let units = c.encode_utf16(&mut units);
+        if len + units.len() > N.saturating_sub(1) {
+            break;
+        }
+        out[len..len + units.len()].copy_from_slice(units);
+        len += units.len();
+    }
+    out
+}
+
+/// Subclass ID for the thumbnail toolbar WM_COMMAND handler.
+#[cfg(target_os = "windows")]
+const THUMBNAIL_SUBCLASS_ID: usize = 0x5752_5954; // "WRYT"
+
+/// Windows: route thumbnail toolbar clicks (WM_COMMAND / THBN_CLICKED) to `handler`, replacing a
+/// previous handler, or remove the routing with None. The boxed handler is freed on replacement or
+/// WM_NCDESTROY.
+#[cfg(target_os = "windows")]
+fn set_thumbnail_subclass(window: &Window, handler: Option<(ThumbnailButtonCallback, usize)>) {
+    use tao::platform::windows::WindowExtWindows;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::{GetWindowSubclass, RemoveWindowSubclass, SetWindowSubclass};
+    let hwnd = HWND(window.hwnd() as _);
+    unsafe {
+        let mut old = 0usize;
+        let had_old = GetWindowSubclass(hwnd, Some(thumbnail_subclass_proc), THUMBNAIL_SUBCLASS_ID, Some(&mut old)).as_bool();
+        match handler {
+            Some(handler) => {
+                let data = Box::into_raw(Box::new(handler));
+                if !SetWindowSubclass(hwnd, Some(thumbnail_subclass_proc), THUMBNAIL_SUBCLASS_ID, data as usize).as_bool() {
+                    drop(Box::from_raw(data));
+                    eprintln!("[wry-native] thumbnail toolbar: SetWindowSubclass failed");
+                    return;
+                }
+            }
+            None => {
+                let _ = RemoveWindowSubclass(hwnd, Some(thumbnail_subclass_proc), THUMBNAIL_SUBCLASS_ID);
+            }
+        }
+        if had_old && old != 0 {
+            drop(Box::from_raw(old as *mut (ThumbnailButtonCallback, usize)));
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn thumbnail_subclass_proc(
+    hwnd: windows::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+    _id: usize,
+    data: usize,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::UI::Shell::{DefSubclassProc, RemoveWindowSubclass};
+    use windows::Win32::UI::WindowsAndMessaging::{WM_COMMAND, WM_NCDESTROY};
+    /// HIWORD(wParam) of the WM_COMMAND a thumbnail button click sends.
+    const THBN_CLICKED: usize = 0x1800;
+
+    match msg {
+        WM_COMMAND if (wparam.0 >> 16) & 0xFFFF == THBN_CLICKED => {
+            let (cb, ctx) = *(data as *const (ThumbnailButtonCallback, usize));
+            cb((wparam.0 & 0xFFFF) as u16 as c_int, ctx as *mut c_void);
+        }
+        WM_NCDESTROY => {
+            let _ = RemoveWindowSubclass(hwnd, Some(thumbnail_subclass_proc), THUMBNAIL_SUBCLASS_ID);
+            drop(Box::from_raw(data as *mut (ThumbnailButtonCallback, usize)));
+        }
+        _ => {}
+    }
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+}
+
+// ---------------------------------------------------------------------------
+// WryApp -- application-level state
+// ---------------------------------------------------------------------------
+
+pub struct WryApp {
+    event_loop: Option<EventLoop<UserEvent>>,
+    pub(crate) proxy: EventLoopProxy<UserEvent>,
+    windows: HashMap<usize, WryWindow>,
+    payloads: HashMap<usize, WindowCreatePayload>,
+    next_window_id: usize,
+    pub(crate) trays: HashMap<usize, WryTray>,
+    pub(crate) tray_payloads: HashMap<usize, tray::TrayCreatePayload>,
+    pub(crate) next_tray_id: usize,
+    exit_requested_handler: Option<(ExitRequestedCallback, usize)>,
+    /// Set to true when the event loop is running (inside run_return). Used to decide initial vs dynamic window creation.
+    run_started: Arc<AtomicBool>,
+    /// Called when a window is materialized and live (initial or dynamic).
+    window_created_handler: Option<(WindowCreatedCallback, usize)>,
+    /// Called when dynamic window creation fails (async path only).
+    window_creation_error_handler: Option<(WindowCreationErrorCallback, usize)>,
+    window_destroyed_handler: Option<(WindowDestroyedCallback, usize)>,
+    suspend_handler: Option<(LifecycleCallback, usize)>,
+    resume_handler: Option<(LifecycleCallback, usize)>,
+    power_event_handler: Option<(PowerEventCallback, usize)>,
+    monitors_changed_handler: Option<(LifecycleCallback, usize)>,
+    /// When false, closing the last window keeps the loop running while a tray icon is live.
+    exit_on_last_window_closed: Arc<AtomicBool>,
+    /// Base control flow (wry_app_set_control_flow): CONTROL_FLOW_WAIT, CONTROL_FLOW_POLL or a
+    /// positive wake-up interval in milliseconds.
+    base_control_flow: Arc<AtomicI64>,
+    /// Origin -> zoom map shared by windows created with zoom_persistence.
+    zoom_store: ZoomStore,
+    /// Window ID -> page-load state, for wry_window_wait_for_load / wry_window_is_loading on other
+    /// threads. Weak so a destroyed window's waiters wake; dead entries are pruned on insert.
+    load_states: Mutex<HashMap<usize, Weak<Mutex<LoadState>>>>,
+    /// Next ID handed out by wry_app_set_interval. Atomic because timers may be set from any thread.
+    next_timer_id: AtomicUsize,
+    /// Held keep-awake assertion (wry_app_set_keep_awake); dropping it releases the assertion.
+    keep_awake: Mutex<Option<power::KeepAwake>>,
+    /// Opaque host pointer (wry_app_set_user_data). Never dereferenced here.
+    user_data: AtomicUsize,
+    /// Thread that created the event loop and runs wry_app_run.
+    loop_thread: std::thread::ThreadId,
+}
+
+// Safety: WryApp is only accessed from the main thread. The proxy field is
+// Send by design (it's the whole point of EventLoopProxy). We need this
+// because the raw pointer in wry_window_dispatch comes from any thread but
+// only accesses the proxy.
+unsafe impl Send for WryApp {}
+unsafe impl Sync for WryApp {}
+
+// ---------------------------------------------------------------------------
+// Helper: read a C string into a Rust String, returning empty on null.
+// ---------------------------------------------------------------------------
+
+pub(crate) unsafe fn c_str_to_string(s: *const c_char) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .unwrap_or("")
+        .to_string()
+}
+
+// ---------------------------------------------------------------------------
+// ===========================================================================
+// EXPORTED C API
+// ===========================================================================
+
+// ---------------------------------------------------------------------------
+// App lifecycle
+// ---------------------------------------------------------------------------
+
+/// Create a new application. Returns an opaque handle.
+#[no_mangle]
+pub extern "C" fn wry_app_new() -> *mut WryApp {
+    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
+    let proxy = event_loop.create_proxy();
+    let app = WryApp {
+        event_loop: Some(event_loop),
+        proxy,
+        windows: HashMap::new(),
+        payloads: HashMap::new(),
+        next_window_id: 1,
+        trays: HashMap::new(),
+        tray_payloads: HashMap::new(),
+        next_tray_id: 1,
+        exit_requested_handler: None,
+        run_started: Arc::new(AtomicBool::new(false)),
+        window_created_handler: None,
+        window_creation_error_handler: None,
+        window_destroyed_handler: None,
+        suspend_handler: None,
+        resume_handler: None,
+        power_event_handler: None,
+        monitors_changed_handler: None,
+        exit_on_last_window_closed: Arc::new(AtomicBool::new(true)),
+        base_control_flow: Arc::new(AtomicI64::new(CONTROL_FLOW_WAIT)),
+        zoom_store: Arc::new(Mutex::new(HashMap::new())),
+        load_states: Mutex::new(HashMap::new()),
+        next_timer_id: AtomicUsize::new(1),
+        keep_awake: Mutex::new(None),
+        user_data: AtomicUsize::new(0),
+        loop_thread: std::thread::current().id(),
+    };
+    Box::into_raw(Box::new(app))
+}
+
+/// Resolve a WryWindow ID (owner / parent references in a create payload) to its live window.
+fn live_window<'a>(
+    id: usize,
+    id_to_window_id: &HashMap<usize, WindowId>,
+    live_windows: &'a HashMap<WindowId, WryWindow>,
+) -> Option<&'a WryWindow> {
+    id_to_window_id.get(&id).and_then(|wid| live_windows.get(wid))
+}
+
+/// Remove windows whose tao window was dropped (wry_window_close) from the event loop's maps,
+/// including their `id_to_window_id` entry so owner / parent lookups cannot find them. Returns
+/// their IDs.
+fn forget_closed_windows(
+    live_windows: &mut HashMap<WindowId, WryWindow>,
+    id_to_window_id: &mut HashMap<usize, WindowId>,
+) -> Vec<usize> {
+    let closed: Vec<WindowId> =
+        live_windows.iter().filter(|(_, w)| w.window.is_none()).map(|(wid, _)| *wid).collect();
+    closed
+        .into_iter()
+        .filter_map(|wid| live_windows.remove(&wid))
+        .map(|win| {
+            id_to_window_id.remove(&win.id);
+            win.id
+        })
+        .collect()
+}
+
+/// Run the application event loop. This blocks the calling thread until all
+/// windows are closed. Must be called on the main thread.
+#[no_mangle]
+pub extern "C" fn wry_app_run(app: *mut WryApp) {
+    if app.is_null() {
+        return;
+    }
+    let app = unsafe { &mut *app };
+
+    let mut event_loop = match app.event_loop.take() {
+        Some(el) => el,
+        None => return, // already consumed
+    };
+
+    let mut pending_windows: Vec<WryWindow> = app.windows.drain().map(|(_, w)| w).collect();
+    let mut pending_payloads: HashMap<usize, WindowCreatePayload> = app.payloads.drain().collect();
+    let mut live_windows: HashMap<WindowId, WryWindow> = HashMap::new();
+    let mut id_to_window_id: HashMap<usize, WindowId> = HashMap::new();
+
+    // Move trays out of the app struct.
+    let mut pending_trays: Vec<WryTray> = app.trays.drain().map(|(_, t)| t).collect();
+    let mut pending_tray_payloads: HashMap<usize, tray::TrayCreatePayload> = app.tray_payloads.drain().collect();
+    let mut live_trays: HashMap<usize, WryTray> = HashMap::new();
+    let mut timers: HashMap<usize, AppTimer> = HashMap::new();
+    // Tray dispatches that arrive before their tray is materialized; flushed right after create().
+    let mut queued_tray_dispatches: HashMap<usize, Vec<(TrayDispatchCallback, usize)>> = HashMap::new();
+
+    // Exit-requested callback (fired when all windows are closed).
+    let exit_requested_handler = app.exit_requested_handler.take();
+    let window_created_handler = app.window_created_handler.take();
+    let window_creation_error_handler = app.window_creation_error_handler.take();
+    let window_destroyed_handler = app.window_destroyed_handler.take();
+    let suspend_handler = app.suspend_handler.take();
+    let resume_handler = app.resume_handler.take();
+    let power_event_handler = app.power_event_handler.take();
+    let monitors_changed_handler = app.monitors_changed_handler.take();
+
+    let run_started = app.run_started.clone();
+    let exit_on_last_window_closed = app.exit_on_last_window_closed.clone();
+    let base_control_flow = app.base_control_flow.clone();
+    // Handed to app dispatch callbacks; the handle outlives wry_app_run.
+    let app_ptr = app as *mut WryApp;
+    let proxy = app.proxy.clone();
+
+    // Wire up tray icon / menu event handlers to forward into the event loop.
+    tray::setup_tray_event_handlers(&app.proxy);
+    if power_event_handler.is_some() {
+        power::start_power_listener(&app.proxy);
+    }
+    if monitors_changed_handler.is_some() {
+        monitors::start_monitor_listener(&app.proxy);
+    }
+
+    // Use run_return so we return to the caller instead of calling process::exit.
+    event_loop.run_return(move |event, event_loop_target, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        run_started.store(true, Ordering::SeqCst);
+
+        match event {
+            Event::NewEvents(StartCause::Init) => {
+                pending_windows.sort_by_key(|w| w.id);
+                for mut win in pending_windows.drain(..) {
+                    let payload = match pending_payloads.remove(&win.id) {
+                        Some(p) => p,
+                        None => continue,
+                    };
+                    let owner_window = payload.owner_window_id
+                        .and_then(|oid| live_window(oid, &id_to_window_id, &live_windows))
+                        .and_then(|w| w.window.as_ref());
+                    let parent_window = payload.parent_window_id
+                        .and_then(|pid| live_window(pid, &id_to_window_id, &live_windows))
+                        .and_then(|w| w.window.as_ref());
+                    match win.create(&payload, event_loop_target, &proxy, owner_window, parent_window) {
+                        Ok(()) => {
+                            if let Some(wid) = win.window_id {
+                                let our_id = win.id;
+                                id_to_window_id.insert(our_id, wid);
+                                live_windows.insert(wid, win);
+                                if let Some((cb, ctx)) = window_created_handler.as_ref() {
+                                    if let Some(win_ref) = live_windows.get_mut(&wid) {
+                                        cb(*ctx as *mut c_void, our_id, win_ref as *mut WryWindow);
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            let our_id = win.id;
+                            if let Some((cb, ctx)) = window_creation_error_handler.as_ref() {
+                                if let Ok(c_msg) = CString::new(e.as_str()) {
+                                    cb(*ctx as *mut c_void, our_id, c_msg.as_ptr());
+                                }
+                            }
+                        }
+                    }
+                }
+                // Materialize all pending tray icons.
+                for mut tray in pending_trays.drain(..) {
+                    let our_id = tray.id;
+                    if let Some(payload) = pending_tray_payloads.remove(&our_id) {
+                        tray.create(&payload);
+                    }
+                    for (callback, ctx) in queued_tray_dispatches.remove(&our_id).unwrap_or_default() {
+                        tray.handle_dispatch(callback, ctx);
+                    }
+                    live_trays.insert(our_id, tray);
+                }
+            }
+
+            Event::WindowEvent {
+                event: ref win_event,
+                window_id,
+                ..
+            } => {
+                // Edge snapping: collect sibling rects before `win` borrows live_windows mutably.
+                let snapping = matches!(win_event, WindowEvent::Moved(_))
+                    && live_windows.get(&window_id).is_some_and(|w| w.snap_threshold.is_some());
+                let snap_siblings: Vec<(i32, i32, i32, i32)> = if snapping {
+                    live_windows
+                        .iter()
+                        .filter(|(id, _)| **id != window_id)
+                        .filter_map(|(_, w)| w.snap_target_rect())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                if let Some(win) = live_windows.get_mut(&window_id) {
+                    #[cfg(target_os = "linux")]
+                    win.reapply_skip_taskbar();
+                    match win_event {
+                        WindowEvent::CloseRequested => {
+                            let allow = if let Some((cb, ctx)) = win.close_handler {
+                                cb(ctx as *mut c_void)
+                            } else {
+                                true
+                            };
+                            if allow {
+                                let our_id = win.id;
+                                id_to_window_id.remove(&our_id);
+                                live_windows.remove(&window_id);
+                                if should_exit(
+                                    ExitTrigger::WindowClosed,
+                                    live_windows.len(),
+                                    live_trays.len(),
+                                    exit_on_last_window_closed.load(Ordering::SeqCst),
+                                ) {
+                                    let should_exit = if let Some((cb, ctx)) = exit_requested_handler {
+                                        cb(false, 0, ctx as *mut c_void)
+                                    } else {
+                                        true
+                                    };
+                                    if should_exit {
+                                        live_trays.clear();
+                                        *control_flow = ControlFlow::Exit;
+                                    }
+                                }
+                            }
+                        }
+                        WindowEvent::Destroyed => {
+                            // Window was destroyed (e.g. by OS when owner closed). Notify C#, then remove from state like Tauri.
+                            let our_id = live_windows.get(&window_id).map(|w| w.id);
+                            if let Some(oid) = our_id {
+                                if let Some((cb, ctx)) = window_destroyed_handler.as_ref() {
+                                    cb(*ctx as *mut c_void, oid);
+                                }
+                                id_to_window_id.remove(&oid);
+                                live_windows.remove(&window_id);
+                                if should_exit(
+                                    ExitTrigger::WindowClosed,
+                                    live_windows.len(),
+                                    live_trays.len(),
+                                    exit_on_last_window_closed.load(Ordering::SeqCst),
+                                ) {
+                                    let should_exit = if let Some((cb, ctx)) = exit_requested_handler {
+                                        cb(false, 0, ctx as *mut c_void)
+                                    } else {
+                                        true
+                                    };
+                                    if should_exit {
+                                        live_trays.clear();
+                                        *control_flow = ControlFlow::Exit;
+                                    }
+                                }
+                            }
+                        }
+                        WindowEvent::Resized(size) => {
+                            if let Some((cb, ctx)) = win.resize_handler {
+                                cb(
+                                    size.width as c_int,
+                                    size.height as c_int,
+                                    ctx as *mut c_void,
+                                );
+                            }
+                            win.update_tiled();
+                        }
+                        WindowEvent::Moved(pos) => {
+                            if let Some((cb, ctx)) = win.move_handler {
+                                cb(pos.x as c_int, pos.y as c_int, ctx as *mut c_void);
+                            }
+                            win.apply_edge_snapping(&snap_siblings);
+                            win.update_tiled();
+                            win.update_monitor();
+                        }
+                        WindowEvent::Focused(focused) => {
+                            if let Some((cb, ctx)) = win.focus_handler {
+                                cb(*focused, ctx as *mut c_void);
+                            }
+                            win.set_background_throttled(!*focused);
+                        }
+                        WindowEvent::ModifiersChanged(state) => {
+                            win.modifiers = modifier_flags(*state);
+                        }
+                        WindowEvent::CursorMoved { position, .. } => {
+                            let scale = win.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
+                            let logical = position.to_logical::<f64>(scale);
+                            win.cursor_position = (logical.x, logical.y);
+                            if let Some((cb, ctx)) = win.cursor_moved_handler {
+                                cb(logical.x, logical.y, ctx as *mut c_void);
+                            }
+                        }
+                        WindowEvent::MouseInput { state, button, .. } => {
+                            if let Some((cb, ctx)) = win.mouse_button_handler {
+                                use tao::event::{ElementState, MouseButton};
+                                let button = match button {
+                                    MouseButton::Left => Some(0),
+                                    MouseButton::Right => Some(1),
+                                    MouseButton::Middle => Some(2),
+                                    MouseButton::Other(n) => Some(3 + *n as c_int),
+                                    #[allow(unreachable_patterns)]
+                                    _ => None,
+                                };
+                                if let Some(button) = button {
+                                    let (x, y) = win.cursor_position;
+                                    let pressed = *state == ElementState::Pressed;
+                                    cb(button, pressed, x, y, win.modifiers, ctx as *mut c_void);
+                                }
+                            }
+                        }
+                        WindowEvent::Touch(touch) => {
+                            if let Some((cb, ctx)) = win.touch_handler {
+                                use tao::event::TouchPhase;
+                                let phase = match touch.phase {
+                                    TouchPhase::Started => Some(0),
+                                    TouchPhase::Moved => Some(1),
+                                    TouchPhase::Ended => Some(2),
+                                    TouchPhase::Cancelled => Some(3),
+                                    #[allow(unreachable_patterns)]
+                                    _ => None,
+                                };
+                                if let Some(phase) = phase {
+                                    let scale = win.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
+                                    let logical = touch.location.to_logical::<f64>(scale);
+                                    cb(phase, touch.id, logical.x, logical.y, ctx as *mut c_void);
+                                }
+                            }
+                        }
+                        // macOS: the wheel monitor reports (and may consume) these before tao does.
+                        WindowEvent::MouseWheel { delta, .. } if !cfg!(target_os = "macos") => {
+                            if let Some((cb, ctx)) = win.mouse_wheel_handler {
+                                use tao::event::MouseScrollDelta;
+                                let scroll = match delta {
+                                    MouseScrollDelta::LineDelta(x, y) => Some((*x as f64, *y as f64, 0)),
+                                    MouseScrollDelta::PixelDelta(p) => {
+                                        let scale = win.window.as_ref().map(|w| w.scale_factor()).unwrap_or(1.0);
+                                        Some((p.x / scale, p.y / scale, 1))
+                                    }
+                                    #[allow(unreachable_patterns)]
+                                    _ => None,
+                                };
+                                // Nothing further handles window-level wheel input, so the result is unused.
+                                if let Some((dx, dy, mode)) = scroll {
+                                    let _ = cb(dx, dy, mode, win.modifiers, ctx as *mut c_void);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            Event::Suspended => {
+                if let Some((cb, ctx)) = suspend_handler {
+                    cb(ctx as *mut c_void);
+                }
+            }
+
+            Event::Resumed => {
+                if let Some((cb, ctx)) = resume_handler {
+                    cb(ctx as *mut c_void);
+                }
+            }
+
+            // All events of this loop iteration are handled: run the batched scripts. Again after
+            // redraws, so scripts queued by redraw handlers do not wait for the next wake-up.
+            Event::MainEventsCleared | Event::RedrawEventsCleared => {
+                for win in live_windows.values_mut() {
+                    win.flush_eval_batch();
+                }
+            }
+
+            Event::RedrawRequested(window_id) => {
+                if let Some(win) = live_windows.get(&window_id) {
+                    if let Some((cb, ctx)) = win.redraw_handler {
+                        cb(ctx as *mut c_void);
+                    }
+                }
+            }
+
+            Event::UserEvent(user_event) => match user_event {
+                UserEvent::Dispatch {
+                    window_id: our_id,
+                    callback,
+                    ctx,
+                    done,
+                } => {
+                    // A callback that closes the window (wry_window_close) is cleaned up below.
+                    if let Some(wid) = id_to_window_id.get(&our_id).copied() {
+                        if let Some(win) = live_windows.get_mut(&wid) {
+                            let win_ptr = win as *mut WryWindow;
+                            callback(win_ptr, ctx as *mut c_void);
+                            if let Some(done) = done {
+                                let _ = done.send(());
+                            }
+                        }
+                    }
+                }
+
+                UserEvent::FirstPaint { window_id: our_id } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get_mut(wid) {
+                            win.show_when_ready_now();
+                            if let Some((cb, ctx)) = win.first_paint_handler {
+                                cb(ctx as *mut c_void);
+                            }
+                        }
+                    }
+                }
+
+                UserEvent::HitTest { window_id: our_id, x, y, clicks } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get(wid) {
+                            win.apply_hit_test(x, y, clicks);
+                        }
+                    }
+                }
+
+                UserEvent::PageLoaded { window_id: our_id, url } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get_mut(wid) {
+                            win.page_loaded(&url);
+                        }
+                    }
+                }
+
+                UserEvent::HtmlFullscreen { window_id: our_id, entered } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get(wid) {
+                            win.html_fullscreen_changed(entered);
+                        }
+                    }
+                }
+
+                UserEvent::ResetWebview { window_id: our_id } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get_mut(wid) {
+                            log_err!(win.reset_webview(), "reset webview");
+                        }
+                    }
+                }
+
+                UserEvent::NavigateAndWait { window_id: our_id, url, reply } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get(wid) {
+                            win.navigate_and_notify(&url, reply);
+                        }
+                    }
+                }
+
+                UserEvent::DocumentTitleChanged { window_id: our_id, title } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get(wid) {
+                            if win.auto_title {
+                                if let Some(ref w) = win.window {
+                                    w.set_title(&title);
+                                }
+                            }
+                            if let Some((cb, ctx)) = win.title_changed_handler {
+                                if let Ok(c_title) = CString::new(title.as_str()) {
+                                    cb(c_title.as_ptr(), ctx as *mut c_void);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                UserEvent::FaviconChanged { window_id: our_id, png } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get(wid) {
+                            if let Some((cb, ctx)) = win.favicon_changed_handler {
+                                match png {
+                                    Some(ref bytes) => cb(bytes.as_ptr(), bytes.len() as c_int, ctx as *mut c_void),
+                                    None => cb(std::ptr::null(), 0, ctx as *mut c_void),
+                                }
+                            }
+                        }
+                    }
+                }
+
+                UserEvent::AppRegionDrag { window_id: our_id, clicks } => {
+                    if let Some(wid) = id_to_window_id.get(&our_id) {
+                        if let Some(win) = live_windows.get(wid) {
+                            win.caption_mouse_down(clicks);
+                        }
+                    }
+                }
+
+                UserEvent::AppDispatch { callback, ctx } => {
+                    callback(app_ptr, ctx as *mut c_void);
+                }
+
+                UserEvent::TrayEvent(ref event) => {
+                    if let Ok(our_id) = event.id().as_ref().parse::<usize>() {
+                        if let Some(t) = live_trays.get_mut(&our_id) {
+                            t.handle_tray_event(event);
+                            let left_click = matches!(
+                                event,
+                                tray_icon::TrayIconEvent::Click {
+                                    button: tray_icon::MouseButton::Left,
+                                    button_state: tray_icon::MouseButtonState::Up,
+                                    ..
+                                }
+                            );
+                            if left_click && t.activate_window_id != 0 {
+                                if let Some(wid) = id_to_window_id.get(&t.activate_window_id) {
+                                    if let Some(win) = live_windows.get_mut(wid) {
+                                        win.show_and_focus();
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                UserEvent::TrayMenuEvent(ref event) => {
+                    let menu_id: &str = event.id.as_ref();
+                    match live_trays.values().find(|t| t.live_items.contains_key(menu_id)) {
+                        Some(t) => t.handle_menu_event(menu_id),
+                        None => tray::handle_dock_menu_event(menu_id),
+                    }
+                }
+
+                UserEvent::TrayDispatch { tray_id, callback, ctx } => {
+                    if let Some(t) = live_trays.get_mut(&tray_id) {
+                        t.handle_dispatch(callback, ctx);
+                    } else if pending_tray_payloads.contains_key(&tray_id) {
+                        queued_tray_dispatches.entry(tray_id).or_default().push((callback, ctx));
+                    }
+                }
+
+                UserEvent::TrayRemove { tray_id } => {
+                    if live_trays.remove(&tray_id).is_some()
+                        && should_exit(
+                            ExitTrigger::TrayRemoved,
+                            live_windows.len(),
+                            live_trays.len(),
+                            exit_on_last_window_closed.load(Ordering::SeqCst),
+                        )
+                    {
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
+
+                UserEvent::SetInterval { timer_id, interval, callback, ctx } => {
+                    timers.insert(timer_id, AppTimer {
+                        interval,
+                        next_due: Instant::now() + interval,
+                        callback,
+                        ctx,
+                    });
+                }
+
+                UserEvent::ClearInterval { timer_id } => {
+                    timers.remove(&timer_id);
+                }
+
+                UserEvent::SetBadgeCount { count } => {
+                    for win in live_windows.values() {
+                        win.apply_badge_count(count);
+                    }
+                }
+
+                UserEvent::Wake => {}
+
+                UserEvent::PowerEvent { kind } => {
+                    if let Some((cb, ctx)) = power_event_handler {
+                        cb(kind, ctx as *mut c_void);
+                    }
+                }
+
+                UserEvent::MonitorsChanged => {
+                    if let Some((cb, ctx)) = monitors_changed_handler {
+                        cb(ctx as *mut c_void);
+                    }
+                }
+
+                UserEvent::GetTrayIds { callback, ctx } => {
+                    let mut ids: Vec<usize> = live_trays.keys().copied().collect();
+                    ids.sort_unstable();
+                    let ptr = if ids.is_empty() { std::ptr::null() } else { ids.as_ptr() };
+                    callback(ptr, ids.len() as c_int, ctx as *mut c_void);
+                }
+
+                UserEvent::RequestExit { code } => {
+                    let should_exit = if let Some((cb, ctx)) = exit_requested_handler {
+                        cb(true, code, ctx as *mut c_void)
+                    } else {
+                        true
+                    };
+                    if should_exit {
+                        live_trays.clear();
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
+
+                UserEvent::CreateWindowWithConfig {
+                    id: our_id,
+                    payload,
+                } => {
+                    let owner_window = payload.owner_window_id
+                        .and_then(|oid| live_window(oid, &id_to_window_id, &live_windows))
+                        .and_then(|w| w.window.as_ref());
+                    let parent_window = payload.parent_window_id
+                        .and_then(|pid| live_window(pid, &id_to_window_id, &live_windows))
+                        .and_then(|w| w.window.as_ref());
+                    let mut win = WryWindow::new(our_id);
+                    match win.create(&payload, event_loop_target, &proxy, owner_window, parent_window) {
+                        Ok(()) => {
+                            if let Some(wid) = win.window_id {
+                                id_to_window_id.insert(our_id, wid);
+                                live_windows.insert(wid, win);
+                                if let Some((cb, ctx)) = window_created_handler.as_ref() {
+                                    if let Some(win_ref) = live_windows.get_mut(&wid) {
+                                        cb(*ctx as *mut c_void, our_id, win_ref as *mut WryWindow);
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            if let Some((cb, ctx)) = window_creation_error_handler.as_ref() {
+                                if let Ok(c_msg) = CString::new(e.as_str()) {
+                                    cb(*ctx as *mut c_void, our_id, c_msg.as_ptr());
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+
+            _ => {}
+        }
+
+        // Windows closed by wry_window_close (from a dispatch or any other callback): notify like
+        // the Destroyed path does. The platform's own Destroyed event for them then finds nothing.
+        let closed = forget_closed_windows(&mut live_windows, &mut id_to_window_id);
+        if !closed.is_empty() {
+            if let Some((cb, ctx)) = window_destroyed_handler.as_ref() {
+                for &our_id in &closed {
+                    cb(*ctx as *mut c_void, our_id);
+                }
+            }
+            if should_exit(
+                ExitTrigger::WindowClosed,
+                live_windows.len(),
+                live_trays.len(),
+                exit_on_last_window_closed.load(Ordering::SeqCst),
+            ) {
+                let should_exit = if let Some((cb, ctx)) = exit_requested_handler {
+                    cb(false, 0, ctx as *mut c_void)
+                } else {
+                    true
+                };
+                if should_exit {
+                    live_trays.clear();
+                    *control_flow = ControlFlow::Exit;
+                }
+            }
+        }
+
+        // Event-loop driven timers (tray animations, intervals, show_when_ready fallback): sleep
+        // until the earliest deadline.
+        if *control_flow == ControlFlow::Wait {
+            let now = Instant::now();
+            let mut next_due = live_trays.values_mut().filter_map(|t| t.tick_animation(now)).min();
+            for win in live_windows.values_mut() {
+                match win.show_when_ready {
+                    Some(deadline) if now >= deadline => win.show_when_ready_now(),
+                    Some(deadline) => next_due = Some(next_due.map_or(deadline, |d| d.min(deadline))),
+                    None => {}
+                }
+            }
+            for (id, timer) in timers.iter_mut() {
+                if now >= timer.next_due {
+                    (timer.callback)(*id, timer.ctx as *mut c_void);
+                    // Skip missed ticks rather than bursting to catch up.
+                    timer.next_due = (timer.next_due + timer.interval).max(now);
+                }
+                next_due = Some(next_due.map_or(timer.next_due, |d| d.min(timer.next_due)));
+            }
+            if let Some(deadline) = next_due {
+                *control_flow = ControlFlow::WaitUntil(deadline);
+            }
+        }
+
+        // Base control flow from wry_app_set_control_flow; never overrides an exit.
+        if *control_flow != ControlFlow::Exit {
+            match base_control_flow.load(Ordering::SeqCst) {
+                CONTROL_FLOW_POLL => *control_flow = ControlFlow::Poll,
+                millis if millis > 0 => {
+                    let deadline = Instant::now() + Duration::from_millis(millis as u64);
+                    *control_flow = match *control_flow {
+                        ControlFlow::WaitUntil(d) => ControlFlow::WaitUntil(d.min(deadline)),
+                        _ => ControlFlow::WaitUntil(deadline),
+                    };
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// wry_app_set_control_flow: sleep until an event arrives (the default).
+const CONTROL_FLOW_WAIT: i64 = 0;
+/// wry_app_set_control_flow: never sleep.
+const CONTROL_FLOW_POLL: i64 = -1;
+
+/// Register a callback that fires when all windows have closed or when
+/// `wry_app_exit` is called. The callback receives `has_code` (false for
+/// user-initiated, true for programmatic), `code` (the exit code when
+/// has_code is true), and the context pointer. Return true to allow exit,
+/// false to prevent it. Must be called before `wry_app_run`.
+#[no_mangle]
+pub extern "C" fn wry_app_on_exit_requested(
+    app: *mut WryApp,
+    callback: ExitRequestedCallback,
+    ctx: *mut c_void,
+) {
+    if app.is_null() { return; }
+    let app = unsafe { &mut *app };
+    app.exit_requested_handler = Some((callback, ctx as usize));
+}
+
+/// Register a callback that fires when a window has been materialized and is live.
+/// Called for both initial windows (at startup) and dynamically created windows.
+/// Signature: fn(ctx: *mut c_void, window_id: usize, window_ptr: *mut WryWindow).
+#[no_mangle]
+pub extern "C" fn wry_app_on_window_created(
+    app: *mut WryApp,
+    callback: WindowCreatedCallback,
+    ctx: *mut c_void,
+) {
+    if app.is_null() { return; }
+    let app = unsafe { &mut *app };
+    app.window_created_handler = Some((callback, ctx as usize));
+}
+
+/// Register a callback that fires when dynamic window creation fails (async path only).
+/// Signature: fn(ctx: *mut c_void, window_id: usize, error_message: *const c_char). error_message is UTF-8.
+#[no_mangle]
+pub extern "C" fn wry_app_on_window_creation_error(
+    app: *mut WryApp,
+    callback: WindowCreationErrorCallback,
+    ctx: *mut c_void,
+) {
+    if app.is_null() { return; }
+    let app = unsafe { &mut *app };
+    app.window_creation_error_handler = Some((callback, ctx as usize));
+}
+
+/// Register a callback that fires when a window has been destroyed (platform Destroyed event, or
+/// wry_window_close once the callback that called it returns).
+/// Signature: fn(ctx: *mut c_void, window_id: usize).
+#[no_mangle]
+pub extern "C" fn wry_app_on_window_destroyed(
+    app: *mut WryApp,
+    callback: WindowDestroyedCallback,
+    ctx: *mut c_void,
+) {
+    if app.is_null() { return; }
+    let app = unsafe { &mut *app };
+    app.window_destroyed_handler = Some((callback, ctx as usize));
+}
+
+/// Register a callback for tao's Suspended event: the app is being backgrounded and should
+/// release resources or pause work. Signature: fn(ctx: *mut c_void).
+/// tao raises it on mobile platforms; desktop platforms do not emit it, not even around system
+/// sleep (see `wry_app_on_power_event`). Must be set before `wry_app_run`.
+#[no_mangle]
+pub extern "C" fn wry_app_on_suspend(app: *mut WryApp, callback: LifecycleCallback, ctx: *mut c_void) {
+    if app.is_null() { return; }
+    let app = unsafe { &mut *app };
+    app.suspend_handler = Some((callback, ctx as usize));
+}
+
+/// Register a callback for tao's Resumed event, the counterpart of `wry_app_on_suspend`.
+/// Signature: fn(ctx: *mut c_void). Must be set before `wry_app_run`.
+#[no_mangle]
+pub extern "C" fn wry_app_on_resume(app: *mut WryApp, callback: LifecycleCallback, ctx: *mut c_void) {
+    if app.is_null() { return; }
+    let app = unsafe { &mut *app };
+    app.resume_handler = Some((callback, ctx as usize));
+}
+
+/// Register a callback for system power and session changes: sleep, wake, battery saver and
+/// session lock / unlock (see PowerEventCallback). Runs on the event loop thread.
+/// Must be set before `wry_app_run`, which starts the platform listener.
+#[no_mangle]
+pub extern "C" fn wry_app_on_power_event(app: *mut WryApp, callback: PowerEventCallback, ctx: *mut c_void) {
+    if app.is_null() { return; }
+    let app = unsafe { &mut *app };
+    app.power_event_handler = Some((callback, ctx as usize));
+}
+
+/// Register a callback for when the set of monitors changes (one connected or disconnected;
+/// on Windows also resolution changes). Carries no data - re-enumerate with `wry_window_get_all_monitors`.
+/// Runs on the event loop thread. Must be set before `wry_app_run`.
+#[no_mangle]
+pub extern "C" fn wry_app_on_monitors_changed(app: *mut WryApp, callback: LifecycleCallback, ctx: *mut c_void) {
+    if app.is_null() { return; }
+    let app = unsafe { &mut *app };
+    app.monitors_changed_handler = Some((callback, ctx as usize));
+}
+
+/// Set the app identity the shell groups windows under. Call before creating windows, ideally
+/// right after `wry_app_new`; windows that already exist keep their old grouping.
+/// Windows: SetCurrentProcessExplicitAppUserModelID - taskbar grouping and pinning, jump lists
+/// and toast notification identity (use the same ID as the Start menu shortcut, e.g.
+/// "Company.Product"). Linux: sets the GLib program name, which becomes the WM_CLASS / Wayland
+/// app ID of windows created afterwards (match your .desktop file name). macOS: no-op, the bundle
+/// identifier is used. Returns false on failure, an empty ID, or macOS.
+#[no_mangle]
+pub extern "C" fn wry_app_set_app_id(app: *mut WryApp, app_id: *const c_char) -> bool {
+    if app.is_null() { return false; }
+    let app_id = unsafe { c_str_to_string(app_id) };
+    if app_id.is_empty() {
+        return false;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use windows::core::HSTRING;
+        use windows::Win32::UI::Shell::SetCurrentProcessExplicitAppUserModelID;
+        let result = unsafe { SetCurrentProcessExplicitAppUserModelID(&HSTRING::from(app_id.as_str())) };
+        log_err!(result.as_ref(), "set app id");
+        result.is_ok()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        webkit2gtk::glib::set_prgname(Some(app_id.as_str()));
+        true
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    false
+}
+
+/// Store an opaque pointer on the app, e.g. a GCHandle to host-side shared state, so callbacks
+/// that only receive the app handle can find it. Never dereferenced or freed by the library;
+/// the host owns it and must free it after `wry_app_destroy`. Safe to call from any thread.
+#[no_mangle]
+pub extern "C" fn wry_app_set_user_data(app: *mut WryApp, data: *mut c_void) {
+    if app.is_null() { return; }
+    let app = unsafe { &*app };
+    app.user_data.store(data as usize, Ordering::SeqCst);
+}
+
+/// Return the pointer stored with `wry_app_set_user_data`, or null if none was set.
+/// Safe to call from any thread.
+#[no_mangle]
+pub extern "C" fn wry_app_get_user_data(app: *mut WryApp) -> *mut c_void {
+    if app.is_null() { return std::ptr::null_mut(); }
+    let app = unsafe { &*app };
+    app.user_data.load(Ordering::SeqCst) as *mut c_void
+}
+
+/// Set how the app appears on macOS: 0 = Regular (dock icon and menu bar; the default),
+/// 1 = Accessory (no dock icon or menu bar, windows can still be shown - menu bar extras and
+/// tray-only apps), 2 = Prohibited (no dock icon and no windows). Call right after `wry_app_new`,
+/// before windows are created; a call during `wry_app_run` applies immediately via
+/// NSApplication's setActivationPolicy. Must be called on the main thread.
+/// Platform: macOS only; no-op returning false elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_app_set_activation_policy(app: *mut WryApp, policy: c_int) -> bool {
+    if app.is_null() { return false; }
+    let app = unsafe { &mut *app };
+    #[cfg(target_os = "macos")]
+    {
+        use tao::platform::macos::{ActivationPolicy, EventLoopExtMacOS};
+        let policy = match policy {
+            1 => ActivationPolicy::Accessory,
+            2 => ActivationPolicy::Prohibited,
+            _ => ActivationPolicy::Regular,
+        };
+        // Before run, tao applies its own stored policy at launch, so it has to go through tao.
+        if let Some(ref mut event_loop) = app.event_loop {
+            event_loop.set_activation_policy(policy);
+            return true;
+        }
+        use objc2::MainThreadMarker;
+        use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy};
+        let Some(mtm) = MainThreadMarker::new() else {
+            eprintln!("[wry-native] set activation policy: not on the main thread");
+            return false;
+        };
+        let policy = match policy {
+            ActivationPolicy::Accessory => NSApplicationActivationPolicy::Accessory,
+            ActivationPolicy::Prohibited => NSApplicationActivationPolicy::Prohibited,
+            _ => NSApplicationActivationPolicy::Regular,
+        };
+        NSApplication::sharedApplication(mtm).setActivationPolicy(policy)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, policy);
+        false
+    }
+}
+
+/// Choose whether closing the last window ends the app (default true). When false, the
+/// event loop keeps running after the last window closes as long as a tray icon is live
+/// ("minimize to tray"); with no tray left it exits as usual. Exit via `wry_app_exit` or by
+/// removing the last tray. Safe to call from any thread, before or during `wry_app_run`.
+#[no_mangle]
+pub extern "C" fn wry_app_set_exit_on_last_window_closed(app: *mut WryApp, enabled: bool) {
+    if app.is_null() { return; }
+    let app = unsafe { &*app };
+    app.exit_on_last_window_closed.store(enabled, Ordering::SeqCst);
+}
+
+/// Choose how the event loop idles between events.
+/// mode: 0 = Wait (sleep until an event or timer is due; the default), 1 = Poll (never sleep),
+/// 2 = WaitUntil (wake at least every `millis` milliseconds, minimum 1).
+/// Poll keeps one CPU core busy for as long as it is set; prefer WaitUntil or
+/// `wry_app_set_interval` when a fixed cadence is enough. Page animations (`requestAnimationFrame`,
+/// CSS) run in the webview and do not need either. Safe to call from any thread, before or during
+/// `wry_app_run`; takes effect from the next loop iteration.
+#[no_mangle]
+pub extern "C" fn wry_app_set_control_flow(app: *mut WryApp, mode: c_int, millis: c_int) {
+    if app.is_null() { return; }
+    let app = unsafe { &*app };
+    let flow = match mode {
+        1 => CONTROL_FLOW_POLL,
+        2 => i64::from(millis.max(1)),
+        _ => CONTROL_FLOW_WAIT,
+    };
+    app.base_control_flow.store(flow, Ordering::SeqCst);
+    // Wake the loop so a switch away from Wait applies now rather than at the next event.
+    log_err!(app.proxy.send_event(UserEvent::Wake), "set control flow");
+}
+
+/// Start a repeating timer that calls `callback(timer_id, ctx)` on the event loop thread
+/// every `millis` milliseconds (minimum 1). The loop sleeps with `ControlFlow::WaitUntil`
+/// between ticks, so no extra thread is involved. Safe to call from any thread, before or
+/// during `wry_app_run`. Returns the timer ID (never 0), or 0 on failure.
+#[no_mangle]
+pub extern "C" fn wry_app_set_interval(
+    app: *mut WryApp,
+    millis: c_int,
+    callback: TimerCallback,
+    ctx: *mut c_void,
+) -> usize {
+    if app.is_null() { return 0; }
+    let app = unsafe { &*app };
+    let timer_id = app.next_timer_id.fetch_add(1, Ordering::SeqCst);
+    let interval = Duration::from_millis(millis.max(1) as u64);
+    match app.proxy.send_event(UserEvent::SetInterval { timer_id, interval, callback, ctx: ctx as usize }) {
+        Ok(()) => timer_id,
+        Err(e) => {
+            eprintln!("[wry-native] set interval failed: {}", e);
+            0
+        }
+    }
+}
+
+/// Stop a timer started with `wry_app_set_interval`. Unknown IDs are ignored.
+/// Safe to call from any thread, including from the timer's own callback.
+#[no_mangle]
+pub extern "C" fn wry_app_clear_interval(app: *mut WryApp, timer_id: usize) {
+    if app.is_null() { return; }
+    let app = unsafe { &*app };
+    log_err!(app.proxy.send_event(UserEvent::ClearInterval { timer_id }), "clear interval");
+}
+
+/// Keep the display and / or the system from sleeping because of inactivity, e.g. during media
+/// playback or a long export. Pass false for both to release the assertion (also released when
+/// the app is destroyed). Each call replaces the previous state. Returns false if the platform
+/// refused the request; nothing is held in that case. Safe to call from any thread.
+/// Platform: Windows (PowerCreateRequest / PowerSetRequest), macOS (IOKit power assertions),
+/// Linux (org.freedesktop.ScreenSaver.Inhibit for the display, logind `idle:sleep` inhibitor
+/// for the system).
+#[no_mangle]
+pub extern "C" fn wry_app_set_keep_awake(app: *mut WryApp, keep_display_awake: bool, keep_system_awake: bool) -> bool {
+    if app.is_null() { return false; }
+    let app = unsafe { &*app };
+    let mut held = app.keep_awake.lock().unwrap_or_else(|e| e.into_inner());
+    // Release first so the new request never stacks on the old one.
+    *held = None;
+    if !keep_display_awake && !keep_system_awake {
+        return true;
+    }
+    *held = power::KeepAwake::acquire(keep_display_awake, keep_system_awake);
+    held.is_some()
+}
+
+/// Set the badge count on every live window (see `wry_window_set_badge_count`); the macOS dock
+/// badge is app-wide anyway. Windows created later start without a badge. 0 or negative clears.
+/// Safe to call from any thread.
+#[no_mangle]
+pub extern "C" fn wry_app_set_badge_count(app: *mut WryApp, count: i64) {
+    if app.is_null() { return; }
+    let app = unsafe { &*app };
+    log_err!(app.proxy.send_event(UserEvent::SetBadgeCount { count }), "set badge count");
+}
+
+/// Enumerate the live tray icons. The callback runs on the event loop thread with an array
+/// of tray IDs (sorted ascending) that is only valid during the call. Safe to call from any thread;
+/// trays that have not been materialized yet (before `wry_app_run` starts) are not reported.
+#[no_mangle]
+pub extern "C" fn wry_app_get_tray_ids(
+    app: *mut WryApp,
+    callback: TrayIdsCallback,
+    ctx: *mut c_void,
+) {
+    if app.is_null() { return; }
+    let app = unsafe { &*app };
+    log_err!(app.proxy.send_event(UserEvent::GetTrayIds {
+        callback,
+        ctx: ctx as usize,
+    }), "get tray ids");
+}
+
+/// Request the application to exit with the given exit code.
+/// This fires the exit-requested callback (if registered) with has_code=true.
+/// If the callback allows exit (or none is registered), the event loop exits
+/// and any remaining tray icons are removed. Safe to call from any thread.
+#[no_mangle]
+pub extern "C" fn wry_app_exit(app: *mut WryApp, code: c_int) {
+    if app.is_null() { return; }
+    let app = unsafe { &*app };
+    log_err!(app.proxy.send_event(UserEvent::RequestExit { code }), "request exit");
+}
+
+/// Destroy the application handle and free resources.
+#[no_mangle]
+pub extern "C" fn wry_app_destroy(app: *mut WryApp) {
+    if !app.is_null() {
+        unsafe {
+            drop(Box::from_raw(app));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Window creation
+// ---------------------------------------------------------------------------
+
+/// Create a window with optional config. Pass 0 for owner/parent for top-level.
+/// config: null = default params; or pointer to WryWindowConfig for title, url, size, etc.
+/// Before run: window is stored in app.windows. After run: posts CreateWindowWithConfig (no queue).
+/// Returns window ID (never 0 on success).
+#[no_mangle]
+pub extern "C" fn wry_window_create(
+    app: *mut WryApp,
+    owner_window_id: usize,
+    parent_window_id: usize,
+    config: *const c_void,
+) -> usize {
+    if app.is_null() {
+        return 0;
+    }
+    let app = unsafe { &mut *app };
+    let id = app.next_window_id;
+    app.next_window_id += 1;
+
+    let mut payload = if config.is_null() {
+        WindowCreatePayload::default()
+    } else {
+        payload_from_config(config as *const WryWindowConfig)
+    };
+    if payload.zoom_persistence {
+        payload.zoom_store = Some(app.zoom_store.clone());
+    }
+    if let Ok(mut states) = app.load_states.lock() {
+        states.retain(|_, state| state.strong_count() > 0);
+        states.insert(id, Arc::downgrade(&payload.load_state));
+    }
+    if owner_window_id != 0 {
+        payload.owner_window_id = Some(owner_window_id);
+        payload.parent_window_id = None;
+    } else if parent_window_id != 0 {
+        payload.parent_window_id = Some(parent_window_id);
+        payload.owner_window_id = None;
+    }
+
+    if !app.run_started.load(Ordering::SeqCst) {
+        let win = WryWindow::new(id);
+        app.windows.insert(id, win);
+        app.payloads.insert(id, payload);
+        return id;
+    }
+
+    let _ = app.proxy.send_event(UserEvent::CreateWindowWithConfig {
+        id,
+        payload: Box::new(payload),
+    });
+    id
+}
+
+/// Make `json` (any JSON value, typically an object with theme, locale, API base URL...)
+/// available to every page as `window.__WRY_DATA` before any other init script or page script
+/// runs, so the frontend needs no IPC round-trip for its startup config. Replaces earlier data.
+/// Pre-run only: `window_id` must come from `wry_window_create` called before `wry_app_run`.
+///
+/// Returns false if `json` is not valid JSON or the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_set_initial_data(app: *mut WryApp, window_id: usize, json: *const c_char) -> bool {
+    if app.is_null() || json.is_null() {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    let Some(script) = initial_data_script(&unsafe { c_str_to_string(json) }) else {
+        eprintln!("[wry-native] set initial data: not valid JSON");
+        return false;
+    };
+    payload.initial_data_script = Some(script);
+    true
+}
+
+/// Add a script that runs in the main frame of every page once the DOM is ready (DOMContentLoaded,
+/// after the init scripts and the page's own parser-blocking scripts). Pre-run only: `window_id`
+/// must come from `wry_window_create` called before `wry_app_run`. The script runs inside a
+/// function, so top-level declarations are local; assign to `window` for globals.
+///
+/// Returns false if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_add_dom_ready_script(app: *mut WryApp, window_id: usize, js: *const c_char) -> bool {
+    if app.is_null() || js.is_null() {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    let js = unsafe { c_str_to_string(js) };
+    if !js.is_empty() {
+        payload.dom_ready_scripts.push(js);
+    }
+    true
+}
+
+/// Add a user stylesheet: CSS applied to every page of the main frame from document start, before
+/// the page's own styles load (later page rules of equal specificity still win; use `!important`
+/// to override them). Pre-run only: `window_id` must come from `wry_window_create` called before
+/// `wry_app_run`.
+///
+/// Returns false if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_add_user_stylesheet(app: *mut WryApp, window_id: usize, css: *const c_char) -> bool {
+    if app.is_null() || css.is_null() {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    let css = unsafe { c_str_to_string(css) };
+    if !css.is_empty() {
+        payload.user_stylesheets.push(css);
+    }
+    true
+}
+
+/// wry_window_add_custom_protocol: the handler was registered.
+pub const PROTOCOL_OK: c_int = 0;
+/// The scheme is empty, not a valid URI scheme, or one the engine handles itself (http, file, ...).
+pub const PROTOCOL_INVALID_SCHEME: c_int = 1;
+/// The window already has a handler for the scheme (schemes are case-insensitive).
+pub const PROTOCOL_DUPLICATE_SCHEME: c_int = 2;
+/// The window is unknown or already created.
+pub const PROTOCOL_WINDOW_UNAVAILABLE: c_int = 3;
+
+/// Schemes served by the engine itself; a custom handler for them is refused.
+const RESERVED_SCHEMES: &[&str] = &["about", "blob", "data", "file", "http", "https", "javascript", "ws", "wss"];
+
+/// PROTOCOL_* status for registering `scheme` on a window that already has `existing`. A valid
+/// scheme is a letter followed by letters, digits, '+', '-' or '.' (RFC 3986).
+fn check_protocol_scheme<'a>(scheme: &str, existing: impl IntoIterator<Item = &'a str>) -> c_int {
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !valid || RESERVED_SCHEMES.iter().any(|r| r.eq_ignore_ascii_case(scheme)) {
+        return PROTOCOL_INVALID_SCHEME;
+    }
+    if existing.into_iter().any(|e| e.eq_ignore_ascii_case(scheme)) {
+        return PROTOCOL_DUPLICATE_SCHEME;
+    }
+    PROTOCOL_OK
+}
+
+/// Linux: register the custom schemes flagged secure / standard with the webview's WebKitGTK
+/// context (as secure, as CORS-enabled). The registration is per scheme but belongs to the context,
+/// so windows sharing it see it too; it applies from the next load.
+#[cfg(target_os = "linux")]
+fn register_scheme_security(webview: &WebView, protocols: &[PendingProtocol]) {
+    use webkit2gtk::{SecurityManagerExt, WebContextExt, WebViewExt};
+    use wry::WebViewExtUnix;
+    let Some(manager) = webview.webview().context().and_then(|c| c.security_manager()) else {
+        return;
+    };
+    for protocol in protocols {
+        if protocol.secure {
+            manager.register_uri_scheme_as_secure(&protocol.scheme);
+        }
+        if protocol.standard {
+            manager.register_uri_scheme_as_cors_enabled(&protocol.scheme);
+        }
+    }
+}
+
+/// A PROTOCOL_* status as a Result, for logging refused registrations with log_err!.
+fn protocol_status_result(scheme: &str, status: c_int) -> Result<(), String> {
+    match status {
+        PROTOCOL_OK => Ok(()),
+        PROTOCOL_INVALID_SCHEME => Err(format!("'{}' is not a valid custom scheme; ignored", scheme)),
+        PROTOCOL_DUPLICATE_SCHEME => Err(format!("'{}' is already registered; keeping the first handler", scheme)),
+        _ => Err(format!("'{}' refused (status {})", scheme, status)),
+    }
+}
+
+/// Register a custom protocol handler for `scheme` (same as a `WryWindowConfig.protocols` entry;
+/// see ProtocolHandlerCallback and wry_protocol_respond). `secure` and `standard` are the
+/// WryProtocolEntry flags of the same names, applied to this scheme only. Pre-run only: `window_id` must come from
+/// `wry_window_create` called before `wry_app_run`.
+///
+/// One handler per scheme and window: a second registration is refused with
+/// PROTOCOL_DUPLICATE_SCHEME rather than replacing the first (remove it first with
+/// wry_window_remove_custom_protocol). Duplicates in the config array are
+/// dropped the same way (the first entry wins, with a log line). There is no limit on the number
+/// of schemes or init scripts beyond memory.
+///
+/// Returns a PROTOCOL_* status.
+#[no_mangle]
+pub extern "C" fn wry_window_add_custom_protocol(
+    app: *mut WryApp,
+    window_id: usize,
+    scheme: *const c_char,
+    callback: ProtocolHandlerCallback,
+    ctx: *mut c_void,
+    secure: c_int,
+    standard: c_int,
+) -> c_int {
+    if app.is_null() {
+        return PROTOCOL_WINDOW_UNAVAILABLE;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return PROTOCOL_WINDOW_UNAVAILABLE;
+    };
+    let scheme = if scheme.is_null() { String::new() } else { unsafe { c_str_to_string(scheme) } };
+    let status = check_protocol_scheme(&scheme, payload.protocols.iter().map(|p| p.scheme.as_str()));
+    if status != PROTOCOL_OK {
+        log_err!(protocol_status_result(&scheme, status), "add custom protocol");
+        return status;
+    }
+    payload.protocols.push(PendingProtocol {
+        scheme,
+        callback,
+        ctx: ctx as usize,
+        secure: secure != 0,
+        standard: standard != 0,
+    });
+    PROTOCOL_OK
+}
+
+/// Remove the handler for `scheme` (case-insensitive) registered through the config or
+/// wry_window_add_custom_protocol, e.g. to register a different one. Pre-run only: `window_id`
+/// must come from `wry_window_create` called before `wry_app_run`.
+///
+/// Returns false if no handler was registered for the scheme or the window is unknown or already
+/// created.
+#[no_mangle]
+pub extern "C" fn wry_window_remove_custom_protocol(app: *mut WryApp, window_id: usize, scheme: *const c_char) -> bool {
+    if app.is_null() || scheme.is_null() {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    let scheme = unsafe { c_str_to_string(scheme) };
+    let before = payload.protocols.len();
+    payload.protocols.retain(|p| !p.scheme.eq_ignore_ascii_case(&scheme));
+    payload.protocols.len() != before
+}
+
+/// Limit the page's animation frame rate while the window is unfocused (in the background):
+/// requestAnimationFrame callbacks run at most `fps` times a second, or not at all for 0, until the
+/// window is focused again. Negative `fps` removes the limit. Pre-run only: `window_id` must come
+/// from `wry_window_create` called before `wry_app_run`.
+///
+/// Implemented in the page on every platform (no engine offers a frame-rate cap), so it covers
+/// requestAnimationFrame-driven rendering (canvas, JS animation) but not CSS animations or video.
+/// It complements the `background_throttling` config, which is the engine's own policy for hidden,
+/// minimized or occluded windows (timers and rendering suspended or slowed); this limit applies to
+/// visible but unfocused windows, and both can be set.
+///
+/// Returns false if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_set_background_fps(app: *mut WryApp, window_id: usize, fps: c_int) -> bool {
+    if app.is_null() {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    payload.background_fps = (fps >= 0).then_some(fps);
+    true
+}
+
+/// Autoplay policy (wry_window_set_autoplay_policy; Chromium's `--autoplay-policy` values):
+/// media may autoplay, with or without sound.
+pub const AUTOPLAY_NO_USER_GESTURE_REQUIRED: c_int = 0;
+/// Media needs a user gesture (click, key press) to start.
+pub const AUTOPLAY_USER_GESTURE_REQUIRED: c_int = 1;
+/// Media needs the document to have had user activation (Chromium's default for the web).
+pub const AUTOPLAY_DOCUMENT_USER_ACTIVATION_REQUIRED: c_int = 2;
+
+/// Chromium `--autoplay-policy` value for an AUTOPLAY_* policy; None for an unknown policy.
+fn autoplay_policy_flag(policy: c_int) -> Option<&'static str> {
+    match policy {
+        AUTOPLAY_NO_USER_GESTURE_REQUIRED => Some("no-user-gesture-required"),
+        AUTOPLAY_USER_GESTURE_REQUIRED => Some("user-gesture-required"),
+        AUTOPLAY_DOCUMENT_USER_ACTIVATION_REQUIRED => Some("document-user-activation-required"),
+        _ => None,
+    }
+}
+
+/// WebView2 browser arguments for the autoplay policy, remote-debugging port, language and HTTP
+/// cache; None when all are at their defaults (wry's defaults apply). Setting browser arguments
+/// replaces wry's defaults, so those are repeated, including the autoplay flag wry would add for
+/// `autoplay` when no policy is set.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn additional_browser_args(
+    autoplay_policy: Option<c_int>,
+    autoplay: bool,
+    remote_debugging_port: Option<u16>,
+    language: Option<&str>,
+    cache_enabled: bool,
+) -> Option<String> {
+    let autoplay = match autoplay_policy {
+        Some(policy) => autoplay_policy_flag(policy),
+        None => autoplay.then_some("no-user-gesture-required"),
+    };
+    if autoplay.is_none() && remote_debugging_port.is_none() && language.is_none() && cache_enabled {
+        return None;
+    }
+    let mut args = String::from("--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection");
+    if let Some(flag) = autoplay {
+        args.push_str(&format!(" --autoplay-policy={}", flag));
+    }
+    if let Some(port) = remote_debugging_port {
+        args.push_str(&format!(" --remote-debugging-port={}", port));
+    }
+    if let Some(language) = language {
+        args.push_str(&format!(" --lang={}", language));
+    }
+    if !cache_enabled {
+        // Chromium reads 0 as "default size"; one byte leaves no room for any entry.
+        args.push_str(" --disk-cache-size=1");
+    }
+    Some(args)
+}
+
+/// Set the window's media autoplay policy (AUTOPLAY_*), replacing the on/off `autoplay` config.
+/// Pre-run only: `window_id` must come from `wry_window_create` called before `wry_app_run`.
+///
+/// - Windows: passed to Chromium as `--autoplay-policy`. Browser arguments are per WebView2
+///   environment: windows sharing a data directory must use the same policy, or later ones fail
+///   to be created.
+/// - macOS: NoUserGestureRequired lets all media autoplay; the other two require a user gesture
+///   for all media (wry builds the WKWebViewConfiguration, so audio-only rules are not reachable).
+/// - Linux: NoUserGestureRequired lets media autoplay; the other two require a user gesture.
+///
+/// Returns false for an unknown policy or if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_set_autoplay_policy(app: *mut WryApp, window_id: usize, policy: c_int) -> bool {
+    if app.is_null() || autoplay_policy_flag(policy).is_none() {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    payload.autoplay_policy = Some(policy);
+    true
+}
+
+/// `locale` as a BCP 47 tag ("de-DE"; POSIX "de_DE" is converted); None unless it is subtags of
+/// 1-8 ASCII letters / digits joined by '-'.
+fn normalize_locale(locale: &str) -> Option<String> {
+    let tag = locale.trim().replace('_', "-");
+    let valid = !tag.is_empty()
+        && tag.split('-').all(|p| (1..=8).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()));
+    valid.then_some(tag)
+}
+
+/// Set the webview's language (a BCP 47 tag such as "de-DE"): the `Accept-Language` header and
+/// `navigator.language`. Pre-run only: `window_id` must come from `wry_window_create` called
+/// before `wry_app_run`.
+///
+/// - Windows: passed to WebView2 as `--lang`. The language belongs to the WebView2 environment,
+///   so it must be set before the webview is created, and windows sharing a data directory must
+///   use the same one, or later ones fail to be created.
+/// - Linux: the preferred languages of the window's WebKitGTK context, set before the first load.
+///   Without a data_directory the window uses the default context, shared by every window
+///   without one, so the language applies to those windows too (last set wins).
+/// - macOS: not available (WKWebView follows the app's languages); returns false.
+///
+/// Returns false for a malformed tag or if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_set_language(app: *mut WryApp, window_id: usize, locale: *const c_char) -> bool {
+    if app.is_null() || locale.is_null() || cfg!(target_os = "macos") {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    let Some(tag) = normalize_locale(&unsafe { c_str_to_string(locale) }) else {
+        return false;
+    };
+    payload.language = Some(tag);
+    true
+}
+
+/// Turn the HTTP cache off (`enabled = false`) so every load fetches assets afresh, e.g. while
+/// developing a frontend against a local dev server. Pre-run only: `window_id` must come from
+/// `wry_window_create` called before `wry_app_run`.
+///
+/// - Windows: passed to WebView2 as `--disk-cache-size=1`, which leaves the disk cache no room.
+///   Browser arguments are per WebView2 environment: windows sharing a data directory must use
+///   the same setting, or later ones fail to be created.
+/// - macOS: the webview gets a non-persistent data store, as with `incognito`, so cookies and web
+///   storage are not kept between runs either.
+/// - Linux: WebKitGTK's `DocumentViewer` cache model, set on the window's web context before the
+///   first load; windows sharing the context share the setting.
+///
+/// Returns false if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_set_cache_enabled(app: *mut WryApp, window_id: usize, enabled: bool) -> bool {
+    if app.is_null() {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    payload.cache_enabled = enabled;
+    true
+}
+
+/// Open a Chrome DevTools Protocol endpoint on `port` (1-65535) for automation tools such as
+/// Playwright or Puppeteer (`connectOverCDP("http://127.0.0.1:<port>")`). Pre-run only:
+/// `window_id` must come from `wry_window_create` called before `wry_app_run`.
+///
+/// Windows only (passed to WebView2 as `--remote-debugging-port`); returns false elsewhere.
+/// Browser arguments are per WebView2 environment: windows sharing a data directory must use the
+/// same port, or later ones fail to be created.
+///
+/// Security: anything that can reach the port gets full control of the webview (script, cookies,
+/// navigation). Chromium binds it to localhost only, but any local process can connect; enable it
+/// for testing and diagnostics, not in production builds.
+///
+/// Returns false for an invalid port or if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_set_remote_debugging_port(app: *mut WryApp, window_id: usize, port: c_int) -> bool {
+    if app.is_null() || !cfg!(target_os = "windows") {
+        return false;
+    }
+    let Ok(port) = u16::try_from(port) else {
+        return false;
+    };
+    if port == 0 {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    payload.remote_debugging_port = Some(port);
+    true
+}
+
+/// Hide the title bar's close / minimize / zoom buttons, for palette-style utility windows
+/// (`with_titlebar_buttons_hidden`). The title bar itself stays; the window can still be closed
+/// from code or with Cmd-W. Pre-run only: `window_id` must come from `wry_window_create` called
+/// before `wry_app_run`.
+///
+/// macOS only; returns false elsewhere (use `decorations` or `closable` / `minimizable` /
+/// `maximizable` in the config instead). Returns false if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_set_titlebar_buttons_hidden(app: *mut WryApp, window_id: usize, hidden: bool) -> bool {
+    if app.is_null() || !cfg!(target_os = "macos") {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    payload.titlebar_buttons_hidden = hidden;
+    true
+}
+
+/// Make the window a tool palette (e.g. a floating inspector) that does not take activation away
+/// from the main window. Pre-run only: `window_id` must come from `wry_window_create` called
+/// before `wry_app_run`. Combine with `topmost` or an owner window to keep it above the others.
+///
+/// - Windows: `WS_EX_TOOLWINDOW` + `WS_EX_NOACTIVATE`. No taskbar button, not listed in Alt-Tab,
+///   and clicking it does not activate it. Since it never becomes the foreground window, text
+///   fields in the page do not receive keyboard input.
+/// - macOS: not available; returns false. tao creates its own NSWindow subclass, and only an
+///   NSPanel can be non-activating.
+/// - Linux: not available; returns false.
+///
+/// The window is not focused when first shown.
+/// Returns false if the window is unknown or already created.
+#[no_mangle]
+pub extern "C" fn wry_window_set_panel_style(app: *mut WryApp, window_id: usize, enabled: bool) -> bool {
+    if app.is_null() || !cfg!(target_os = "windows") {
+        return false;
+    }
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    payload.panel_style = enabled;
+    true
+}
+
+// ---------------------------------------------------------------------------
+// JavaScript evaluation (post-run: use *mut WryWindow)
+// ---------------------------------------------------------------------------
+
+/// Evaluate JavaScript in the webview. Must be called post-run (from a callback
+/// or dispatch) with the `*mut WryWindow` pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_eval_js(win: *mut WryWindow, js: *const c_char) {
+    if win.is_null() || js.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let js = unsafe { c_str_to_string(js) };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.evaluate_script(&js), "evaluate_script");
+    }
+}
+
+/// Queue JavaScript to run in the webview together with the other scripts queued in the same
+/// event loop iteration: they are joined into a single evaluate_script call once the iteration's
+/// events are handled. For high-frequency host-to-page updates (streaming data), where one
+/// evaluate_script per update backs up the webview's script queue. Must be called post-run
+/// (from a callback or dispatch) with the `*mut WryWindow` pointer.
+///
+/// Ordering: batched scripts run in the order they were queued, after any scripts passed to
+/// wry_window_eval_js during the same iteration; use one of the two for updates whose relative
+/// order matters. Each script runs in its own block, so an exception stops only that script
+/// (it is logged to the page console), but top-level `let` / `const` / `class` are local to it
+/// (assign to `window` for globals). A syntax error in one script prevents the whole batch.
+#[no_mangle]
+pub extern "C" fn wry_window_eval_js_batched(win: *mut WryWindow, js: *const c_char) {
+    if win.is_null() || js.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let js = unsafe { c_str_to_string(js) };
+    if win.webview.is_some() && !js.is_empty() {
+        win.eval_batch.push(js);
+    }
+}
+
+/// One script running `scripts` in order, each in its own try block. The newlines keep a trailing
+/// `//` comment in a script from swallowing what follows.
+fn eval_batch_script(scripts: &[String]) -> String {
+    scripts
+        .iter()
+        .map(|js| format!("try {{\n{}\n}} catch (e) {{ console.error(e); }}", js))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Evaluate JavaScript in the webview and receive the result via a callback.
+/// The callback receives the JSON-encoded result string (or an error message).
+/// Must be called post-run (from a callback or dispatch).
+#[no_mangle]
+pub extern "C" fn wry_window_eval_js_callback(
+    win: *mut WryWindow,
+    js: *const c_char,
+    callback: EvalResultCallback,
+    ctx: *mut c_void,
+) {
+    if win.is_null() || js.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let js = unsafe { c_str_to_string(js) };
+    if let Some(ref wv) = win.webview {
+        let ctx_usize = ctx as usize;
+        log_err!(wv.evaluate_script_with_callback(&js, move |result| {
+            match CString::new(result.as_str()) {
+                Ok(cs) => {
+                    callback(cs.as_ptr(), ctx_usize as *mut c_void);
+                }
+                Err(_) => {
+                    // If the result contains null bytes, pass empty
+                    let empty = CString::new("").unwrap();
+                    callback(empty.as_ptr(), ctx_usize as *mut c_void);
+                }
+            };
+        }), "evaluate_script_with_callback");
+    }
+}
+
+/// List the page's iframes as a JSON array of `{"id", "name", "url", "sameOrigin"}` passed to
+/// `callback`, depth-first in document order. Returns false (callback not called) if the webview is
+/// not created. Call from a callback with the WryWindow pointer.
+///
+/// Works from the page's DOM on every platform (WKWebView cannot enumerate frames through public
+/// API), so it is bounded by the same-origin policy: frames inside a cross-origin frame are not
+/// listed, and a cross-origin frame reports its `src` attribute as `url` and `sameOrigin: false`.
+///
+/// IDs are not stable across pages: an iframe element keeps its ID while it stays in the page
+/// (other frames being added or removed do not change it), but a new top-level page numbers its
+/// frames afresh. List again after a navigation before using an ID.
+#[no_mangle]
+pub extern "C" fn wry_window_get_frames(win: *mut WryWindow, callback: FramesCallback, ctx: *mut c_void) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    let Some(ref wv) = win.webview else { return false; };
+    let ctx = ctx as usize;
+    let reachable_frames = win.reachable_frames.clone();
+    let result = wv.evaluate_script_with_callback(FRAMES_SCRIPT, move |json| {
+        if let Ok(mut frames) = reachable_frames.lock() {
+            *frames = same_origin_frame_ids(&json);
+        }
+        let json = CString::new(json).unwrap_or_default();
+        callback(json.as_ptr(), ctx as *mut c_void);
+    });
+    log_err!(result.as_ref(), "get frames");
+    result.is_ok()
+}
+
+/// Evaluate JavaScript in the iframe `frame_id`, an ID listed with `sameOrigin: true` by the last
+/// wry_window_get_frames call for the current page. Returns false without running anything for a
+/// cross-origin frame, an ID not in that listing (list again after a navigation) or a malformed
+/// one, or if the webview is not created. A frame removed since the listing, or navigated to
+/// another origin, is reported as an error in the page console. Call from a callback with the
+/// WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_eval_in_frame(win: *mut WryWindow, frame_id: *const c_char, js: *const c_char) -> bool {
+    if win.is_null() || frame_id.is_null() || js.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    let Some(ref wv) = win.webview else { return false; };
+    let frame_id = unsafe { c_str_to_string(frame_id) };
+    let reachable = parse_frame_id(&frame_id)
+        .is_some_and(|id| win.reachable_frames.lock().is_ok_and(|frames| frames.contains(&id)));
+    if !reachable {
+        eprintln!("[wry-native] eval in frame: {:?} is not a listed same-origin frame", frame_id);
+        return false;
+    }
+    let Some(script) = frame_eval_script(&frame_id, &unsafe { c_str_to_string(js) }) else {
+        return false;
+    };
+    wv.evaluate_script(&script).is_ok()
+}
+
+/// A page dialog waiting for its answer; handed to the script dialog handler as `responder`.
+pub struct ScriptDialogResponder {
+    #[cfg(target_os = "windows")]
+    args: webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2ScriptDialogOpeningEventArgs,
+    #[cfg(target_os = "windows")]
+    deferral: webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Deferral,
+    #[cfg(target_os = "linux")]
+    dialog: webkit2gtk::ScriptDialog,
+}
+
+/// Answer a page dialog. Must be called exactly once per script dialog handler invocation, on the
+/// UI thread. `responder` is the opaque pointer passed to the handler.
+///
+/// - `accepted`: non-zero = OK (confirm returns true, prompt returns `text`, the page is left for
+///   BeforeUnload); 0 = Cancel (confirm returns false, prompt returns null). Ignored for Alert.
+/// - `text`: prompt result as a UTF-8 C string. null = empty string.
+#[no_mangle]
+pub extern "C" fn wry_script_dialog_respond(responder: *mut c_void, accepted: c_int, text: *const c_char) {
+    if responder.is_null() {
+        return;
+    }
+    let responder = unsafe { Box::from_raw(responder as *mut ScriptDialogResponder) };
+    let accepted = accepted != 0;
+    let text = unsafe { c_str_to_string(text) };
+
+    #[cfg(target_os = "windows")]
+    unsafe {
+        if accepted {
+            log_err!(responder.args.SetResultText(&windows::core::HSTRING::from(text)), "script dialog text");
+            log_err!(responder.args.Accept(), "script dialog accept");
+        }
+        log_err!(responder.deferral.Complete(), "script dialog complete");
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::ScriptDialogType;
+        let mut dialog = responder.dialog;
+        match dialog.dialog_type() {
+            ScriptDialogType::Confirm | ScriptDialogType::BeforeUnloadConfirm => {
+                dialog.confirm_set_confirmed(accepted)
+            }
+            ScriptDialogType::Prompt if accepted => dialog.prompt_set_text(&text),
+            _ => {}
+        }
+        dialog.close();
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let _ = (responder, accepted, text);
+}
+
+/// Respond to a custom protocol request. Must be called exactly once per
+/// protocol handler invocation. `responder` is the opaque pointer passed to
+/// the protocol handler callback.
+///
+/// - `data`: pointer to response body bytes
+/// - `data_len`: length of response body
+/// - `content_type`: MIME type as a UTF-8 C string (e.g. "text/html")
+/// - `status_code`: HTTP status code (e.g. 200)
+/// - `extra_headers`: additional response headers as "Key: Value\r\n" pairs
+///   (UTF-8 C string). Pass null for no extra headers.
+#[no_mangle]
+pub extern "C" fn wry_protocol_respond(
+    responder: *mut c_void,
+    data: *const u8,
+    data_len: c_int,
+    content_type: *const c_char,
+    status_code: c_int,
+    extra_headers: *const c_char,
+) {
+    if responder.is_null() {
+        return;
+    }
+
+    let responder =
+        unsafe { Box::from_raw(responder as *mut wry::RequestAsyncResponder) };
+
+    let body: Cow<'static, [u8]> = if data.is_null() || data_len <= 0 {
+        Cow::Borrowed(&[])
+    } else {
+        let slice = unsafe { std::slice::from_raw_parts(data, data_len as usize) };
+        Cow::Owned(slice.to_vec())
+    };
+
+    let mime = unsafe { c_str_to_string(content_type) };
+    let status = if (100..600).contains(&status_code) {
+        status_code as u16
+    } else {
+        200
+    };
+
+    let mut builder = http::Response::builder()
+        .status(status)
+        .header("Content-Type", mime);
+
+    // Parse extra headers ("Key: Value\r\n" pairs)
+    if !extra_headers.is_null() {
+        let headers_str = unsafe { c_str_to_string(extra_headers) };
+        for line in headers_str.split("\r\n") {
+            if let Some((key, value)) = line.split_once(": ") {
+                let key = key.trim();
+                let value = value.trim();
+                if !key.is_empty() {
+                    builder = builder.header(key, value);
+                }
+            }
+        }
+    }
+
+    let response = builder
+        .body(body)
+        .unwrap_or_else(|_| {
+            http::Response::builder()
+                .status(500)
+                .body(Cow::Borrowed(&[] as &[u8]))
+                .unwrap()
+        });
+
+    responder.respond(response);
+}
+
+// ---------------------------------------------------------------------------
+// Window close (post-run: use *mut WryWindow)
+// ---------------------------------------------------------------------------
+
+/// Request the window to close. If a close callback is set, it will be invoked
+/// first. This must be called from the main thread or via dispatch. The window-destroyed
+/// callback fires once the current callback returns, as for windows closed by the OS.
+#[no_mangle]
+pub extern "C" fn wry_window_close(win: *mut WryWindow) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    // macOS: remove the occlusion observer and wheel monitor while the NSWindow they watch exists.
+    #[cfg(target_os = "macos")]
+    {
+        win.occlusion_observer.take();
+        win.wheel_monitor.take();
+    }
+    // Trigger a close by destroying the webview and window
+    win.webview.take();
+    win.window.take();
+}
+
+// ---------------------------------------------------------------------------
+// Window queries (post-run, via *mut WryWindow from callbacks)
+// ---------------------------------------------------------------------------
+
+/// Get the current window inner size in logical pixels.
+#[no_mangle]
+pub extern "C" fn wry_window_get_size(
+    win: *mut WryWindow,
+    width: *mut c_int,
+    height: *mut c_int,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        let size = w.inner_size();
+        let scale = w.scale_factor();
+        let logical = size.to_logical::<i32>(scale);
+        if !width.is_null() {
+            unsafe { *width = logical.width };
+        }
+        if !height.is_null() {
+            unsafe { *height = logical.height };
+        }
+    }
+}
+
+/// Get the current window outer position in logical pixels.
+#[no_mangle]
+pub extern "C" fn wry_window_get_position(
+    win: *mut WryWindow,
+    x: *mut c_int,
+    y: *mut c_int,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        let pos = w.outer_position().unwrap_or_default();
+        let scale = w.scale_factor();
+        let logical = pos.to_logical::<i32>(scale);
+        if !x.is_null() {
+            unsafe { *x = logical.x };
+        }
+        if !y.is_null() {
+            unsafe { *y = logical.y };
+        }
+    }
+}
+
+/// Get the window's inner (client area) size in logical pixels. Same as `wry_window_get_size`.
+#[no_mangle]
+pub extern "C" fn wry_window_get_inner_size(
+    win: *mut WryWindow,
+    width: *mut c_int,
+    height: *mut c_int,
+) {
+    wry_window_get_size(win, width, height);
+}
+
+/// Get the window's outer size (including title bar and borders) in logical pixels.
+#[no_mangle]
+pub extern "C" fn wry_window_get_outer_size(
+    win: *mut WryWindow,
+    width: *mut c_int,
+    height: *mut c_int,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        let logical = w.outer_size().to_logical::<i32>(w.scale_factor());
+        if !width.is_null() {
+            unsafe { *width = logical.width };
+        }
+        if !height.is_null() {
+            unsafe { *height = logical.height };
+        }
+    }
+}
+
+/// Get the position of the window's client area (top-left of the inner area) in logical pixels.
+/// The difference to `wry_window_get_outer_position` is the title bar / border size.
+#[no_mangle]
+pub extern "C" fn wry_window_get_inner_position(
+    win: *mut WryWindow,
+    x: *mut c_int,
+    y: *mut c_int,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        let pos = w.inner_position().unwrap_or_default();
+        let logical = pos.to_logical::<i32>(w.scale_factor());
+        if !x.is_null() {
+            unsafe { *x = logical.x };
+        }
+        if !y.is_null() {
+            unsafe { *y = logical.y };
+        }
+    }
+}
+
+/// Get the position of the window's outer frame in logical pixels. Same as `wry_window_get_position`.
+#[no_mangle]
+pub extern "C" fn wry_window_get_outer_position(
+    win: *mut WryWindow,
+    x: *mut c_int,
+    y: *mut c_int,
+) {
+    wry_window_get_position(win, x, y);
+}
+
+/// Get the window's inner size in physical pixels, without logical conversion.
+/// Physical = logical * `wry_window_get_screen_dpi`.
+#[no_mangle]
+pub extern "C" fn wry_window_get_inner_size_physical(
+    win: *mut WryWindow,
+    width: *mut c_int,
+    height: *mut c_int,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        let size = w.inner_size();
+        if !width.is_null() {
+            unsafe { *width = size.width as c_int };
+        }
+        if !height.is_null() {
+            unsafe { *height = size.height as c_int };
+        }
+    }
+}
+
+/// Get the window's outer position in physical pixels, without logical conversion.
+/// Physical = logical * `wry_window_get_screen_dpi`.
+#[no_mangle]
+pub extern "C" fn wry_window_get_position_physical(
+    win: *mut WryWindow,
+    x: *mut c_int,
+    y: *mut c_int,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        let pos = w.outer_position().unwrap_or_default();
+        if !x.is_null() {
+            unsafe { *x = pos.x };
+        }
+        if !y.is_null() {
+            unsafe { *y = pos.y };
+        }
+    }
+}
+
+/// Get the window title. Returns a pointer to a UTF-8 C string that the caller
+/// must free with `wry_string_free()`.
+#[no_mangle]
+pub extern "C" fn wry_window_get_title(win: *mut WryWindow) -> *mut c_char {
+    if win.is_null() {
+        return std::ptr::null_mut();
+    }
+    let win = unsafe { &*win };
+    let title = if let Some(ref w) = win.window {
+        w.title()
+    } else {
+        String::new()
+    };
+    CString::new(title)
+        .map(|cs| cs.into_raw())
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// Free a string returned by `wry_window_get_title` or `wry_window_get_url`.
+#[no_mangle]
+pub extern "C" fn wry_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        unsafe {
+            drop(CString::from_raw(s));
+        }
+    }
+}
+
+/// Get whether the window is resizable.
+#[no_mangle]
+pub extern "C" fn wry_window_get_resizable(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        w.is_resizable()
+    } else {
+        false
+    }
+}
+
+/// Get whether the window is fullscreen.
+#[no_mangle]
+pub extern "C" fn wry_window_get_fullscreen(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        w.fullscreen().is_some()
+    } else {
+        false
+    }
+}
+
+/// Get whether the window is maximized.
+#[no_mangle]
+pub extern "C" fn wry_window_get_maximized(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        w.is_maximized()
+    } else {
+        false
+    }
+}
+
+/// Get whether the window is minimized.
+#[no_mangle]
+pub extern "C" fn wry_window_get_minimized(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        w.is_minimized()
+    } else {
+        false
+    }
+}
+
+/// Get whether the window is visible.
+#[no_mangle]
+pub extern "C" fn wry_window_get_visible(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        w.is_visible()
+    } else {
+        false
+    }
+}
+
+/// Get whether the window has decorations (title bar, borders).
+#[no_mangle]
+pub extern "C" fn wry_window_get_decorated(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return true;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        w.is_decorated()
+    } else {
+        true
+    }
+}
+
+/// Get current window theme. Returns 0 = auto/unknown, 1 = dark, 2 = light.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_get_theme(win: *mut WryWindow) -> c_int {
+    if win.is_null() {
+        return 0;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        match w.theme() {
+            Theme::Dark => 1,
+            Theme::Light => 2,
+            _ => 0,
+        }
+    } else {
+        0
+    }
+}
+
+/// Get the DPI scale factor for the window's current monitor.
+/// Returns 1.0 as default if the window hasn't been created yet.
+#[no_mangle]
+pub extern "C" fn wry_window_get_screen_dpi(win: *mut WryWindow) -> f64 {
+    if win.is_null() {
+        return 1.0;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        w.scale_factor()
+    } else {
+        1.0
+    }
+}
+
+/// Get the current URL loaded in the webview. Returns a pointer to a UTF-8
+/// C string that the caller must free with `wry_string_free()`.
+/// Returns null if the webview is not yet created.
+#[no_mangle]
+pub extern "C" fn wry_window_get_url(win: *mut WryWindow) -> *mut c_char {
+    if win.is_null() {
+        return std::ptr::null_mut();
+    }
+    let win = unsafe { &*win };
+    if let Some(ref wv) = win.webview {
+        if let Ok(url) = wv.url() {
+            return CString::new(url)
+                .map(|cs| cs.into_raw())
+                .unwrap_or(std::ptr::null_mut());
+        }
+    }
+    std::ptr::null_mut()
+}
+
+/// Page-load state of window `window_id`, if it still exists.
+fn window_load_state(app: &WryApp, window_id: usize) -> Option<Arc<Mutex<LoadState>>> {
+    app.load_states.lock().ok()?.get(&window_id)?.upgrade()
+}
+
+/// Whether a navigation is in progress in window `window_id` (started, not yet finished), roughly
+/// `document.readyState` != "complete". False if the window does not exist. Callable from any
+/// thread.
+#[no_mangle]
+pub extern "C" fn wry_window_is_loading(app: *mut WryApp, window_id: usize) -> bool {
+    if app.is_null() {
+        return false;
+    }
+    let app = unsafe { &*app };
+    window_load_state(app, window_id)
+        .and_then(|state| state.lock().ok().map(|s| s.loading))
+        .unwrap_or(false)
+}
+
+/// Block until the current page load of window `window_id` finishes. Returns true when the
+/// page-load Finished event fires (immediately if no load is in progress), false on timeout, if
+/// the window does not exist or if it is destroyed meanwhile. `timeout_ms` < 0 waits without
+/// limit. A navigation that was requested but has not reported Started yet is not waited for.
+///
+/// Call from a thread other than the event loop thread: the load completes on that thread, so
+/// waiting there would always time out. Such calls return false at once.
+#[no_mangle]
+pub extern "C" fn wry_window_wait_for_load(app: *mut WryApp, window_id: usize, timeout_ms: c_int) -> bool {
+    if app.is_null() {
+        return false;
+    }
+    let app = unsafe { &*app };
+    if std::thread::current().id() == app.loop_thread {
+        eprintln!("[wry-native] wait_for_load: called on the event loop thread");
+        return false;
+    }
+    let Some(state) = window_load_state(app, window_id) else { return false; };
+    let rx = {
+        let Ok(mut s) = state.lock() else { return false; };
+        if !s.loading {
+            return true;
+        }
+        let (tx, rx) = std::sync::mpsc::channel();
+        s.waiters.push((tx, true));
+        rx
+    };
+    // Waiters hold no strong reference, so closing the window drops the sender and wakes us.
+    drop(state);
+    if timeout_ms < 0 {
+        rx.recv().is_ok()
+    } else {
+        rx.recv_timeout(Duration::from_millis(timeout_ms as u64)).is_ok()
+    }
+}
+
+/// wry_window_navigate_and_wait: the page finished loading.
+pub const NAVIGATE_OK: c_int = 0;
+/// wry_window_navigate_and_wait: the timeout elapsed first.
+pub const NAVIGATE_TIMEOUT: c_int = 1;
+/// wry_window_navigate_and_wait: the navigation failed (HTTP error, DNS, network; Windows and
+/// Linux only), the window does not exist or was closed, or the call was not allowed.
+pub const NAVIGATE_FAILED: c_int = 2;
+
+/// Load `url` in the window and block until that load finishes or `timeout_ms` elapses (< 0 =
+/// no limit). Returns NAVIGATE_OK, NAVIGATE_TIMEOUT or NAVIGATE_FAILED. macOS cannot detect failed
+/// loads and reports them as NAVIGATE_OK.
+///
+/// Call from a thread other than the event loop thread, after `wry_app_run` has started; other
+/// calls return NAVIGATE_FAILED at once, since the navigation could never start.
+#[no_mangle]
+pub extern "C" fn wry_window_navigate_and_wait(
+    app: *mut WryApp,
+    window_id: usize,
+    url: *const c_char,
+    timeout_ms: c_int,
+) -> c_int {
+    if app.is_null() || url.is_null() {
+        return NAVIGATE_FAILED;
+    }
+    let app = unsafe { &*app };
+    if std::thread::current().id() == app.loop_thread || !app.run_started.load(Ordering::SeqCst) {
+        eprintln!("[wry-native] navigate_and_wait: must be called off the event loop thread, after wry_app_run");
+        return NAVIGATE_FAILED;
+    }
+    let url = unsafe { c_str_to_string(url) };
+    let (reply, rx) = std::sync::mpsc::channel();
+    if app.proxy.send_event(UserEvent::NavigateAndWait { window_id, url, reply }).is_err() {
+        return NAVIGATE_FAILED;
+    }
+    let result = if timeout_ms < 0 {
+        rx.recv().map_err(|_| std::sync::mpsc::RecvTimeoutError::Disconnected)
+    } else {
+        rx.recv_timeout(Duration::from_millis(timeout_ms as u64))
+    };
+    match result {
+        Ok(true) => NAVIGATE_OK,
+        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => NAVIGATE_TIMEOUT,
+        Ok(false) | Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => NAVIGATE_FAILED,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Post-run window property setters (via *mut WryWindow from callbacks)
+// ---------------------------------------------------------------------------
+
+/// Set the window title. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_title(win: *mut WryWindow, title: *const c_char) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let title = unsafe { c_str_to_string(title) };
+    if let Some(ref w) = win.window {
+        w.set_title(&title);
+    }
+}
+
+/// Navigate to a URL. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_load_url(win: *mut WryWindow, url: *const c_char) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let url = unsafe { c_str_to_string(url) };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.load_url(&url), "load_url");
+    }
+}
+
+/// Load HTML content. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_load_html(win: *mut WryWindow, html: *const c_char) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let html = unsafe { c_str_to_string(html) };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.load_html(&html), "load_html");
+    }
+}
+
+/// Load HTML content whose relative URLs (images, scripts, links) resolve against `base_url`,
+/// e.g. a custom-protocol asset root. A null or empty `base_url` behaves like `wry_window_load_html`.
+/// Call from a callback with the WryWindow pointer.
+/// Platform: macOS uses `loadHTMLString:baseURL:` and Linux `webkit_web_view_load_html` with a
+/// base URI, so the page also takes the base URL's origin. Windows (NavigateToString has no base
+/// URL) inserts `<base href>` into the document instead: relative URLs resolve, but the page keeps
+/// NavigateToString's opaque origin, so `fetch` to the base origin is cross-origin and storage is
+/// not shared with it.
+#[no_mangle]
+pub extern "C" fn wry_window_load_html_with_base_url(
+    win: *mut WryWindow,
+    html: *const c_char,
+    base_url: *const c_char,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let html = unsafe { c_str_to_string(html) };
+    let base_url = unsafe { c_str_to_string(base_url) };
+    let Some(ref wv) = win.webview else { return; };
+    if base_url.is_empty() {
+        log_err!(wv.load_html(&html), "load_html_with_base_url");
+        return;
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_foundation::{NSString, NSURL};
+        use wry::WebViewExtMacOS;
+        let url = NSURL::URLWithString(&NSString::from_str(&base_url));
+        if url.is_none() {
+            eprintln!("[wry-native] load_html_with_base_url: invalid base URL {}", base_url);
+        }
+        let _ = unsafe { wv.webview().loadHTMLString_baseURL(&NSString::from_str(&html), url.as_deref()) };
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+        wv.webview().load_html(&html, Some(&base_url));
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    log_err!(wv.load_html(&inject_base_href(&html, &base_url)), "load_html_with_base_url");
+}
+
+/// Set window size. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_size(
+    win: *mut WryWindow,
+    width: c_int,
+    height: c_int,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let w = width.max(1) as u32;
+    let h = height.max(1) as u32;
+    if let Some(ref window) = win.window {
+        window.set_inner_size(LogicalSize::new(w, h));
+    }
+}
+
+/// Set window position. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_position(
+    win: *mut WryWindow,
+    x: c_int,
+    y: c_int,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref window) = win.window {
+        window.set_outer_position(LogicalPosition::new(x, y));
+    }
+}
+
+/// Set minimum window inner size. Pass width 0 or height 0 to clear the constraint.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_min_size(win: *mut WryWindow, width: c_int, height: c_int) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref window) = win.window {
+        if width <= 0 || height <= 0 {
+            window.set_min_inner_size::<LogicalSize<u32>>(None);
+        } else {
+            window.set_min_inner_size(Some(LogicalSize::new(width as u32, height as u32)));
+        }
+    }
+}
+
+/// Keep the inner size at `width_ratio:height_ratio` while the user resizes. Pass 0 for either to clear.
+/// Call from a callback with the WryWindow pointer.
+///
+/// Platform: Windows (WM_SIZING), macOS (`contentAspectRatio`). Linux: no-op, since GTK takes the
+/// aspect through the same geometry hints tao uses for the min / max size.
+#[no_mangle]
+pub extern "C" fn wry_window_set_aspect_ratio(win: *mut WryWindow, width_ratio: c_int, height_ratio: c_int) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    let Some(ref w) = win.window else { return; };
+    let ratio = (width_ratio > 0 && height_ratio > 0).then(|| width_ratio as f64 / height_ratio as f64);
+    #[cfg(target_os = "windows")]
+    set_aspect_ratio_subclass(w, ratio);
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_app_kit::NSWindow;
+        use objc2_foundation::NSSize;
+        use tao::platform::macos::WindowExtMacOS;
+        let ns_window = unsafe { &*(w.ns_window() as *const NSWindow) };
+        match ratio {
+            Some(_) => ns_window.setContentAspectRatio(NSSize::new(width_ratio as f64, height_ratio as f64)),
+            // Resize increments and aspect ratio are exclusive; setting one clears the other.
+            None => ns_window.setContentResizeIncrements(NSSize::new(1.0, 1.0)),
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let _ = (w, ratio);
+}
+
+/// Snap the window to the edges of its monitor's work area when a move ends within `threshold`
+/// logical pixels of one. `threshold <= 0` or `enabled = false` turns snapping off.
+/// Call from a callback with the WryWindow pointer.
+///
+/// The work area excludes the taskbar on Windows; elsewhere it is the full monitor bounds.
+#[no_mangle]
+pub extern "C" fn wry_window_set_edge_snapping(win: *mut WryWindow, enabled: bool, threshold: c_int) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    win.snap_threshold = (enabled && threshold > 0).then_some(threshold as f64);
+}
+
+/// Set maximum window inner size. Pass width 0 or height 0 to clear the constraint.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_max_size(win: *mut WryWindow, width: c_int, height: c_int) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref window) = win.window {
+        if width <= 0 || height <= 0 {
+            window.set_max_inner_size::<LogicalSize<u32>>(None);
+        } else {
+            window.set_max_inner_size(Some(LogicalSize::new(width as u32, height as u32)));
+        }
+    }
+}
+
+/// Set window theme. theme: 0 = auto/system, 1 = dark, 2 = light.
+/// Call from a callback with the WryWindow pointer.
+/// Platform: Windows, Linux, macOS (behavior may be app-wide on some platforms).
+#[no_mangle]
+pub extern "C" fn wry_window_set_theme(win: *mut WryWindow, theme: c_int) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref window) = win.window {
+        let t = match theme {
+            1 => Some(Theme::Dark),
+            2 => Some(Theme::Light),
+            _ => None,
+        };
+        window.set_theme(t);
+    }
+}
+
+/// Set window decorations. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_decorations(win: *mut WryWindow, decorations: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_decorations(decorations);
+    }
+}
+
+/// Set skip taskbar. Call from a callback with the WryWindow pointer. Platform: Windows, Linux.
+/// Linux: if the window is not yet visible, the hint is applied again once it is shown,
+/// because some window managers ignore a hint set before the GTK window is mapped.
+#[no_mangle]
+pub extern "C" fn wry_window_set_skip_taskbar(win: *mut WryWindow, skip: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    win.skip_taskbar = skip;
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    if let Some(ref w) = win.window {
+        #[cfg(target_os = "windows")]
+        {
+            use tao::platform::windows::WindowExtWindows;
+            let _ = w.set_skip_taskbar(skip);
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use tao::platform::unix::WindowExtUnix;
+            let _ = w.set_skip_taskbar(skip);
+            win.skip_taskbar_reapply = !w.is_visible();
+        }
+    }
+}
+
+/// Get the last requested skip-taskbar state (from config or `wry_window_set_skip_taskbar`).
+/// The platform offers no reliable query, so this reports what was asked for. Platform: Windows, Linux.
+#[no_mangle]
+pub extern "C" fn wry_window_get_skip_taskbar(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    win.skip_taskbar
+}
+
+/// Set content protection. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_content_protected(win: *mut WryWindow, protected: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_content_protection(protected);
+    }
+}
+
+/// Set undecorated shadow. Call from a callback with the WryWindow pointer. Platform: Windows.
+#[no_mangle]
+pub extern "C" fn wry_window_set_shadow(win: *mut WryWindow, shadow: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        use tao::platform::windows::WindowExtWindows;
+        w.set_undecorated_shadow(shadow);
+    }
+}
+
+/// Windows: set a 32-bit DWM window attribute. Attributes newer than the running Windows version
+/// are rejected, which callers ignore (the setters are no-ops there).
+#[cfg(target_os = "windows")]
+fn set_dwm_attribute(window: &Window, attribute: DWMWINDOWATTRIBUTE, value: u32) -> windows::core::Result<()> {
+    use tao::platform::windows::WindowExtWindows;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Dwm::DwmSetWindowAttribute;
+    unsafe {
+        DwmSetWindowAttribute(
+            HWND(window.hwnd() as _),
+            attribute,
+            &value as *const u32 as *const c_void,
+            std::mem::size_of::<u32>() as u32,
+        )
+    }
+}
+
+/// Windows 11: set a DWM color attribute (caption / border) to 0x00BBGGRR.
+#[cfg(target_os = "windows")]
+fn set_dwm_color(window: &Window, attribute: DWMWINDOWATTRIBUTE, r: u8, g: u8, b: u8) {
+    let color = u32::from(r) | (u32::from(g) << 8) | (u32::from(b) << 16);
+    let _ = set_dwm_attribute(window, attribute, color);
+}
+
+/// Set the title bar background color. Call from a callback with the WryWindow pointer.
+/// Platform: Windows 11 (`DWMWA_CAPTION_COLOR`); no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_title_bar_color(win: *mut WryWindow, r: u8, g: u8, b: u8) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        set_dwm_color(w, windows::Win32::Graphics::Dwm::DWMWA_CAPTION_COLOR, r, g, b);
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = (win, r, g, b);
+}
+
+/// Set the window border color. Call from a callback with the WryWindow pointer.
+/// Platform: Windows 11 (`DWMWA_BORDER_COLOR`); no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_border_color(win: *mut WryWindow, r: u8, g: u8, b: u8) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        set_dwm_color(w, windows::Win32::Graphics::Dwm::DWMWA_BORDER_COLOR, r, g, b);
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = (win, r, g, b);
+}
+
+/// Set the Windows 11 system backdrop drawn behind the window.
+/// material: 0 = None, 1 = Mica, 2 = Acrylic, 3 = Tabbed (Mica Alt).
+/// The backdrop only shows through transparent content: create the window with `transparent`,
+/// a zero-alpha background color and a page without its own background. The frame is extended
+/// into the client area while a backdrop is set.
+/// Call from a callback with the WryWindow pointer. Platform: Windows 11 22H2+; no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_system_backdrop(win: *mut WryWindow, material: c_int) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        use tao::platform::windows::WindowExtWindows;
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::Graphics::Dwm::*;
+        use windows::Win32::UI::Controls::MARGINS;
+        let backdrop = match material {
+            1 => DWMSBT_MAINWINDOW,
+            2 => DWMSBT_TRANSIENTWINDOW,
+            3 => DWMSBT_TABBEDWINDOW,
+            _ => DWMSBT_NONE,
+        };
+        let extend = if backdrop == DWMSBT_NONE { 0 } else { -1 };
+        let margins = MARGINS {
+            cxLeftWidth: extend,
+            cxRightWidth: extend,
+            cyTopHeight: extend,
+            cyBottomHeight: extend,
+        };
+        let result = unsafe { DwmExtendFrameIntoClientArea(HWND(w.hwnd() as _), &margins) }
+            .and_then(|()| set_dwm_attribute(w, DWMWA_SYSTEMBACKDROP_TYPE, backdrop.0 as u32));
+        log_err!(result, "system backdrop");
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = (win, material);
+}
+
+/// Blur the desktop behind the window with an NSVisualEffectView placed under the webview.
+/// material: 0 = None (remove), 1 = Titlebar, 2 = Selection, 3 = Menu, 4 = Popover, 5 = Sidebar,
+/// 6 = HeaderView, 7 = Sheet, 8 = WindowBackground, 9 = HudWindow, 10 = FullScreenUI, 11 = Tooltip,
+/// 12 = ContentBackground, 13 = UnderWindowBackground, 14 = UnderPageBackground.
+/// Only visible through transparent content: create the window with `transparent`, a zero-alpha
+/// background color and a page without its own background.
+/// Call from a callback with the WryWindow pointer. Platform: macOS; no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_vibrancy(win: *mut WryWindow, material: c_int) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    #[cfg(target_os = "macos")]
+    win.set_vibrancy(material);
+    #[cfg(not(target_os = "macos"))]
+    let _ = (win, material);
+}
+
+/// Set the taskbar (Windows) / dock (macOS) / launcher (Linux, Unity) progress bar.
+/// state: 0 = None (hidden), 1 = Indeterminate, 2 = Normal, 3 = Error, 4 = Paused.
+/// progress: 0.0-1.0, clamped; ignored for None and Indeterminate.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_progress_bar(win: *mut WryWindow, state: c_int, progress: f64) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        use tao::window::{ProgressBarState, ProgressState};
+        let state = match state {
+            1 => ProgressState::Indeterminate,
+            2 => ProgressState::Normal,
+            3 => ProgressState::Error,
+            4 => ProgressState::Paused,
+            _ => ProgressState::None,
+        };
+        let progress = if progress.is_finite() { progress.clamp(0.0, 1.0) } else { 0.0 };
+        w.set_progress_bar(ProgressBarState {
+            state: Some(state),
+            progress: Some((progress * 100.0).round() as u64),
+            desktop_filename: None,
+        });
+    }
+}
+
+/// Show a badge count for the window: macOS dock badge, Windows taskbar overlay icon,
+/// Linux launcher count (Unity launcher API, where the desktop supports it). 0 or negative clears.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_badge_count(win: *mut WryWindow, count: i64) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    win.apply_badge_count(count);
+}
+
+/// Show a small status overlay on the window's taskbar button (e.g. a glyph for "busy" or
+/// "error"), from `width` x `height` RGBA pixels. `description` is the accessibility text read
+/// for it. Pass null `rgba` to clear. Shares the overlay slot with `wry_window_set_badge_count`,
+/// so whichever is set last wins. Call from a callback with the WryWindow pointer.
+///
+/// Platform: Windows only (ITaskbarList3::SetOverlayIcon); no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_taskbar_overlay(
+    win: *mut WryWindow,
+    rgba: *const u8,
+    width: c_int,
+    height: c_int,
+    description: *const c_char,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    let Some(ref _w) = win.window else { return; };
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::WindowExtWindows;
+        use windows::core::HSTRING;
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+        use windows::Win32::UI::HiDpi::{GetDpiForWindow, GetSystemMetricsForDpi};
+        use windows::Win32::UI::Shell::{ITaskbarList3, TaskbarList};
+        use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, HICON, SM_CXSMICON};
+        let hwnd = HWND(_w.hwnd() as _);
+        let description = HSTRING::from(unsafe { c_str_to_string(description) });
+        let icon = if rgba.is_null() || width <= 0 || height <= 0 {
+            None
+        } else {
+            let data = unsafe { std::slice::from_raw_parts(rgba, width as usize * height as usize * 4) }.to_vec();
+            let Some(img) = image::RgbaImage::from_raw(width as u32, height as u32, data) else { return; };
+            let size = unsafe { GetSystemMetricsForDpi(SM_CXSMICON, GetDpiForWindow(hwnd).max(96)) }.max(1) as u32;
+            match create_hicon(&img, size) {
+                Ok(icon) => Some(icon),
+                Err(e) => {
+                    eprintln!("[wry-native] set taskbar overlay: CreateIcon failed: {}", e);
+                    return;
+                }
+            }
+        };
+        let result = unsafe {
+            CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER).and_then(|taskbar| {
+                taskbar.HrInit()?;
+                taskbar.SetOverlayIcon(hwnd, icon.unwrap_or(HICON::default()), &description)
+            })
+        };
+        log_err!(result, "set taskbar overlay");
+        // The taskbar keeps its own copy.
+        if let Some(icon) = icon {
+            log_err!(unsafe { DestroyIcon(icon) }, "set taskbar overlay DestroyIcon");
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = (rgba, width, height, description);
+}
+
+/// Attach an opaque pointer to the window, e.g. a GCHandle to host-side per-window state, so
+/// callbacks handed the WryWindow pointer can recover it without an ID lookup. Never
+/// dereferenced or freed by the library; free it from the close handler or
+/// `wry_app_on_window_destroyed`. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_user_data(win: *mut WryWindow, data: *mut c_void) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    win.user_data = data as usize;
+}
+
+/// Return the pointer stored with `wry_window_set_user_data`, or null if none was set.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_get_user_data(win: *mut WryWindow) -> *mut c_void {
+    if win.is_null() {
+        return std::ptr::null_mut();
+    }
+    let win = unsafe { &*win };
+    win.user_data as *mut c_void
+}
+
+/// Set the taskbar thumbnail toolbar: up to 7 buttons (e.g. previous / play / next) under the
+/// window's taskbar preview. `buttons` is an array of `count` WryThumbnailButton; clicks go to
+/// the `wry_window_on_thumbnail_button` callback with the button's `id`.
+/// Windows only allows adding buttons once per window, so the first call fixes the number of
+/// buttons and their IDs (entries beyond 7 are ignored). Later calls update those slots in order -
+/// icon, tooltip, enabled state; their `id` is ignored - and hide slots they do not fill, so an
+/// empty list hides them all.
+/// The taskbar button must exist, so call after the window is shown. Returns false on failure.
+/// Call from a callback with the WryWindow pointer.
+///
+/// Platform: Windows only (ITaskbarList3::ThumbBarAddButtons / ThumbBarUpdateButtons); no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_thumbnail_toolbar(
+    win: *mut WryWindow,
+    buttons: *const WryThumbnailButton,
+    count: c_int,
+) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &mut *win };
+    let buttons: &[WryThumbnailButton] = if buttons.is_null() || count <= 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(buttons, count as usize) }
+    };
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::WindowExtWindows;
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+        use windows::Win32::UI::HiDpi::{GetDpiForWindow, GetSystemMetricsForDpi};
+        use windows::Win32::UI::Shell::{
+            ITaskbarList3, TaskbarList, THBF_DISABLED, THBF_ENABLED, THBF_HIDDEN, THB_FLAGS, THB_ICON, THB_TOOLTIP,
+            THUMBBUTTON,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, SM_CXSMICON};
+        const MAX_BUTTONS: usize = 7;
+
+        let Some(ref w) = win.window else { return false; };
+        let hwnd = HWND(w.hwnd() as _);
+        if buttons.len() > MAX_BUTTONS {
+            eprintln!("[wry-native] thumbnail toolbar: {} buttons given, only {} are shown", buttons.len(), MAX_BUTTONS);
+        }
+        let first = win.thumbnail_ids.is_none();
+        let ids = win
+            .thumbnail_ids
+            .clone()
+            .unwrap_or_else(|| buttons.iter().take(MAX_BUTTONS).map(|b| b.id as u32).collect());
+        if ids.is_empty() {
+            return true; // nothing added yet and nothing to add
+        }
+        let icon_size = unsafe { GetSystemMetricsForDpi(SM_CXSMICON, GetDpiForWindow(hwnd).max(96)) }.max(1) as u32;
+        let mut icons = Vec::new();
+        let thumbs: Vec<THUMBBUTTON> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| {
+                let Some(b) = buttons.get(i) else {
+                    return THUMBBUTTON { dwMask: THB_FLAGS, iId: id, dwFlags: THBF_HIDDEN, ..Default::default() };
+                };
+                let mut thumb = THUMBBUTTON {
+                    dwMask: THB_FLAGS | THB_TOOLTIP,
+                    iId: id,
+                    szTip: to_wide_fixed(&unsafe { c_str_to_string(b.tooltip) }),
+                    dwFlags: if b.enabled != 0 { THBF_ENABLED } else { THBF_DISABLED },
+                    ..Default::default()
+                };
+                if !b.rgba.is_null() && b.width > 0 && b.height > 0 {
+                    let len = b.width as usize * b.height as usize * 4;
+                    let data = unsafe { std::slice::from_raw_parts(b.rgba, len) }.to_vec();
+                    let icon = image::RgbaImage::from_raw(b.width as u32, b.height as u32, data)
+                        .map(|img| create_hicon(&img, icon_size));
+                    match icon {
+                        Some(Ok(icon)) => {
+                            thumb.dwMask |= THB_ICON;
+                            thumb.hIcon = icon;
+                            icons.push(icon);
+                        }
+                        Some(Err(e)) => eprintln!("[wry-native] thumbnail toolbar: CreateIcon failed: {}", e),
+                        None => {}
+                    }
+                }
+                thumb
+            })
+            .collect();
+        let result = unsafe {
+            CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER).and_then(|taskbar| {
+                taskbar.HrInit()?;
+                if first {
+                    taskbar.ThumbBarAddButtons(hwnd, &thumbs)
+                } else {
+                    taskbar.ThumbBarUpdateButtons(hwnd, &thumbs)
+                }
+            })
+        };
+        if let Err(e) = result {
+            eprintln!("[wry-native] thumbnail toolbar failed: {}", e);
+            for icon in icons {
+                let _ = unsafe { DestroyIcon(icon) };
+            }
+            return false;
+        }
+        win.thumbnail_ids = Some(ids);
+        for old in std::mem::replace(&mut win.thumbnail_icons, icons) {
+            let _ = unsafe { DestroyIcon(old) };
+        }
+        true
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (win, buttons);
+        false
+    }
+}
+
+/// Register the callback for taskbar thumbnail toolbar clicks (see
+/// `wry_window_set_thumbnail_toolbar`); it receives the clicked button's `id`. Replaces a
+/// previous callback; pass null to remove it. Call from a callback with the WryWindow pointer.
+///
+/// Platform: Windows only; no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_on_thumbnail_button(
+    win: *mut WryWindow,
+    callback: Option<ThumbnailButtonCallback>,
+    ctx: *mut c_void,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        set_thumbnail_subclass(w, callback.map(|cb| (cb, ctx as usize)));
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = (win, callback, ctx);
+}
+
+/// Mark the window's document as edited (dot in the close button). Call from a callback with
+/// the WryWindow pointer. Platform: macOS; no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_document_edited(win: *mut WryWindow, edited: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "macos")]
+    if let Some(ref w) = win.window {
+        use tao::platform::macos::WindowExtMacOS;
+        w.set_is_document_edited(edited);
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (win, edited);
+}
+
+/// Set the file shown as the title-bar proxy icon. Null or empty path clears it.
+/// Call from a callback with the WryWindow pointer. Platform: macOS; no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_represented_filename(win: *mut WryWindow, path: *const c_char) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    let path = if path.is_null() { String::new() } else { unsafe { c_str_to_string(path) } };
+    #[cfg(target_os = "macos")]
+    if let Some(ref w) = win.window {
+        use objc2_app_kit::NSWindow;
+        use objc2_foundation::NSString;
+        use tao::platform::macos::WindowExtMacOS;
+        unsafe {
+            let ns_window = &*(w.ns_window() as *const NSWindow);
+            ns_window.setRepresentedFilename(&NSString::from_str(&path));
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (win, path);
+}
+
+/// Let drags on the window background move the window (`NSWindow.movableByWindowBackground`).
+/// Views decide whether they count as background; the webview does not, so this covers the parts of
+/// the window it leaves uncovered (use `app_region_drag` for drag areas inside the page).
+/// Call from a callback with the WryWindow pointer. Platform: macOS; no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_movable_by_background(win: *mut WryWindow, enabled: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "macos")]
+    if let Some(ref w) = win.window {
+        use objc2_app_kit::NSWindow;
+        use tao::platform::macos::WindowExtMacOS;
+        unsafe {
+            let ns_window = &*(w.ns_window() as *const NSWindow);
+            ns_window.setMovableByWindowBackground(enabled);
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (win, enabled);
+}
+
+/// Whether drags on the window background move the window. Platform: macOS; false elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_get_movable_by_background(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "macos")]
+    if let Some(ref w) = win.window {
+        use objc2_app_kit::NSWindow;
+        use tao::platform::macos::WindowExtMacOS;
+        let ns_window = unsafe { &*(w.ns_window() as *const NSWindow) };
+        return ns_window.isMovableByWindowBackground();
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = win;
+    false
+}
+
+/// Keep the window out of the Window menu and the Dock's window list
+/// (`NSWindow.excludedFromWindowsMenu`), e.g. for palettes and other auxiliary windows. The app
+/// switcher (Cmd-Tab) lists apps, not windows, so it is unaffected either way.
+/// Call from a callback with the WryWindow pointer. Platform: macOS; no-op elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_set_excluded_from_windows_menu(win: *mut WryWindow, excluded: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "macos")]
+    if let Some(ref w) = win.window {
+        use objc2_app_kit::NSWindow;
+        use tao::platform::macos::WindowExtMacOS;
+        unsafe {
+            let ns_window = &*(w.ns_window() as *const NSWindow);
+            ns_window.setExcludedFromWindowsMenu(excluded);
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (win, excluded);
+}
+
+/// Whether the window is kept out of the Window menu. Platform: macOS; false elsewhere.
+#[no_mangle]
+pub extern "C" fn wry_window_get_excluded_from_windows_menu(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "macos")]
+    if let Some(ref w) = win.window {
+        use objc2_app_kit::NSWindow;
+        use tao::platform::macos::WindowExtMacOS;
+        let ns_window = unsafe { &*(w.ns_window() as *const NSWindow) };
+        return ns_window.isExcludedFromWindowsMenu();
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = win;
+    false
+}
+
+/// Enable or disable mouse input for the whole window. With `enabled = false` the window is
+/// click-through: clicks and hover go to whatever lies beneath and the window (webview included)
+/// receives no mouse input until re-enabled. There is no per-region variant; to keep an
+/// interactive area, toggle this back on from a timer or hotkey (e.g. when the cursor enters it).
+/// Returns false if the platform rejected the change. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_cursor_hittest(win: *mut WryWindow, enabled: bool) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    match win.window {
+        Some(ref w) => match w.set_ignore_cursor_events(!enabled) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("[wry-native] set cursor hittest failed: {}", e);
+                false
+            }
+        },
+        None => false,
+    }
+}
+
+/// Turn kiosk mode on or off. On: borderless fullscreen, no decorations, always on top, not
+/// resizable / minimizable / maximizable / closable, devtools closed; on Windows the webview's
+/// accelerator keys, default context menus and devtools are also disabled. Off: restores the values
+/// saved when kiosk mode was turned on. The individual setters keep working either way.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_kiosk_mode(win: *mut WryWindow, enabled: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    win.set_kiosk_mode(enabled);
+}
+
+/// Set always on bottom. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_always_on_bottom(win: *mut WryWindow, always_on_bottom: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_always_on_bottom(always_on_bottom);
+    }
+}
+
+/// Move the window to the top (`raise = true`) or bottom of the z-order once, without activating
+/// it or changing topmost / always-on-bottom. Call from a callback with the WryWindow pointer.
+///
+/// Windows: `SetWindowPos` with `HWND_TOP` / `HWND_BOTTOM`. macOS: `orderFront` / `orderBack`.
+/// Linux: `gdk_window_raise` / `gdk_window_lower` (window managers may ignore it).
+fn restack(win: *mut WryWindow, raise: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    let Some(ref w) = win.window else { return; };
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::WindowExtWindows;
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::{
+            SetWindowPos, HWND_BOTTOM, HWND_TOP, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
+        };
+        let after = if raise { HWND_TOP } else { HWND_BOTTOM };
+        let flags = SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE;
+        log_err!(unsafe { SetWindowPos(HWND(w.hwnd() as _), Some(after), 0, 0, 0, 0, flags) }, "restack");
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_app_kit::NSWindow;
+        use tao::platform::macos::WindowExtMacOS;
+        let ns_window = unsafe { &*(w.ns_window() as *const NSWindow) };
+        if raise {
+            ns_window.orderFront(None);
+        } else {
+            ns_window.orderBack(None);
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use gtk::prelude::WidgetExt;
+        use tao::platform::unix::WindowExtUnix;
+        if let Some(gdk_window) = w.gtk_window().window() {
+            if raise {
+                gdk_window.raise();
+            } else {
+                gdk_window.lower();
+            }
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let _ = (w, raise);
+}
+
+/// Bring the window to the front of the z-order without focusing it or making it topmost.
+/// Call from a callback with the WryWindow pointer. See `restack` for platform notes.
+#[no_mangle]
+pub extern "C" fn wry_window_raise(win: *mut WryWindow) {
+    restack(win, true);
+}
+
+/// Send the window to the back of the z-order without changing always-on-bottom.
+/// Call from a callback with the WryWindow pointer. See `restack` for platform notes.
+#[no_mangle]
+pub extern "C" fn wry_window_lower(win: *mut WryWindow) {
+    restack(win, false);
+}
+
+/// Ask for a RedrawRequested event, delivered to the window's redraw handler.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_request_redraw(win: *mut WryWindow) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        w.request_redraw();
+    }
+}
+
+/// Set maximizable. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_maximizable(win: *mut WryWindow, maximizable: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_maximizable(maximizable);
+    }
+}
+
+/// Set minimizable. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_minimizable(win: *mut WryWindow, minimizable: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_minimizable(minimizable);
+    }
+}
+
+/// Set closable. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_closable(win: *mut WryWindow, closable: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_closable(closable);
+    }
+}
+
+/// Set focusable. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_focusable(win: *mut WryWindow, focusable: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_focusable(focusable);
+    }
+}
+
+/// Enable or disable mouse and keyboard input to the window. Used for modal dialogs:
+/// disable the owner window while the dialog is open, then re-enable before closing the dialog.
+/// Windows only; no-op on other platforms.
+#[no_mangle]
+pub extern "C" fn wry_window_set_enabled(win: *mut WryWindow, enabled: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        use tao::platform::windows::WindowExtWindows;
+        w.set_enable(enabled);
+    }
+}
+
+/// Returns whether the window is enabled (can receive input). Windows only; returns true on other platforms.
+#[no_mangle]
+pub extern "C" fn wry_window_is_enabled(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return true;
+    }
+    let win = unsafe { &*win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        use tao::platform::windows::WindowExtWindows;
+        return unsafe { windows::Win32::UI::Input::KeyboardAndMouse::IsWindowEnabled(windows::Win32::Foundation::HWND(w.hwnd() as _)) }.as_bool();
+    }
+    true
+}
+
+/// Set webview zoom level. Call from a callback with the WryWindow pointer.
+/// 1.0 = 100%, 2.0 = 200%, etc.
+#[no_mangle]
+pub extern "C" fn wry_window_set_zoom(win: *mut WryWindow, zoom: f64) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let z = if zoom > 0.0 { zoom } else { 1.0 };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.zoom(z), "zoom");
+        if let Some(ref store) = win.zoom_store {
+            if let Some(origin) = wv.url().ok().as_deref().and_then(url_origin) {
+                if let Ok(mut map) = store.lock() {
+                    map.insert(origin, z);
+                }
+            }
+        }
+    }
+}
+
+/// Turn the webview's spellchecker on or off for editable content. Call from a callback with the
+/// WryWindow pointer. The setting is re-applied after every navigation; elements with their own
+/// `spellcheck` attribute keep it. Linux also toggles spell checking on the WebKitGTK web context.
+#[no_mangle]
+pub extern "C" fn wry_window_set_spellcheck(win: *mut WryWindow, enabled: c_int) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    let enabled = enabled != 0;
+    win.spellcheck = Some(enabled);
+    if let Some(ref wv) = win.webview {
+        set_native_spellcheck(wv, enabled);
+        log_err!(wv.evaluate_script(&spellcheck_script(enabled)), "spellcheck");
+    }
+}
+
+/// Change the user agent at runtime; it applies from the next request (reload to re-fetch the
+/// current page with it). `ua` must be non-empty. Returns false if it could not be applied.
+/// Call from a callback with the WryWindow pointer. The `user_agent` config sets it at creation.
+///
+/// Windows: `ICoreWebView2Settings2.UserAgent`. macOS: `WKWebView.customUserAgent`.
+/// Linux: WebKitGTK `user-agent` setting.
+#[no_mangle]
+pub extern "C" fn wry_window_set_user_agent(win: *mut WryWindow, ua: *const c_char) -> bool {
+    if win.is_null() || ua.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    let Some(ref wv) = win.webview else { return false; };
+    let ua = unsafe { c_str_to_string(ua) };
+    if ua.is_empty() {
+        return false;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings2;
+        use windows::core::{Interface, HSTRING};
+        use wry::WebViewExtWindows;
+        let result = unsafe {
+            wv.webview()
+                .Settings()
+                .and_then(|s| s.cast::<ICoreWebView2Settings2>())
+                .and_then(|s| s.SetUserAgent(&HSTRING::from(ua.as_str())))
+        };
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("[wry-native] set user agent failed: {}", e);
+                false
+            }
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_foundation::NSString;
+        use wry::WebViewExtMacOS;
+        unsafe { wv.webview().setCustomUserAgent(Some(&NSString::from_str(&ua))) };
+        true
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{SettingsExt, WebViewExt};
+        use wry::WebViewExtUnix;
+        match WebViewExt::settings(&wv.webview()) {
+            Some(settings) => {
+                settings.set_user_agent(Some(&ua));
+                true
+            }
+            None => false,
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (wv, ua);
+        false
+    }
+}
+
+/// Restore the window from minimized or maximized state.
+/// Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_restore(win: *mut WryWindow) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_minimized(false);
+        w.set_maximized(false);
+    }
+}
+
+/// Set fullscreen state. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_fullscreen(win: *mut WryWindow, fullscreen: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        if fullscreen {
+            w.set_fullscreen(Some(Fullscreen::Borderless(None)));
+        } else {
+            w.set_fullscreen(None);
+        }
+    }
+}
+
+/// Set maximized state. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_maximized(win: *mut WryWindow, maximized: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_maximized(maximized);
+    }
+}
+
+/// Set minimized state. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_minimized(win: *mut WryWindow, minimized: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_minimized(minimized);
+    }
+}
+
+/// Set topmost (always on top) state. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_topmost(win: *mut WryWindow, topmost: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_always_on_top(topmost);
+    }
+}
+
+/// Set visibility state. Call from a callback with the WryWindow pointer.
+/// Cancels a pending show-when-ready, so an explicit hide before the first paint sticks.
+#[no_mangle]
+pub extern "C" fn wry_window_set_visible(win: *mut WryWindow, visible: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    win.show_when_ready = None;
+    if let Some(ref w) = win.window {
+        w.set_visible(visible);
+    }
+    #[cfg(target_os = "linux")]
+    if visible {
+        win.reapply_skip_taskbar();
+    }
+}
+
+/// Enumerate all available monitors. The callback is invoked once per monitor
+/// with its position (x, y), size (width, height) in physical pixels, and
+/// the DPI scale factor. Call from the main thread (from a callback).
+#[no_mangle]
+pub extern "C" fn wry_window_get_all_monitors(
+    win: *mut WryWindow,
+    callback: MonitorCallback,
+    ctx: *mut c_void,
+) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref w) = win.window {
+        for monitor in w.available_monitors() {
+            let pos = monitor.position();
+            let size = monitor.size();
+            let scale = monitor.scale_factor();
+            callback(
+                pos.x as c_int,
+                pos.y as c_int,
+                size.width as c_int,
+                size.height as c_int,
+                scale,
+                ctx,
+            );
+        }
+    }
+}
+
+/// Set resizable state. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_set_resizable(win: *mut WryWindow, resizable: bool) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        w.set_resizable(resizable);
+    }
+}
+
+/// Center the window on its current monitor. Call from a callback with the
+/// WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_center(win: *mut WryWindow) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &mut *win };
+    if let Some(ref w) = win.window {
+        if let Some(monitor) = w.current_monitor() {
+            let screen_size = monitor.size();
+            let window_size = w.outer_size();
+            let x = (screen_size.width as i32 - window_size.width as i32) / 2;
+            let y = (screen_size.height as i32 - window_size.height as i32) / 2;
+            w.set_outer_position(tao::dpi::PhysicalPosition::new(x.max(0), y.max(0)));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WebView runtime methods (post-run, via *mut WryWindow from callbacks)
+// ---------------------------------------------------------------------------
+
+/// Print the webview content. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_print(win: *mut WryWindow) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.print(), "print");
+    }
+}
 
-                UserEvent::TrayMenuEvent(ref event) => {
-                    let menu_id: &str = event.id.as_ref();
-                    for t in live_trays.values() {
-                        if t.live_items.contains_key(menu_id) {
-                            t.handle_menu_event(menu_id);
-                            break;
-                        }
-                    }
-                }
+/// Print with job settings. Returns false if the options are invalid (malformed page ranges,
+/// unknown printer on macOS) or the print could not be started. `options` null = wry_window_print.
+/// Call from a callback with the WryWindow pointer.
+///
+/// - Windows: silent jobs use WebView2 `Print` (runtime 1.0.1518+) with all settings. With a dialog
+///   the browser print preview is shown and the settings are not applied.
+/// - macOS: `NSPrintOperation` from the WKWebView; the dialog, when shown, starts from the settings.
+///   Pages print from the first to the last page listed in `page_ranges`.
+/// - Linux: WebKitGTK print operation; the dialog, when shown, starts from the settings.
+#[no_mangle]
+pub extern "C" fn wry_window_print_ex(win: *mut WryWindow, options: *const WryPrintOptions) -> bool {
+    if win.is_null() {
+        return false;
+    }
+    let win = unsafe { &*win };
+    let Some(ref wv) = win.webview else { return false; };
+    if options.is_null() {
+        return wv.print().is_ok();
+    }
+    let o = unsafe { &*options };
+    let ranges_spec = unsafe { c_str_to_string(o.page_ranges) };
+    let Some(ranges) = parse_page_ranges(&ranges_spec) else {
+        eprintln!("[wry-native] print: invalid page ranges {:?}", ranges_spec);
+        return false;
+    };
+    let job = PrintJob {
+        silent: o.silent != 0,
+        printer: Some(unsafe { c_str_to_string(o.printer) }).filter(|p| !p.is_empty()),
+        copies: o.copies.max(1),
+        ranges,
+        orientation: o.orientation,
+    };
+    match print_job(win, wv, &job) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("[wry-native] print failed: {}", e);
+            false
+        }
+    }
+}
 
-                UserEvent::TrayDispatch { tray_id, callback, ctx } => {
-                    if let Some(t) = live_trays.get_mut(&tray_id) {
-                        t.handle_dispatch(callback, ctx);
-                    }
-                }
+/// wry_window_print_ex settings after validation.
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos", target_os = "linux")), allow(dead_code))]
+struct PrintJob {
+    silent: bool,
+    printer: Option<String>,
+    copies: c_int,
+    /// 1-based inclusive ranges; empty = all pages.
+    ranges: Vec<(u32, u32)>,
+    orientation: c_int,
+}
 
-                UserEvent::TrayRemove { tray_id } => {
-                    live_trays.remove(&tray_id);
-                    if live_windows.is_empty() && live_trays.is_empty() {
-                        *control_flow = ControlFlow::Exit;
-                    }
-                }
+/// Parse "1-3, 5" (1-based, inclusive) into ranges; empty = all pages. None if malformed.
+fn parse_page_ranges(spec: &str) -> Option<Vec<(u32, u32)>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (first, last) = part.split_once('-').unwrap_or((part, part));
+            let (first, last): (u32, u32) = (first.trim().parse().ok()?, last.trim().parse().ok()?);
+            (first >= 1 && last >= first).then_some((first, last))
+        })
+        .collect()
+}
 
-                UserEvent::RequestExit { code } => {
-                    let should_exit = if let Some((cb, ctx)) = exit_requested_handler {
-                        cb(true, code, ctx as *mut c_void)
-                    } else {
-                        true
-                    };
-                    if should_exit {
-                        live_trays.clear();
-                        *control_flow = ControlFlow::Exit;
-                    }
-                }
+#[cfg(target_os = "windows")]
+fn print_job(_win: &WryWindow, wv: &WebView, job: &PrintJob) -> Result<(), String> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::*;
+    use webview2_com::PrintCompletedHandler;
+    use windows::core::{Interface, HSTRING};
+    use wry::WebViewExtWindows;
 
-                UserEvent::CreateWindowWithConfig {
-                    id: our_id,
-                    payload,
-                } => {
-                    let owner_window = payload.owner_window_id.and_then(|oid| {
-                        id_to_window_id.get(&oid).and_then(|tid| live_windows.get(tid))
-                            .and_then(|w| w.window.as_ref())
-                    });
-                    let parent_window = payload.parent_window_id.and_then(|pid| {
-                        id_to_window_id.get(&pid).and_then(|tid| live_windows.get(tid))
-                            .and_then(|w| w.window.as_ref())
-                    });
-                    let mut win = WryWindow::new(our_id);
-                    match win.create(&payload, event_loop_target, owner_window, parent_window) {
-                        Ok(()) => {
-                            if let Some(wid) = win.window_id {
-                                id_to_window_id.insert(our_id, wid);
-                                live_windows.insert(wid, win);
-                                if let Some((cb, ctx)) = window_created_handler.as_ref() {
-                                    if let Some(win_ref) = live_windows.get_mut(&wid) {
-                                        cb(*ctx as *mut c_void, our_id, win_ref as *mut WryWindow);
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            if let Some((cb, ctx)) = window_creation_error_handler.as_ref() {
-                                if let Ok(c_msg) = CString::new(e.as_str()) {
-                                    cb(*ctx as *mut c_void, our_id, c_msg.as_ptr());
-                                }
-                            }
-                        }
-                    }
-                }
-            },
+    if !job.silent {
+        return wv.print().map_err(|e| e.to_string());
+    }
+    let start = || -> windows::core::Result<()> {
+        unsafe {
+            let webview16: ICoreWebView2_16 = wv.webview().cast()?;
+            let environment6: ICoreWebView2Environment6 = wv.environment().cast()?;
+            let settings = environment6.CreatePrintSettings()?;
+            match job.orientation {
+                1 => settings.SetOrientation(COREWEBVIEW2_PRINT_ORIENTATION_PORTRAIT)?,
+                2 => settings.SetOrientation(COREWEBVIEW2_PRINT_ORIENTATION_LANDSCAPE)?,
+                _ => {}
+            }
+            let settings2: ICoreWebView2PrintSettings2 = settings.cast()?;
+            settings2.SetCopies(job.copies)?;
+            if let Some(ref printer) = job.printer {
+                settings2.SetPrinterName(&HSTRING::from(printer.as_str()))?;
+            }
+            if !job.ranges.is_empty() {
+                let ranges: Vec<String> = job
+                    .ranges
+                    .iter()
+                    .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+                    .collect();
+                settings2.SetPageRanges(&HSTRING::from(ranges.join(",")))?;
+            }
+            let handler = PrintCompletedHandler::create(Box::new(|result, _| {
+                log_err!(result, "print");
+                Ok(())
+            }));
+            webview16.Print(&settings, &handler)
+        }
+    };
+    start().map_err(|e| e.to_string())
+}
 
+#[cfg(target_os = "macos")]
+fn print_job(win: &WryWindow, wv: &WebView, job: &PrintJob) -> Result<(), String> {
+    use objc2_app_kit::{NSPaperOrientation, NSPrintInfo, NSPrinter, NSWindow};
+    use objc2_app_kit::{NSPrintAllPages, NSPrintCopies, NSPrintFirstPage, NSPrintLastPage};
+    use objc2_foundation::{NSNumber, NSString};
+    use tao::platform::macos::WindowExtMacOS;
+    use wry::WebViewExtMacOS;
+
+    let Some(ref w) = win.window else { return Err("no window".into()); };
+    unsafe {
+        let shared = NSPrintInfo::sharedPrintInfo();
+        let info = NSPrintInfo::initWithDictionary(NSPrintInfo::alloc(), &shared.dictionary());
+        if let Some(ref name) = job.printer {
+            let printer = NSPrinter::printerWithName(&NSString::from_str(name))
+                .ok_or_else(|| format!("unknown printer {}", name))?;
+            info.setPrinter(&printer);
+        }
+        match job.orientation {
+            1 => info.setOrientation(NSPaperOrientation::Portrait),
+            2 => info.setOrientation(NSPaperOrientation::Landscape),
             _ => {}
         }
-    });
+        let dict = info.dictionary();
+        dict.insert(NSPrintCopies, &NSNumber::new_i32(job.copies));
+        let first = job.ranges.iter().map(|r| r.0).min();
+        let last = job.ranges.iter().map(|r| r.1).max();
+        if let (Some(first), Some(last)) = (first, last) {
+            dict.insert(NSPrintAllPages, &NSNumber::new_bool(false));
+            dict.insert(NSPrintFirstPage, &NSNumber::new_u32(first));
+            dict.insert(NSPrintLastPage, &NSNumber::new_u32(last));
+        }
+        let operation = wv.webview().printOperationWithPrintInfo(&info);
+        operation.setShowsPrintPanel(!job.silent);
+        operation.setShowsProgressPanel(!job.silent);
+        let ns_window = &*(w.ns_window() as *const NSWindow);
+        operation.runOperationModalForWindow_delegate_didRunSelector_contextInfo(
+            ns_window,
+            None,
+            None,
+            std::ptr::null_mut(),
+        );
+    }
+    Ok(())
 }
 
-/// Register a callback that fires when all windows have closed or when
-/// `wry_app_exit` is called. The callback receives `has_code` (false for
-/// user-initiated, true for programmatic), `code` (the exit code when
-/// has_code is true), and the context pointer. Return true to allow exit,
-/// false to prevent it. Must be called before `wry_app_run`.
-#[no_mangle]
-pub extern "C" fn wry_app_on_exit_requested(
-    app: *mut WryApp,
-    callback: ExitRequestedCallback,
-    ctx: *mut c_void,
-) {
-    if app.is_null() { return; }
-    let app = unsafe { &mut *app };
-    app.exit_requested_handler = Some((callback, ctx as usize));
+#[cfg(target_os = "linux")]
+fn print_job(win: &WryWindow, wv: &WebView, job: &PrintJob) -> Result<(), String> {
+    use gtk::{PageOrientation, PageRange, PrintPages, PrintSettings};
+    use tao::platform::unix::WindowExtUnix;
+    use webkit2gtk::{PrintOperation, PrintOperationExt};
+    use wry::WebViewExtUnix;
+
+    let settings = PrintSettings::new();
+    if let Some(ref printer) = job.printer {
+        settings.set_printer(printer);
+    }
+    settings.set_n_copies(job.copies);
+    match job.orientation {
+        1 => settings.set_orientation(PageOrientation::Portrait),
+        2 => settings.set_orientation(PageOrientation::Landscape),
+        _ => {}
+    }
+    if !job.ranges.is_empty() {
+        let ranges: Vec<PageRange> = job
+            .ranges
+            .iter()
+            .map(|&(first, last)| PageRange::new(first as i32 - 1, last as i32 - 1))
+            .collect();
+        settings.set_page_ranges(&ranges);
+        settings.set_print_pages(PrintPages::Ranges);
+    }
+    let operation = PrintOperation::new(&wv.webview());
+    operation.set_print_settings(&settings);
+    operation.connect_failed(|_, e| eprintln!("[wry-native] print failed: {}", e));
+    if job.silent {
+        operation.print();
+    } else {
+        let parent = win.window.as_ref().map(|w| w.gtk_window());
+        operation.run_dialog(parent);
+    }
+    Ok(())
 }
 
-/// Register a callback that fires when a window has been materialized and is live.
-/// Called for both initial windows (at startup) and dynamically created windows.
-/// Signature: fn(ctx: *mut c_void, window_id: usize, window_ptr: *mut WryWindow).
-#[no_mangle]
-pub extern "C" fn wry_app_on_window_created(
-    app: *mut WryApp,
-    callback: WindowCreatedCallback,
-    ctx: *mut c_void,
-) {
-    if app.is_null() { return; }
-    let app = unsafe { &mut *app };
-    app.window_created_handler = Some((callback, ctx as usize));
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn print_job(_win: &WryWindow, wv: &WebView, _job: &PrintJob) -> Result<(), String> {
+    wv.print().map_err(|e| e.to_string())
 }
 
-/// Register a callback that fires when dynamic window creation fails (async path only).
-/// Signature: fn(ctx: *mut c_void, window_id: usize, error_message: *const c_char). error_message is UTF-8.
+/// Render the page to PDF without a dialog and pass the bytes to `callback` (once, on the event
+/// loop thread; null / 0 on failure). `options` may be null for platform defaults. Call from a
+/// callback with the WryWindow pointer.
+///
+/// - Windows: WebView2 `PrintToPdf` (runtime 1.0.1020+), honoring all options.
+/// - Linux: WebKitGTK print operation to GTK's "Print to File" printer, honoring all options.
+/// - macOS: `WKWebView.createPDF` (macOS 11+): the whole page as one page of its own size; the
+///   page setup options do not apply.
 #[no_mangle]
-pub extern "C" fn wry_app_on_window_creation_error(
-    app: *mut WryApp,
-    callback: WindowCreationErrorCallback,
+pub extern "C" fn wry_window_print_to_pdf(
+    win: *mut WryWindow,
+    options: *const WryPdfOptions,
+    callback: PdfCallback,
     ctx: *mut c_void,
 ) {
-    if app.is_null() { return; }
-    let app = unsafe { &mut *app };
-    app.window_creation_error_handler = Some((callback, ctx as usize));
+    let ctx = ctx as usize;
+    let wv = if win.is_null() { None } else { unsafe { (*win).webview.as_ref() } };
+    let Some(wv) = wv else {
+        deliver_pdf(callback, ctx, None);
+        return;
+    };
+    let options = if options.is_null() { WryPdfOptions::default() } else { unsafe { *options } };
+    if let Err(e) = print_to_pdf(wv, &options, callback, ctx) {
+        eprintln!("[wry-native] print to pdf failed: {}", e);
+        deliver_pdf(callback, ctx, None);
+    }
 }
 
-/// Register a callback that fires when a window has been destroyed (platform Destroyed event).
-/// Signature: fn(ctx: *mut c_void, window_id: usize).
-#[no_mangle]
-pub extern "C" fn wry_app_on_window_destroyed(
-    app: *mut WryApp,
-    callback: WindowDestroyedCallback,
-    ctx: *mut c_void,
-) {
-    if app.is_null() { return; }
-    let app = unsafe { &mut *app };
-    app.window_destroyed_handler = Some((callback, ctx as usize));
+fn deliver_pdf(callback: PdfCallback, ctx: usize, pdf: Option<Vec<u8>>) {
+    match pdf {
+        Some(ref bytes) => callback(bytes.as_ptr(), bytes.len() as c_int, ctx as *mut c_void),
+        None => callback(std::ptr::null(), 0, ctx as *mut c_void),
+    }
 }
 
-/// Request the application to exit with the given exit code.
-/// This fires the exit-requested callback (if registered) with has_code=true.
-/// If the callback allows exit (or none is registered), the event loop exits
-/// and any remaining tray icons are removed. Safe to call from any thread.
-#[no_mangle]
-pub extern "C" fn wry_app_exit(app: *mut WryApp, code: c_int) {
-    if app.is_null() { return; }
-    let app = unsafe { &*app };
-    log_err!(app.proxy.send_event(UserEvent::RequestExit { code }), "request exit");
+/// Unique file in the temp directory for a PDF written by the platform and read back.
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+fn pdf_temp_path() -> std::path::PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("wry-native-{}-{}.pdf", std::process::id(), n))
 }
 
-/// Destroy the application handle and free resources.
-#[no_mangle]
-pub extern "C" fn wry_app_destroy(app: *mut WryApp) {
-    if !app.is_null() {
+/// Start rendering; on Ok the result is delivered later, on Err it never is.
+#[cfg(target_os = "windows")]
+fn print_to_pdf(wv: &WebView, options: &WryPdfOptions, callback: PdfCallback, ctx: usize) -> Result<(), String> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::*;
+    use webview2_com::PrintToPdfCompletedHandler;
+    use windows::core::{Interface, HSTRING};
+    use wry::WebViewExtWindows;
+
+    let start = || -> windows::core::Result<()> {
         unsafe {
-            drop(Box::from_raw(app));
+            let webview7: ICoreWebView2_7 = wv.webview().cast()?;
+            let environment6: ICoreWebView2Environment6 = wv.environment().cast()?;
+            let settings = environment6.CreatePrintSettings()?;
+            settings.SetOrientation(if options.landscape != 0 {
+                COREWEBVIEW2_PRINT_ORIENTATION_LANDSCAPE
+            } else {
+                COREWEBVIEW2_PRINT_ORIENTATION_PORTRAIT
+            })?;
+            if options.page_width > 0.0 && options.page_height > 0.0 {
+                settings.SetPageWidth(options.page_width)?;
+                settings.SetPageHeight(options.page_height)?;
+            }
+            if options.margin_top >= 0.0 {
+                settings.SetMarginTop(options.margin_top)?;
+            }
+            if options.margin_right >= 0.0 {
+                settings.SetMarginRight(options.margin_right)?;
+            }
+            if options.margin_bottom >= 0.0 {
+                settings.SetMarginBottom(options.margin_bottom)?;
+            }
+            if options.margin_left >= 0.0 {
+                settings.SetMarginLeft(options.margin_left)?;
+            }
+            settings.SetShouldPrintBackgrounds(options.print_backgrounds != 0)?;
+
+            let path = pdf_temp_path();
+            let result_path = path.clone();
+            let handler = PrintToPdfCompletedHandler::create(Box::new(move |result, success| {
+                let pdf = if result.is_ok() && success { std::fs::read(&result_path).ok() } else { None };
+                let _ = std::fs::remove_file(&result_path);
+                deliver_pdf(callback, ctx, pdf);
+                Ok(())
+            }));
+            webview7.PrintToPdf(&HSTRING::from(path.as_os_str()), &settings, &handler)
+        }
+    };
+    start().map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn print_to_pdf(wv: &WebView, _options: &WryPdfOptions, callback: PdfCallback, ctx: usize) -> Result<(), String> {
+    use objc2_foundation::{NSData, NSError};
+    use wry::WebViewExtMacOS;
+
+    let pending = std::cell::Cell::new(Some((callback, ctx)));
+    let completed = block2::RcBlock::new(move |data: *mut NSData, _error: *mut NSError| {
+        let Some((callback, ctx)) = pending.take() else { return; };
+        let pdf = unsafe { data.as_ref() }.map(|d| d.to_vec());
+        deliver_pdf(callback, ctx, pdf);
+    });
+    unsafe { wv.webview().createPDFWithConfiguration_completionHandler(None, &completed) };
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn print_to_pdf(wv: &WebView, options: &WryPdfOptions, callback: PdfCallback, ctx: usize) -> Result<(), String> {
+    use gtk::{PageOrientation, PageSetup, PaperSize, PrintSettings, Unit};
+    use webkit2gtk::{PrintOperation, PrintOperationExt, SettingsExt, WebViewExt};
+    use wry::WebViewExtUnix;
+
+    let path = pdf_temp_path();
+    let uri = gtk::glib::filename_to_uri(&path, None).map_err(|e| e.to_string())?;
+    let settings = PrintSettings::new();
+    // GTK's file backend; "output-uri" and "output-file-format" are only honored by it.
+    settings.set_printer("Print to File");
+    settings.set("output-uri", Some(uri.as_str()));
+    settings.set("output-file-format", Some("pdf"));
+
+    let setup = PageSetup::new();
+    if options.page_width > 0.0 && options.page_height > 0.0 {
+        let paper = PaperSize::new_custom("wry-native", "Custom", options.page_width, options.page_height, Unit::Inch);
+        setup.set_paper_size(&paper);
+    }
+    if options.landscape != 0 {
+        setup.set_orientation(PageOrientation::Landscape);
+    }
+    if options.margin_top >= 0.0 {
+        setup.set_top_margin(options.margin_top, Unit::Inch);
+    }
+    if options.margin_right >= 0.0 {
+        setup.set_right_margin(options.margin_right, Unit::Inch);
+    }
+    if options.margin_bottom >= 0.0 {
+        setup.set_bottom_margin(options.margin_bottom, Unit::Inch);
+    }
+    if options.margin_left >= 0.0 {
+        setup.set_left_margin(options.margin_left, Unit::Inch);
+    }
+
+    let view = wv.webview();
+    // The setting is shared with regular printing; put the page's own value back when done.
+    let restore_backgrounds = std::cell::Cell::new(None);
+    if options.print_backgrounds != 0 {
+        if let Some(web_settings) = WebViewExt::settings(&view) {
+            if !web_settings.is_print_backgrounds() {
+                web_settings.set_print_backgrounds(true);
+                restore_backgrounds.set(Some(web_settings));
+            }
         }
     }
+    let operation = PrintOperation::new(&view);
+    operation.set_print_settings(&settings);
+    operation.set_page_setup(&setup);
+
+    // "failed" is followed by "finished"; whichever comes first answers.
+    let pending = std::rc::Rc::new(std::cell::Cell::new(Some((callback, ctx))));
+    let failed = pending.clone();
+    operation.connect_failed(move |_, e| {
+        eprintln!("[wry-native] print to pdf failed: {}", e);
+        if let Some((callback, ctx)) = failed.take() {
+            deliver_pdf(callback, ctx, None);
+        }
+    });
+    operation.connect_finished(move |_| {
+        if let Some(web_settings) = restore_backgrounds.take() {
+            web_settings.set_print_backgrounds(false);
+        }
+        if let Some((callback, ctx)) = pending.take() {
+            let pdf = std::fs::read(&path).ok();
+            let _ = std::fs::remove_file(&path);
+            deliver_pdf(callback, ctx, pdf);
+        }
+    });
+    operation.print();
+    Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Window creation
-// ---------------------------------------------------------------------------
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn print_to_pdf(_wv: &WebView, _options: &WryPdfOptions, _callback: PdfCallback, _ctx: usize) -> Result<(), String> {
+    Err("not supported on this platform".into())
+}
 
-/// Create a window with optional config. Pass 0 for owner/parent for top-level.
-/// config: null = default params; or pointer to WryWindowConfig for title, url, size, etc.
-/// Before run: window is stored in app.windows. After run: posts CreateWindowWithConfig (no queue).
-/// Returns window ID (never 0 on success).
+/// Replace the window's webview with a fresh one built from its creation config, keeping the
+/// tao window (position, size, state). The new webview gets the same handlers, init scripts and
+/// settings and loads the configured URL / HTML; runtime changes made to the old one (zoom,
+/// user agent, navigation history) are not carried over. Use it to recover from a crashed or
+/// leaking renderer (see the webview crash callback).
+///
+/// The rebuild runs on the event loop after the current callback returns, so it is safe to call
+/// from handlers raised by the webview itself. Returns false if the window is not created. Call
+/// from a callback with the WryWindow pointer (e.g. via wry_window_dispatch).
 #[no_mangle]
-pub extern "C" fn wry_window_create(
-    app: *mut WryApp,
-    owner_window_id: usize,
-    parent_window_id: usize,
-    config: *const c_void,
-) -> usize {
-    if app.is_null() {
-        return 0;
+pub extern "C" fn wry_window_reset_webview(win: *mut WryWindow) -> bool {
+    if win.is_null() {
+        return false;
     }
-    let app = unsafe { &mut *app };
-    let id = app.next_window_id;
-    app.next_window_id += 1;
+    let win = unsafe { &*win };
+    let Some(ref proxy) = win.proxy else { return false; };
+    let sent = proxy.send_event(UserEvent::ResetWebview { window_id: win.id });
+    log_err!(sent.as_ref(), "reset webview dispatch");
+    sent.is_ok()
+}
 
-    let mut payload = if config.is_null() {
-        WindowCreatePayload::default()
-    } else {
-        payload_from_config(config as *const WryWindowConfig)
-    };
-    if owner_window_id != 0 {
-        payload.owner_window_id = Some(owner_window_id);
-        payload.parent_window_id = None;
-    } else if parent_window_id != 0 {
-        payload.parent_window_id = Some(parent_window_id);
-        payload.owner_window_id = None;
+/// Reload the current page. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_reload(win: *mut WryWindow) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.reload(), "reload");
     }
+}
 
-    if !app.run_started.load(Ordering::SeqCst) {
-        let win = WryWindow::new(id);
-        app.windows.insert(id, win);
-        app.payloads.insert(id, payload);
-        return id;
+/// Reload the page bypassing the HTTP cache ("hard reload"). Call from a callback with the
+/// WryWindow pointer.
+///
+/// Windows: DevTools protocol `Page.reload` with `ignoreCache` (WebView2 `Reload` has no flag).
+/// macOS: `WKWebView.reloadFromOrigin`. Linux: `webkit_web_view_reload_bypass_cache`.
+/// If the platform call fails, navigates to the current URL with a cache-busting query parameter.
+#[no_mangle]
+pub extern "C" fn wry_window_reload_ignore_cache(win: *mut WryWindow) {
+    if win.is_null() {
+        return;
     }
+    let win = unsafe { &*win };
+    let Some(ref wv) = win.webview else { return; };
 
-    let _ = app.proxy.send_event(UserEvent::CreateWindowWithConfig {
-        id,
-        payload: Box::new(payload),
-    });
-    id
+    #[cfg(target_os = "windows")]
+    let done = {
+        use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+        use windows::core::w;
+        use wry::WebViewExtWindows;
+        let handler = CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(())));
+        let result = unsafe {
+            wv.webview().CallDevToolsProtocolMethod(w!("Page.reload"), w!("{\"ignoreCache\":true}"), &handler)
+        };
+        log_err!(result.as_ref(), "reload ignore cache");
+        result.is_ok()
+    };
+    #[cfg(target_os = "macos")]
+    let done = {
+        use wry::WebViewExtMacOS;
+        unsafe { wv.webview().reloadFromOrigin() };
+        true
+    };
+    #[cfg(target_os = "linux")]
+    let done = {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+        wv.webview().reload_bypass_cache();
+        true
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let done = false;
+
+    if !done {
+        if let Ok(url) = wv.url() {
+            let stamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or(0);
+            log_err!(wv.load_url(&cache_bust_url(&url, stamp)), "reload ignore cache (fallback)");
+        }
+    }
+}
+
+/// Move focus to the webview. Call from a callback with the WryWindow pointer.
+#[no_mangle]
+pub extern "C" fn wry_window_focus(win: *mut WryWindow) {
+    if win.is_null() {
+        return;
+    }
+    let win = unsafe { &*win };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.focus(), "focus");
+    }
 }
 
-// ---------------------------------------------------------------------------
-// JavaScript evaluation (post-run: use *mut WryWindow)
-// ---------------------------------------------------------------------------
-
-/// Evaluate JavaScript in the webview. Must be called post-run (from a callback
-/// or dispatch) with the `*mut WryWindow` pointer.
+/// Move focus away from the webview back to the parent window.
+/// Call from a callback with the WryWindow pointer.
+///
+/// Platform: Android not implemented.
 #[no_mangle]
-pub extern "C" fn wry_window_eval_js(win: *mut WryWindow, js: *const c_char) {
-    if win.is_null() || js.is_null() {
+pub extern "C" fn wry_window_focus_parent(win: *mut WryWindow) {
+    if win.is_null() {
         return;
     }
-    let win = unsafe { &mut *win };
-    let js = unsafe { c_str_to_string(js) };
+    let win = unsafe { &*win };
     if let Some(ref wv) = win.webview {
-        log_err!(wv.evaluate_script(&js), "evaluate_script");
+        log_err!(wv.focus_parent(), "focus_parent");
     }
 }
 
-/// Evaluate JavaScript in the webview and receive the result via a callback.
-/// The callback receives the JSON-encoded result string (or an error message).
-/// Must be called post-run (from a callback or dispatch).
+/// Clear all browsing data. Call from a callback with the WryWindow pointer.
 #[no_mangle]
-pub extern "C" fn wry_window_eval_js_callback(
-    win: *mut WryWindow,
-    js: *const c_char,
-    callback: EvalResultCallback,
-    ctx: *mut c_void,
-) {
-    if win.is_null() || js.is_null() {
+pub extern "C" fn wry_window_clear_all_browsing_data(win: *mut WryWindow) {
+    if win.is_null() {
         return;
     }
-    let win = unsafe { &mut *win };
-    let js = unsafe { c_str_to_string(js) };
+    let win = unsafe { &*win };
     if let Some(ref wv) = win.webview {
-        let ctx_usize = ctx as usize;
-        log_err!(wv.evaluate_script_with_callback(&js, move |result| {
-            match CString::new(result.as_str()) {
-                Ok(cs) => {
-                    callback(cs.as_ptr(), ctx_usize as *mut c_void);
-                }
-                Err(_) => {
-                    // If the result contains null bytes, pass empty
-                    let empty = CString::new("").unwrap();
-                    callback(empty.as_ptr(), ctx_usize as *mut c_void);
-                }
-            };
-        }), "evaluate_script_with_callback");
+        log_err!(wv.clear_all_browsing_data(), "clear_all_browsing_data");
     }
 }
 
-/// Respond to a custom protocol request. Must be called exactly once per
-/// protocol handler invocation. `responder` is the opaque pointer passed to
-/// the protocol handler callback.
+// Browsing data kinds for wry_window_clear_browsing_data.
+const BROWSING_DATA_COOKIES: c_int = 1;
+const BROWSING_DATA_CACHE: c_int = 2;
+const BROWSING_DATA_LOCAL_STORAGE: c_int = 4;
+const BROWSING_DATA_INDEXED_DB: c_int = 8;
+const BROWSING_DATA_SERVICE_WORKERS: c_int = 16;
+
+/// Clear selected kinds of browsing data for the webview's profile / data store. `flags` is a
+/// bitmask: 1 = Cookies, 2 = Cache (disk, memory and Cache Storage), 4 = LocalStorage,
+/// 8 = IndexedDB, 16 = Service workers. Clearing runs asynchronously; returns false if it could
+/// not be started. Call from a callback with the WryWindow pointer.
 ///
-/// - `data`: pointer to response body bytes
-/// - `data_len`: length of response body
-/// - `content_type`: MIME type as a UTF-8 C string (e.g. "text/html")
-/// - `status_code`: HTTP status code (e.g. 200)
-/// - `extra_headers`: additional response headers as "Key: Value\r\n" pairs
-///   (UTF-8 C string). Pass null for no extra headers.
+/// Windows: `ICoreWebView2Profile2::ClearBrowsingData` (runtime 1.0.1245+).
+/// macOS: `WKWebsiteDataStore removeDataOfTypes`. Linux: `WebsiteDataManager::clear`.
 #[no_mangle]
-pub extern "C" fn wry_protocol_respond(
-    responder: *mut c_void,
-    data: *const u8,
-    data_len: c_int,
-    content_type: *const c_char,
-    status_code: c_int,
-    extra_headers: *const c_char,
-) {
-    if responder.is_null() {
-        return;
+pub extern "C" fn wry_window_clear_browsing_data(win: *mut WryWindow, flags: c_int) -> bool {
+    if win.is_null() || flags == 0 {
+        return false;
     }
+    let win = unsafe { &*win };
+    let Some(ref wv) = win.webview else { return false; };
+    let has = |flag: c_int| flags & flag != 0;
 
-    let responder =
-        unsafe { Box::from_raw(responder as *mut wry::RequestAsyncResponder) };
-
-    let body: Cow<'static, [u8]> = if data.is_null() || data_len <= 0 {
-        Cow::Borrowed(&[])
-    } else {
-        let slice = unsafe { std::slice::from_raw_parts(data, data_len as usize) };
-        Cow::Owned(slice.to_vec())
-    };
-
-    let mime = unsafe { c_str_to_string(content_type) };
-    let status = if (100..600).contains(&status_code) {
-        status_code as u16
-    } else {
-        200
-    };
-
-    let mut builder = http::Response::builder()
-        .status(status)
-        .header("Content-Type", mime);
-
-    // Parse extra headers ("Key: Value\r\n" pairs)
-    if !extra_headers.is_null() {
-        let headers_str = unsafe { c_str_to_string(extra_headers) };
-        for line in headers_str.split("\r\n") {
-            if let Some((key, value)) = line.split_once(": ") {
-                let key = key.trim();
-                let value = value.trim();
-                if !key.is_empty() {
-                    builder = builder.header(key, value);
-                }
+    #[cfg(target_os = "windows")]
+    {
+        use webview2_com::ClearBrowsingDataCompletedHandler;
+        use webview2_com::Microsoft::Web::WebView2::Win32::*;
+        use windows::core::Interface;
+        use wry::WebViewExtWindows;
+        let mut kinds = 0;
+        if has(BROWSING_DATA_COOKIES) {
+            kinds |= COREWEBVIEW2_BROWSING_DATA_KINDS_COOKIES.0;
+        }
+        if has(BROWSING_DATA_CACHE) {
+            kinds |= COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE.0 | COREWEBVIEW2_BROWSING_DATA_KINDS_CACHE_STORAGE.0;
+        }
+        if has(BROWSING_DATA_LOCAL_STORAGE) {
+            kinds |= COREWEBVIEW2_BROWSING_DATA_KINDS_LOCAL_STORAGE.0;
+        }
+        if has(BROWSING_DATA_INDEXED_DB) {
+            kinds |= COREWEBVIEW2_BROWSING_DATA_KINDS_INDEXED_DB.0;
+        }
+        if has(BROWSING_DATA_SERVICE_WORKERS) {
+            kinds |= COREWEBVIEW2_BROWSING_DATA_KINDS_SERVICE_WORKERS.0;
+        }
+        fn clear(wv: &WebView, kinds: COREWEBVIEW2_BROWSING_DATA_KINDS) -> windows::core::Result<()> {
+            unsafe {
+                let profile: ICoreWebView2Profile2 = wv.webview().cast::<ICoreWebView2_13>()?.Profile()?.cast()?;
+                let handler = ClearBrowsingDataCompletedHandler::create(Box::new(|_| Ok(())));
+                profile.ClearBrowsingData(kinds, &handler)
+            }
+        }
+        match clear(wv, COREWEBVIEW2_BROWSING_DATA_KINDS(kinds)) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("[wry-native] clear browsing data failed: {}", e);
+                false
             }
         }
     }
-
-    let response = builder
-        .body(body)
-        .unwrap_or_else(|_| {
-            http::Response::builder()
-                .status(500)
-                .body(Cow::Borrowed(&[] as &[u8]))
-                .unwrap()
-        });
-
-    responder.respond(response);
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_foundation::{NSDate, NSSet, NSString};
+        use objc2_web_kit::*;
+        use wry::WebViewExtMacOS;
+        unsafe {
+            let mut types: Vec<&NSString> = Vec::new();
+            if has(BROWSING_DATA_COOKIES) {
+                types.push(WKWebsiteDataTypeCookies);
+            }
+            if has(BROWSING_DATA_CACHE) {
+                types.extend([WKWebsiteDataTypeDiskCache, WKWebsiteDataTypeMemoryCache, WKWebsiteDataTypeFetchCache]);
+            }
+            if has(BROWSING_DATA_LOCAL_STORAGE) {
+                types.push(WKWebsiteDataTypeLocalStorage);
+            }
+            if has(BROWSING_DATA_INDEXED_DB) {
+                types.push(WKWebsiteDataTypeIndexedDBDatabases);
+            }
+            if has(BROWSING_DATA_SERVICE_WORKERS) {
+                types.push(WKWebsiteDataTypeServiceWorkerRegistrations);
+            }
+            let store = wv.webview().configuration().websiteDataStore();
+            let done = block2::RcBlock::new(|| {});
+            store.removeDataOfTypes_modifiedSince_completionHandler(
+                &NSSet::from_slice(&types),
+                &NSDate::distantPast(),
+                &done,
+            );
+        }
+        true
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{WebViewExt, WebsiteDataManagerExt, WebsiteDataTypes};
+        use wry::WebViewExtUnix;
+        let mut types = WebsiteDataTypes::empty();
+        if has(BROWSING_DATA_COOKIES) {
+            types |= WebsiteDataTypes::COOKIES;
+        }
+        if has(BROWSING_DATA_CACHE) {
+            types |= WebsiteDataTypes::DISK_CACHE | WebsiteDataTypes::MEMORY_CACHE;
+        }
+        if has(BROWSING_DATA_LOCAL_STORAGE) {
+            types |= WebsiteDataTypes::LOCAL_STORAGE;
+        }
+        if has(BROWSING_DATA_INDEXED_DB) {
+            types |= WebsiteDataTypes::INDEXEDDB_DATABASES;
+        }
+        if has(BROWSING_DATA_SERVICE_WORKERS) {
+            types |= WebsiteDataTypes::SERVICE_WORKER_REGISTRATIONS;
+        }
+        match wv.webview().website_data_manager() {
+            Some(manager) => {
+                manager.clear(
+                    types,
+                    webkit2gtk::glib::TimeSpan::from_seconds(0),
+                    None::<&webkit2gtk::gio::Cancellable>,
+                    |result| log_err!(result, "clear browsing data"),
+                );
+                true
+            }
+            None => false,
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (wv, has);
+        false
+    }
 }
 
-// ---------------------------------------------------------------------------
-// Window close (post-run: use *mut WryWindow)
-// ---------------------------------------------------------------------------
-
-/// Request the window to close. If a close callback is set, it will be invoked
-/// first. This must be called from the main thread or via dispatch.
+/// Set the webview background color at runtime (RGBA, 0-255 each).
+/// Call from a callback with the WryWindow pointer.
+///
+/// Platform: macOS not implemented.
 #[no_mangle]
-pub extern "C" fn wry_window_close(win: *mut WryWindow) {
+pub extern "C" fn wry_window_set_background_color(
+    win: *mut WryWindow,
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+) {
     if win.is_null() {
         return;
     }
-    let win = unsafe { &mut *win };
-    // Trigger a close by destroying the webview and window
-    win.webview.take();
-    win.window.take();
+    let win = unsafe { &*win };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.set_background_color((r, g, b, a)), "set_background_color");
+    }
 }
 
-// ---------------------------------------------------------------------------
-// Window queries (post-run, via *mut WryWindow from callbacks)
-// ---------------------------------------------------------------------------
-
-/// Get the current window size in logical pixels.
+/// Set the color shown before the page paints and wherever it has no background of its own:
+/// WebView2's `DefaultBackgroundColor` on Windows (alpha is 0 or 255 only), the webview background
+/// elsewhere, plus the native window background. Call from a callback with the WryWindow pointer.
 #[no_mangle]
-pub extern "C" fn wry_window_get_size(
+pub extern "C" fn wry_window_set_default_background_color(
     win: *mut WryWindow,
-    width: *mut c_int,
-    height: *mut c_int,
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
 ) {
     if win.is_null() {
         return;
     }
     let win = unsafe { &*win };
     if let Some(ref w) = win.window {
-        let size = w.inner_size();
-        let scale = w.scale_factor();
-        let logical = size.to_logical::<i32>(scale);
-        if !width.is_null() {
-            unsafe { *width = logical.width };
-        }
-        if !height.is_null() {
-            unsafe { *height = logical.height };
-        }
+        w.set_background_color(Some((r, g, b, a)));
     }
+    let Some(ref wv) = win.webview else { return; };
+    #[cfg(target_os = "windows")]
+    log_err!(set_default_background_color(wv, (r, g, b, a)), "set default background color");
+    #[cfg(not(target_os = "windows"))]
+    log_err!(wv.set_background_color((r, g, b, a)), "set default background color");
+}
+
+/// Windows: WebView2's controller `DefaultBackgroundColor`. Alpha is 0 or 255 only; anything
+/// else counts as opaque.
+#[cfg(target_os = "windows")]
+fn set_default_background_color(wv: &WebView, (r, g, b, a): (u8, u8, u8, u8)) -> windows::core::Result<()> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{ICoreWebView2Controller2, COREWEBVIEW2_COLOR};
+    use windows::core::Interface;
+    use wry::WebViewExtWindows;
+    let color = COREWEBVIEW2_COLOR { A: if a == 0 { 0 } else { 255 }, R: r, G: g, B: b };
+    let controller: ICoreWebView2Controller2 = wv.controller().cast()?;
+    unsafe { controller.SetDefaultBackgroundColor(color) }
 }
 
-/// Get the current window position in logical pixels.
+/// Set the window icon from RGBA pixel data at runtime.
+/// Call from a callback or dispatch with the WryWindow pointer.
+/// Pass null / zero length / zero dimensions to clear the icon.
+///
+/// Platform: Windows and Linux only. macOS has no per-window icon.
 #[no_mangle]
-pub extern "C" fn wry_window_get_position(
+pub extern "C" fn wry_window_set_icon(
     win: *mut WryWindow,
-    x: *mut c_int,
-    y: *mut c_int,
+    rgba: *const u8,
+    rgba_len: c_int,
+    width: c_int,
+    height: c_int,
 ) {
     if win.is_null() {
         return;
     }
     let win = unsafe { &*win };
     if let Some(ref w) = win.window {
-        let pos = w.outer_position().unwrap_or_default();
-        let scale = w.scale_factor();
-        let logical = pos.to_logical::<i32>(scale);
-        if !x.is_null() {
-            unsafe { *x = logical.x };
+        if rgba.is_null() || rgba_len <= 0 || width <= 0 || height <= 0 {
+            w.set_window_icon(None);
+            return;
         }
-        if !y.is_null() {
-            unsafe { *y = logical.y };
+        let data = unsafe { std::slice::from_raw_parts(rgba, rgba_len as usize) }.to_vec();
+        match Icon::from_rgba(data, width as u32, height as u32) {
+            Ok(icon) => w.set_window_icon(Some(icon)),
+            Err(e) => eprintln!("[wry-native] wry_window_set_icon: {}", e),
         }
     }
 }
 
-/// Get the window title. Returns a pointer to a UTF-8 C string that the caller
-/// must free with `wry_string_free()`.
+/// Set the window icon from encoded image file bytes (PNG, ICO, JPEG, BMP, GIF, WebP, SVG) at runtime.
+/// Call from a callback or dispatch with the WryWindow pointer.
+/// Pass null or zero length to clear the icon.
+///
+/// Platform: Windows and Linux only. macOS has no per-window icon.
 #[no_mangle]
-pub extern "C" fn wry_window_get_title(win: *mut WryWindow) -> *mut c_char {
+pub extern "C" fn wry_window_set_icon_from_bytes(
+    win: *mut WryWindow,
+    data: *const u8,
+    data_len: c_int,
+) {
     if win.is_null() {
-        return std::ptr::null_mut();
+        return;
     }
     let win = unsafe { &*win };
-    let title = if let Some(ref w) = win.window {
-        w.title()
+    if let Some(ref w) = win.window {
+        if data.is_null() || data_len <= 0 {
+            w.set_window_icon(None);
+            return;
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(data, data_len as usize) };
+        if let Some(icon) = decode_icon_from_bytes(bytes) {
+            w.set_window_icon(Some(icon));
+        }
+    }
+}
+
+/// Index of the image to use for a `target` px icon: the smallest whose longer side is at least
+/// `target` (so it is only ever scaled down), else the largest. `sizes` must not be empty.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn pick_icon_image(sizes: &[(u32, u32)], target: u32) -> usize {
+    let edge = |i: usize| sizes[i].0.max(sizes[i].1);
+    (0..sizes.len())
+        .filter(|&i| edge(i) >= target)
+        .min_by_key(|&i| edge(i))
+        .or_else(|| (0..sizes.len()).max_by_key(|&i| edge(i)))
+        .unwrap_or(0)
+}
+
+/// Windows: HICON of `img` resized to `size` x `size` (Lanczos3 when scaling is needed).
+#[cfg(target_os = "windows")]
+fn create_hicon(img: &image::RgbaImage, size: u32) -> windows::core::Result<windows::Win32::UI::WindowsAndMessaging::HICON> {
+    use windows::Win32::UI::WindowsAndMessaging::CreateIcon;
+    let resized;
+    let img = if img.dimensions() == (size, size) {
+        img
     } else {
-        String::new()
+        resized = image::imageops::resize(img, size, size, image::imageops::FilterType::Lanczos3);
+        &resized
     };
-    CString::new(title)
-        .map(|cs| cs.into_raw())
-        .unwrap_or(std::ptr::null_mut())
+    // 32 bpp icons take transparency from the alpha channel; the AND mask stays all zero.
+    let bgra: Vec<u8> = img.pixels().flat_map(|p| [p[2], p[1], p[0], p[3]]).collect();
+    let mask_stride = size.div_ceil(16) as usize * 2;
+    let and_mask = vec![0u8; mask_stride * size as usize];
+    unsafe { CreateIcon(None, size as i32, size as i32, 1, 32, and_mask.as_ptr(), bgra.as_ptr()) }
 }
 
-/// Free a string returned by `wry_window_get_title` or `wry_window_get_url`.
+/// Set the window icon from several RGBA images of the same artwork at different sizes
+/// (e.g. 16, 24, 32, 48, 256), so each place the OS shows it gets a sharp version.
+///
+/// - `images`: array of `count` pointers to RGBA pixel data
+/// - `image_lens`: byte length of each image (must be at least width * height * 4)
+/// - `widths` / `heights`: pixel size of each image
+///
+/// Windows: builds separate HICONs for the small (title bar, SM_CXSMICON) and big (alt-tab,
+/// taskbar, SM_CXICON) icon slots at the window's DPI, each from the closest larger image, and
+/// installs them with WM_SETICON - a single-size icon would be scaled by the OS and look blurry.
+/// Linux: the largest image is used. macOS has no per-window icon.
+/// Invalid entries are skipped; returns false if none is usable. Pixel data is copied.
+/// Call from a callback or dispatch with the WryWindow pointer.
 #[no_mangle]
-pub extern "C" fn wry_string_free(s: *mut c_char) {
-    if !s.is_null() {
-        unsafe {
-            drop(CString::from_raw(s));
+pub extern "C" fn wry_window_set_icon_multi(
+    win: *mut WryWindow,
+    images: *const *const u8,
+    image_lens: *const c_int,
+    widths: *const c_int,
+    heights: *const c_int,
+    count: c_int,
+) -> bool {
+    if win.is_null() || images.is_null() || image_lens.is_null() || widths.is_null() || heights.is_null() || count <= 0 {
+        return false;
+    }
+    let win = unsafe { &mut *win };
+    let Some(ref w) = win.window else { return false; };
+    let n = count as usize;
+    let (ptrs, lens, ws, hs) = unsafe {
+        (
+            std::slice::from_raw_parts(images, n),
+            std::slice::from_raw_parts(image_lens, n),
+            std::slice::from_raw_parts(widths, n),
+            std::slice::from_raw_parts(heights, n),
+        )
+    };
+    let imgs: Vec<image::RgbaImage> = (0..n)
+        .filter(|&i| !ptrs[i].is_null() && ws[i] > 0 && hs[i] > 0)
+        .filter(|&i| lens[i] as i64 >= ws[i] as i64 * hs[i] as i64 * 4)
+        .filter_map(|i| {
+            let len = ws[i] as usize * hs[i] as usize * 4;
+            let data = unsafe { std::slice::from_raw_parts(ptrs[i], len) }.to_vec();
+            image::RgbaImage::from_raw(ws[i] as u32, hs[i] as u32, data)
+        })
+        .collect();
+    if imgs.is_empty() {
+        eprintln!("[wry-native] wry_window_set_icon_multi: no usable images");
+        return false;
+    }
+    let sizes: Vec<(u32, u32)> = imgs.iter().map(|img| img.dimensions()).collect();
+
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::WindowExtWindows;
+        use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+        use windows::Win32::UI::HiDpi::{GetDpiForWindow, GetSystemMetricsForDpi};
+        use windows::Win32::UI::WindowsAndMessaging::{
+            DestroyIcon, SendMessageW, ICON_BIG, ICON_SMALL, SM_CXICON, SM_CXSMICON, WM_SETICON,
+        };
+        let hwnd = HWND(w.hwnd() as _);
+        let dpi = unsafe { GetDpiForWindow(hwnd) }.max(96);
+        let mut installed = Vec::new();
+        for (slot, metric) in [(ICON_SMALL, SM_CXSMICON), (ICON_BIG, SM_CXICON)] {
+            let target = unsafe { GetSystemMetricsForDpi(metric, dpi) }.max(1) as u32;
+            let img = &imgs[pick_icon_image(&sizes, target)];
+            match create_hicon(img, target) {
+                Ok(icon) => {
+                    unsafe { SendMessageW(hwnd, WM_SETICON, Some(WPARAM(slot as usize)), Some(LPARAM(icon.0 as isize))) };
+                    installed.push(icon);
+                }
+                Err(e) => eprintln!("[wry-native] wry_window_set_icon_multi: CreateIcon failed: {}", e),
+            }
+        }
+        if installed.is_empty() {
+            return false;
+        }
+        for old in std::mem::replace(&mut win.multi_icons, installed) {
+            log_err!(unsafe { DestroyIcon(old) }, "set_icon_multi DestroyIcon");
+        }
+        true
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let img = &imgs[pick_icon_image(&sizes, u32::MAX)];
+        let (width, height) = img.dimensions();
+        match Icon::from_rgba(img.as_raw().clone(), width, height) {
+            Ok(icon) => {
+                w.set_window_icon(Some(icon));
+                true
+            }
+            Err(e) => {
+                eprintln!("[wry-native] wry_window_set_icon_multi: {}", e);
+                false
+            }
         }
     }
 }
 
-/// Get whether the window is resizable.
+/// Set the window icon from SVG (or SVGZ) bytes, rasterized to a `size_px` x `size_px` RGBA icon
+/// (aspect ratio kept, centered). Pick the largest size the OS shows, e.g. 256; it scales down
+/// for title bars. Text and embedded raster images in the SVG are not rendered - convert text to
+/// paths. Call from a callback or dispatch with the WryWindow pointer. Returns false if the SVG
+/// could not be parsed or `size_px` is not positive.
+///
+/// Platform: Windows and Linux only. macOS has no per-window icon.
 #[no_mangle]
-pub extern "C" fn wry_window_get_resizable(win: *mut WryWindow) -> bool {
-    if win.is_null() {
+pub extern "C" fn wry_window_set_icon_from_svg(
+    win: *mut WryWindow,
+    svg: *const u8,
+    svg_len: c_int,
+    size_px: c_int,
+) -> bool {
+    if win.is_null() || svg.is_null() || svg_len <= 0 || size_px <= 0 {
         return false;
     }
     let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        w.is_resizable()
-    } else {
-        false
+    let Some(ref w) = win.window else { return false; };
+    let bytes = unsafe { std::slice::from_raw_parts(svg, svg_len as usize) };
+    let icon = rasterize_svg(bytes, size_px as u32)
+        .and_then(|(rgba, width, height)| Icon::from_rgba(rgba, width, height).map_err(|e| e.to_string()));
+    match icon {
+        Ok(icon) => {
+            w.set_window_icon(Some(icon));
+            true
+        }
+        Err(e) => {
+            eprintln!("[wry-native] wry_window_set_icon_from_svg: {}", e);
+            false
+        }
     }
 }
 
-/// Get whether the window is fullscreen.
+/// Open the web inspector (dev tools).
+/// Call from a callback with the WryWindow pointer.
+///
+/// Platform: Android / iOS not supported.
 #[no_mangle]
-pub extern "C" fn wry_window_get_fullscreen(win: *mut WryWindow) -> bool {
+pub extern "C" fn wry_window_open_devtools(win: *mut WryWindow) {
     if win.is_null() {
-        return false;
+        return;
     }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        w.fullscreen().is_some()
-    } else {
-        false
+    let _win = unsafe { &*win };
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    if let Some(ref wv) = _win.webview {
+        wv.open_devtools();
     }
 }
 
-/// Get whether the window is maximized.
+/// Close the web inspector (dev tools).
+/// Call from a callback with the WryWindow pointer.
+///
+/// Platform: Windows / Android / iOS not supported.
 #[no_mangle]
-pub extern "C" fn wry_window_get_maximized(win: *mut WryWindow) -> bool {
+pub extern "C" fn wry_window_close_devtools(win: *mut WryWindow) {
     if win.is_null() {
-        return false;
+        return;
     }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        w.is_maximized()
-    } else {
-        false
+    let _win = unsafe { &*win };
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    if let Some(ref wv) = _win.webview {
+        wv.close_devtools();
     }
 }
 
-/// Get whether the window is minimized.
+/// Check if the web inspector (dev tools) is open.
+/// Call from a callback with the WryWindow pointer.
+/// Returns false if the webview is not created or devtools feature is disabled.
+///
+/// Platform: Windows / Android / iOS not supported.
 #[no_mangle]
-pub extern "C" fn wry_window_get_minimized(win: *mut WryWindow) -> bool {
+pub extern "C" fn wry_window_is_devtools_open(win: *mut WryWindow) -> bool {
     if win.is_null() {
         return false;
     }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        w.is_minimized()
-    } else {
-        false
+    let _win = unsafe { &*win };
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    if let Some(ref wv) = _win.webview {
+        return wv.is_devtools_open();
     }
+    false
 }
 
-/// Get whether the window is visible.
+/// Get the WebView/WebKit engine version on the current platform.
+/// Returns a pointer to a UTF-8 C string that the caller must free with
+/// `wry_string_free()`. Returns null on failure.
 #[no_mangle]
-pub extern "C" fn wry_window_get_visible(win: *mut WryWindow) -> bool {
-    if win.is_null() {
-        return false;
-    }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        w.is_visible()
-    } else {
-        false
+pub extern "C" fn wry_webview_version() -> *mut c_char {
+    match webview_version() {
+        Ok(version) => CString::new(version)
+            .map(|cs| cs.into_raw())
+            .unwrap_or(std::ptr::null_mut()),
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Get whether the window has decorations (title bar, borders).
+/// Get the engine version actually serving this window's webview. On Windows this is the
+/// BrowserVersionString of the window's CoreWebView2Environment, so it differs from
+/// `wry_webview_version` when a fixed-version runtime folder is in use. macOS and Linux have a
+/// single system engine per process; this returns the same value as `wry_webview_version`.
+/// Returns a UTF-8 C string the caller must free with `wry_string_free()`, or null if the
+/// window has no webview or the query fails.
 #[no_mangle]
-pub extern "C" fn wry_window_get_decorated(win: *mut WryWindow) -> bool {
+pub extern "C" fn wry_window_get_webview_version(win: *mut WryWindow) -> *mut c_char {
     if win.is_null() {
-        return true;
+        return std::ptr::null_mut();
     }
     let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        w.is_decorated()
-    } else {
-        true
+    let Some(ref _wv) = win.webview else { return std::ptr::null_mut(); };
+    #[cfg(target_os = "windows")]
+    let version = {
+        use webview2_com::take_pwstr;
+        use windows::core::PWSTR;
+        use wry::WebViewExtWindows;
+        let mut version = PWSTR::null();
+        unsafe { _wv.environment().BrowserVersionString(&mut version) }.map(|()| take_pwstr(version))
+    };
+    #[cfg(not(target_os = "windows"))]
+    let version = webview_version();
+    match version {
+        Ok(version) => CString::new(version)
+            .map(|cs| cs.into_raw())
+            .unwrap_or(std::ptr::null_mut()),
+        Err(e) => {
+            eprintln!("[wry-native] get webview version failed: {}", e);
+            std::ptr::null_mut()
+        }
     }
 }
 
-/// Get current window theme. Returns 0 = auto/unknown, 1 = dark, 2 = light.
-/// Call from a callback with the WryWindow pointer.
+/// Report the memory used by the window's web engine processes to `callback` (called once,
+/// before this returns; -1 if it cannot be measured). Call from a callback with the WryWindow
+/// pointer. Engine processes are shared between windows using the same data directory / context,
+/// so the figure covers all of them; use it as a trend for deciding when to recycle.
+///
+/// - Windows: working sets of the WebView2 browser, renderer, GPU and utility processes
+///   (`ICoreWebView2Environment8.GetProcessInfos`, runtime 1.0.1185+).
+/// - Linux: resident sets of the WebKitGTK web, network and sandbox processes (descendants of
+///   this process named like ENGINE_PROCESS_NAMES, from `/proc`).
+/// - macOS: not available (WebKit's XPC processes cannot be queried with public API); -1.
 #[no_mangle]
-pub extern "C" fn wry_window_get_theme(win: *mut WryWindow) -> c_int {
-    if win.is_null() {
-        return 0;
+pub extern "C" fn wry_window_get_memory_usage(win: *mut WryWindow, callback: MemoryUsageCallback, ctx: *mut c_void) {
+    let wv = if win.is_null() { None } else { unsafe { (*win).webview.as_ref() } };
+    let bytes = match wv.map(engine_memory_usage) {
+        Some(result) => {
+            log_err!(result.as_ref(), "get memory usage");
+            result.map_or(-1, |bytes| bytes as i64)
+        }
+        None => -1,
+    };
+    callback(bytes, ctx);
+}
+
+#[cfg(target_os = "windows")]
+fn engine_memory_usage(wv: &WebView) -> Result<u64, String> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Environment8;
+    use windows::core::Interface;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+    use wry::WebViewExtWindows;
+
+    unsafe {
+        let infos = wv
+            .environment()
+            .cast::<ICoreWebView2Environment8>()
+            .and_then(|env| env.GetProcessInfos())
+            .map_err(|e| e.to_string())?;
+        let mut count = 0u32;
+        infos.Count(&mut count).map_err(|e| e.to_string())?;
+        let mut total = 0u64;
+        for i in 0..count {
+            let Ok(info) = infos.GetValueAtIndex(i) else { continue };
+            let mut pid = 0i32;
+            if info.ProcessId(&mut pid).is_err() {
+                continue;
+            }
+            // A process may exit between listing and opening; skip it.
+            let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid as u32) else {
+                continue;
+            };
+            let mut counters = PROCESS_MEMORY_COUNTERS {
+                cb: std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+                ..Default::default()
+            };
+            if GetProcessMemoryInfo(process, &mut counters, counters.cb).is_ok() {
+                total += counters.WorkingSetSize as u64;
+            }
+            let _ = CloseHandle(process);
+        }
+        Ok(total)
     }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        match w.theme() {
-            Theme::Dark => 1,
-            Theme::Light => 2,
-            _ => 0,
+}
+
+#[cfg(target_os = "linux")]
+fn engine_memory_usage(_wv: &WebView) -> Result<u64, String> {
+    let entries = std::fs::read_dir("/proc").map_err(|e| e.to_string())?;
+    let parents: Vec<(u32, u32)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+            Some((pid, stat_parent_pid(&stat)?))
+        })
+        .collect();
+    let total = descendant_pids(std::process::id(), &parents)
+        .into_iter()
+        .filter(|pid| {
+            std::fs::read_to_string(format!("/proc/{}/comm", pid)).is_ok_and(|comm| is_engine_process(&comm))
+        })
+        .filter_map(|pid| std::fs::read_to_string(format!("/proc/{}/status", pid)).ok())
+        .filter_map(|status| status_resident_bytes(&status))
+        .sum();
+    Ok(total)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn engine_memory_usage(_wv: &WebView) -> Result<u64, String> {
+    Err("not supported on this platform".into())
+}
+
+/// Linux: WebKitGTK's web and network processes and the sandbox helpers that host them. Other
+/// children of the host process (its own tools, shells) are not counted.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const ENGINE_PROCESS_NAMES: [&str; 4] = ["WebKitWebProcess", "WebKitNetworkProcess", "bwrap", "xdg-dbus-proxy"];
+
+/// Whether `/proc/<pid>/comm` names a web engine process. The kernel truncates names to 15 bytes.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_engine_process(comm: &str) -> bool {
+    let comm = comm.trim_end();
+    ENGINE_PROCESS_NAMES.iter().any(|name| &name[..name.len().min(15)] == comm)
+}
+
+/// Parent PID from a `/proc/<pid>/stat` line. The command name may contain spaces and
+/// parentheses, so fields are read after its last ')'.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn stat_parent_pid(stat: &str) -> Option<u32> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    // Fields after the name: state, ppid, ...
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Resident set size in bytes from `/proc/<pid>/status` (the `VmRSS:` line, in kB).
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn status_resident_bytes(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line["VmRSS:".len()..].split_whitespace().next()?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// All transitive children of `root` given (pid, parent pid) pairs, excluding `root` itself.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn descendant_pids(root: u32, parents: &[(u32, u32)]) -> Vec<u32> {
+    let mut found = Vec::new();
+    let mut pending = vec![root];
+    while let Some(parent) = pending.pop() {
+        for &(pid, ppid) in parents {
+            if ppid == parent && pid != root && !found.contains(&pid) {
+                found.push(pid);
+                pending.push(pid);
+            }
         }
-    } else {
-        0
     }
+    found
 }
 
-/// Get the DPI scale factor for the window's current monitor.
-/// Returns 1.0 as default if the window hasn't been created yet.
+/// ABI revision of the exported C API. Bump whenever an exported signature or a `#[repr(C)]`
+/// struct layout changes incompatibly; the managed binding refuses to load a mismatch.
+pub const WRY_NATIVE_ABI_VERSION: c_int = 2;
+
+/// Get the version of this native library (the wry-native crate version, e.g. "0.1.0").
+/// Returns a UTF-8 C string that the caller must free with `wry_string_free()`.
 #[no_mangle]
-pub extern "C" fn wry_window_get_screen_dpi(win: *mut WryWindow) -> f64 {
-    if win.is_null() {
-        return 1.0;
-    }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        w.scale_factor()
-    } else {
-        1.0
-    }
+pub extern "C" fn wry_native_version() -> *mut c_char {
+    CString::new(env!("CARGO_PKG_VERSION"))
+        .map(|cs| cs.into_raw())
+        .unwrap_or(std::ptr::null_mut())
 }
 
-/// Get the current URL loaded in the webview. Returns a pointer to a UTF-8
-/// C string that the caller must free with `wry_string_free()`.
-/// Returns null if the webview is not yet created.
+/// Get the ABI revision of this native library (WRY_NATIVE_ABI_VERSION). Bindings compare it
+/// against the revision they were generated for before calling anything else.
 #[no_mangle]
-pub extern "C" fn wry_window_get_url(win: *mut WryWindow) -> *mut c_char {
-    if win.is_null() {
-        return std::ptr::null_mut();
+pub extern "C" fn wry_native_abi_version() -> c_int {
+    WRY_NATIVE_ABI_VERSION
+}
+
+// Capability bits reported by wry_get_capabilities.
+const CAP_DEVTOOLS: u32 = 1 << 0;
+const CAP_TRANSPARENCY: u32 = 1 << 1;
+const CAP_INCOGNITO: u32 = 1 << 2;
+const CAP_VIBRANCY: u32 = 1 << 3;
+const CAP_SYSTEM_BACKDROP: u32 = 1 << 4;
+const CAP_TITLE_BAR_COLOR: u32 = 1 << 5;
+const CAP_SCRIPT_DIALOGS: u32 = 1 << 6;
+const CAP_POWER_EVENTS: u32 = 1 << 7;
+const CAP_LOW_POWER_EVENT: u32 = 1 << 8;
+const CAP_IDLE_TIME: u32 = 1 << 9;
+const CAP_KEEP_AWAKE: u32 = 1 << 10;
+const CAP_DOCK_MENU: u32 = 1 << 11;
+const CAP_TASKBAR: u32 = 1 << 12;
+const CAP_WINDOW_ICON: u32 = 1 << 13;
+const CAP_ASPECT_RATIO: u32 = 1 << 14;
+
+/// Windows: the OS build number (e.g. 22631), via RtlGetVersion since GetVersionEx is
+/// manifest-dependent. 0 if unknown.
+#[cfg(target_os = "windows")]
+fn windows_build_number() -> u32 {
+    use windows::Win32::System::SystemInformation::OSVERSIONINFOW;
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn RtlGetVersion(info: *mut OSVERSIONINFOW) -> i32;
+    }
+    let mut info = OSVERSIONINFOW { dwOSVersionInfoSize: std::mem::size_of::<OSVERSIONINFOW>() as u32, ..Default::default() };
+    if unsafe { RtlGetVersion(&mut info) } == 0 { info.dwBuildNumber } else { 0 }
+}
+
+/// Capability bits for this build on this machine: compile-time platform and features, plus
+/// runtime checks where support depends on the OS version or session.
+fn capabilities() -> u32 {
+    let mut caps = 0;
+    if cfg!(any(debug_assertions, feature = "devtools")) {
+        caps |= CAP_DEVTOOLS;
     }
-    let win = unsafe { &*win };
-    if let Some(ref wv) = win.webview {
-        if let Ok(url) = wv.url() {
-            return CString::new(url)
-                .map(|cs| cs.into_raw())
-                .unwrap_or(std::ptr::null_mut());
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    {
+        caps |= CAP_INCOGNITO | CAP_POWER_EVENTS | CAP_KEEP_AWAKE;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        caps |= CAP_TRANSPARENCY | CAP_SCRIPT_DIALOGS | CAP_LOW_POWER_EVENT | CAP_IDLE_TIME;
+        caps |= CAP_TASKBAR | CAP_WINDOW_ICON | CAP_ASPECT_RATIO;
+        let build = windows_build_number();
+        if build >= 22000 {
+            caps |= CAP_TITLE_BAR_COLOR; // Windows 11
+        }
+        if build >= 22621 {
+            caps |= CAP_SYSTEM_BACKDROP; // Windows 11 22H2
         }
     }
-    std::ptr::null_mut()
+    #[cfg(target_os = "macos")]
+    {
+        caps |= CAP_VIBRANCY | CAP_LOW_POWER_EVENT | CAP_IDLE_TIME | CAP_DOCK_MENU | CAP_ASPECT_RATIO;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        caps |= CAP_TRANSPARENCY | CAP_SCRIPT_DIALOGS | CAP_WINDOW_ICON;
+        // XScreenSaver is only there on X11 sessions with libXss installed.
+        if idle_time_ms().is_some() {
+            caps |= CAP_IDLE_TIME;
+        }
+    }
+    caps
 }
 
-// ---------------------------------------------------------------------------
-// Post-run window property setters (via *mut WryWindow from callbacks)
-// ---------------------------------------------------------------------------
-
-/// Set the window title. Call from a callback with the WryWindow pointer.
+/// Report which optional features work in this build on this machine, as a bitmask, so hosts
+/// can hide UI for functions that would be no-ops here:
+/// 1 = DevTools (debug builds or the `devtools` feature), 2 = Transparency (`transparent`
+/// window config; not macOS), 4 = Incognito, 8 = Vibrancy (macOS), 16 = SystemBackdrop (Windows 11
+/// 22H2+), 32 = TitleBarColor / BorderColor (Windows 11), 64 = ScriptDialogs (page dialog
+/// interception), 128 = PowerEvents, 256 = LowPower power event (Windows, macOS),
+/// 512 = IdleTime (`wry_app_get_idle_time`; Linux only on X11), 1024 = KeepAwake,
+/// 2048 = DockMenu (macOS), 4096 = Taskbar integration (overlay, thumbnail toolbar, app ID;
+/// Windows), 8192 = WindowIcon (per-window icons; not macOS), 16384 = AspectRatio (not Linux).
 #[no_mangle]
-pub extern "C" fn wry_window_set_title(win: *mut WryWindow, title: *const c_char) {
-    if win.is_null() {
-        return;
+pub extern "C" fn wry_get_capabilities() -> u32 {
+    capabilities()
+}
+
+/// Milliseconds since the last keyboard / mouse input anywhere in the user session, or None
+/// when the platform cannot tell.
+fn idle_time_ms() -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use windows::Win32::System::SystemInformation::GetTickCount;
+        use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+        let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // Both are 32-bit tick counts; wrapping_sub stays correct across the 49.7-day rollover.
+        Some(GetTickCount().wrapping_sub(info.dwTime) as u64)
     }
-    let win = unsafe { &mut *win };
-    let title = unsafe { c_str_to_string(title) };
-    if let Some(ref w) = win.window {
-        w.set_title(&title);
+    #[cfg(target_os = "macos")]
+    {
+        #[link(name = "CoreGraphics", kind = "framework")]
+        extern "C" {
+            fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+        }
+        const COMBINED_SESSION_STATE: i32 = 0;
+        const ANY_INPUT_EVENT_TYPE: u32 = !0;
+        let secs = unsafe { CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT_TYPE) };
+        secs.is_finite().then(|| (secs.max(0.0) * 1000.0) as u64)
+    }
+    #[cfg(target_os = "linux")]
+    unsafe {
+        // XScreenSaver extension, loaded at runtime; not available on Wayland-only sessions.
+        let xlib = x11_dl::xlib::Xlib::open().ok()?;
+        let xss = x11_dl::xss::Xss::open().ok()?;
+        let display = (xlib.XOpenDisplay)(std::ptr::null());
+        if display.is_null() {
+            return None;
+        }
+        let info = (xss.XScreenSaverAllocInfo)();
+        let root = (xlib.XDefaultRootWindow)(display);
+        let idle = if !info.is_null() && (xss.XScreenSaverQueryInfo)(display, root, info) != 0 {
+            Some((*info).idle as u64)
+        } else {
+            None
+        };
+        if !info.is_null() {
+            (xlib.XFree)(info as *mut c_void);
+        }
+        (xlib.XCloseDisplay)(display);
+        idle
     }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    None
 }
 
-/// Navigate to a URL. Call from a callback with the WryWindow pointer.
+/// Milliseconds since the last user input (keyboard / mouse) system-wide, for inactivity
+/// timeouts and attract loops. Returns -1 when unavailable.
+/// Platform: Windows (GetLastInputInfo), macOS (CGEventSourceSecondsSinceLastEventType),
+/// Linux X11 (XScreenSaver extension; -1 on Wayland or without libXss).
 #[no_mangle]
-pub extern "C" fn wry_window_load_url(win: *mut WryWindow, url: *const c_char) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &mut *win };
-    let url = unsafe { c_str_to_string(url) };
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.load_url(&url), "load_url");
-    }
+pub extern "C" fn wry_app_get_idle_time() -> i64 {
+    idle_time_ms().map_or(-1, |ms| ms as i64)
+}
+
+/// List the encoded image formats accepted by the icon functions (`icon_data` in the window
+/// config, `wry_window_set_icon_from_bytes`, the tray icon setters), as a comma-separated,
+/// lowercase string such as "png,jpeg,gif,webp,bmp,ico,svg". Reflects what is compiled into this
+/// build. Returns a UTF-8 C string the caller must free with `wry_string_free()`.
+#[no_mangle]
+pub extern "C" fn wry_supported_image_formats() -> *mut c_char {
+    CString::new(supported_image_formats())
+        .map(|cs| cs.into_raw())
+        .unwrap_or(std::ptr::null_mut())
 }
 
-/// Load HTML content. Call from a callback with the WryWindow pointer.
+// ---------------------------------------------------------------------------
+// WebView2 native handles (Windows only)
+// ---------------------------------------------------------------------------
+// ---------------------------------------------------------------------------
+// Native file drag (drag source)
+// ---------------------------------------------------------------------------
+
+/// Drag preview image: a small translucent tile, PNG-encoded.
+fn drag_preview_png() -> Vec<u8> {
+    let tile = image::RgbaImage::from_pixel(32, 32, image::Rgba([128, 128, 128, 160]));
+    let mut out = std::io::Cursor::new(Vec::new());
+    log_err!(tile.write_to(&mut out, image::ImageFormat::Png), "drag preview encode");
+    out.into_inner()
+}
+
+/// Start an OS drag-and-drop with the given files as payload, so they can be dropped on the
+/// desktop or other apps. `paths` is an array of `path_count` UTF-8 paths; null entries are skipped.
+/// Must be called on the event loop thread while the left mouse button is held down, e.g. from a
+/// dispatch posted by the page's `mousedown`/`dragstart`. Returns false if no drag was started.
+/// Platform: Windows (DoDragDrop), macOS (NSDraggingSession), Linux (GTK drag source), via the drag crate.
 #[no_mangle]
-pub extern "C" fn wry_window_load_html(win: *mut WryWindow, html: *const c_char) {
-    if win.is_null() {
-        return;
+pub extern "C" fn wry_window_begin_file_drag(
+    win: *mut WryWindow,
+    paths: *const *const c_char,
+    path_count: c_int,
+) -> bool {
+    if win.is_null() || paths.is_null() || path_count <= 0 {
+        return false;
     }
-    let win = unsafe { &mut *win };
-    let html = unsafe { c_str_to_string(html) };
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.load_html(&html), "load_html");
+    let win = unsafe { &*win };
+    let files: Vec<std::path::PathBuf> = (0..path_count as usize)
+        .filter_map(|i| {
+            let p = unsafe { *paths.add(i) };
+            if p.is_null() {
+                None
+            } else {
+                Some(std::path::PathBuf::from(unsafe { c_str_to_string(p) }))
+            }
+        })
+        .collect();
+    if files.is_empty() {
+        return false;
+    }
+    let Some(ref w) = win.window else { return false; };
+    let item = drag::DragItem::Files(files);
+    let image = drag::Image::Raw(drag_preview_png());
+    #[cfg(target_os = "linux")]
+    let result = {
+        use tao::platform::unix::WindowExtUnix;
+        drag::start_drag(w.gtk_window(), item, image, |_, _| {}, drag::Options::default())
+    };
+    #[cfg(not(target_os = "linux"))]
+    let result = drag::start_drag(w, item, image, |_, _| {}, drag::Options::default());
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("[wry-native] begin file drag failed: {}", e);
+            false
+        }
     }
 }
 
-/// Set window size. Call from a callback with the WryWindow pointer.
+// ---------------------------------------------------------------------------
+// Windows native window handles (HWND, HINSTANCE)
+// ---------------------------------------------------------------------------
+
+/// Return the window's HWND. Windows only; returns null on other platforms.
+/// The handle is valid until the window is destroyed.
 #[no_mangle]
-pub extern "C" fn wry_window_set_size(
-    win: *mut WryWindow,
-    width: c_int,
-    height: c_int,
-) {
+pub extern "C" fn wry_window_get_hwnd(win: *mut WryWindow) -> *mut c_void {
     if win.is_null() {
-        return;
+        return std::ptr::null_mut();
     }
-    let win = unsafe { &mut *win };
-    let w = width.max(1) as u32;
-    let h = height.max(1) as u32;
-    if let Some(ref window) = win.window {
-        window.set_inner_size(LogicalSize::new(w, h));
+    let win = unsafe { &*win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        use tao::platform::windows::WindowExtWindows;
+        return w.hwnd() as *mut c_void;
     }
+    std::ptr::null_mut()
 }
 
-/// Set window position. Call from a callback with the WryWindow pointer.
+/// Return the window's HINSTANCE (module instance handle). Windows only; returns null on other platforms.
 #[no_mangle]
-pub extern "C" fn wry_window_set_position(
-    win: *mut WryWindow,
-    x: c_int,
-    y: c_int,
-) {
+pub extern "C" fn wry_window_get_hinstance(win: *mut WryWindow) -> *mut c_void {
     if win.is_null() {
-        return;
+        return std::ptr::null_mut();
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref window) = win.window {
-        window.set_outer_position(LogicalPosition::new(x, y));
+    let win = unsafe { &*win };
+    #[cfg(target_os = "windows")]
+    if let Some(ref w) = win.window {
+        use tao::platform::windows::WindowExtWindows;
+        let hwnd = w.hwnd();
+        let ptr = unsafe {
+            windows::Win32::UI::WindowsAndMessaging::GetWindowLongPtrW(
+                windows::Win32::Foundation::HWND(hwnd as _),
+                windows::Win32::UI::WindowsAndMessaging::GWLP_HINSTANCE,
+            )
+        };
+        return ptr as *mut c_void;
     }
+    std::ptr::null_mut()
 }
 
-/// Set minimum window inner size. Pass width 0 or height 0 to clear the constraint.
-/// Call from a callback with the WryWindow pointer.
+// ---------------------------------------------------------------------------
+// Return raw COM interface pointers (ICoreWebView2Controller, ICoreWebView2Environment,
+// ICoreWebView2). The caller (e.g. C#) may use them with the WebView2 SDK. We clone the
+// interface so the returned pointer has its own ref; the caller must not release it
+// until they are done (or they may wrap with Marshal.GetObjectForIUnknown which AddRefs).
+
+/// Return the WebView2 controller (ICoreWebView2Controller) as a raw pointer.
+/// Windows only; returns null on other platforms or if the webview is not created.
+/// The pointer is valid until the window is destroyed. Caller must not release the COM reference.
 #[no_mangle]
-pub extern "C" fn wry_window_set_min_size(win: *mut WryWindow, width: c_int, height: c_int) {
+pub extern "C" fn wry_window_get_webview2_controller(win: *mut WryWindow) -> *mut c_void {
     if win.is_null() {
-        return;
+        return std::ptr::null_mut();
     }
     let win = unsafe { &*win };
-    if let Some(ref window) = win.window {
-        if width <= 0 || height <= 0 {
-            window.set_min_inner_size::<LogicalSize<u32>>(None);
-        } else {
-            window.set_min_inner_size(Some(LogicalSize::new(width as u32, height as u32)));
-        }
+    #[cfg(target_os = "windows")]
+    if let Some(ref wv) = win.webview {
+        use wry::WebViewExtWindows;
+        let c = wv.controller();
+        let ptr = unsafe { std::mem::transmute_copy::<_, *mut c_void>(&c) };
+        std::mem::forget(c);
+        return ptr;
     }
+    std::ptr::null_mut()
 }
 
-/// Set maximum window inner size. Pass width 0 or height 0 to clear the constraint.
-/// Call from a callback with the WryWindow pointer.
+/// Return the WebView2 environment (ICoreWebView2Environment) as a raw pointer.
+/// Windows only; returns null on other platforms or if the webview is not created.
 #[no_mangle]
-pub extern "C" fn wry_window_set_max_size(win: *mut WryWindow, width: c_int, height: c_int) {
+pub extern "C" fn wry_window_get_webview2_environment(win: *mut WryWindow) -> *mut c_void {
     if win.is_null() {
-        return;
+        return std::ptr::null_mut();
     }
     let win = unsafe { &*win };
-    if let Some(ref window) = win.window {
-        if width <= 0 || height <= 0 {
-            window.set_max_inner_size::<LogicalSize<u32>>(None);
-        } else {
-            window.set_max_inner_size(Some(LogicalSize::new(width as u32, height as u32)));
-        }
+    #[cfg(target_os = "windows")]
+    if let Some(ref wv) = win.webview {
+        use wry::WebViewExtWindows;
+        let e = wv.environment();
+        let ptr = unsafe { std::mem::transmute_copy::<_, *mut c_void>(&e) };
+        std::mem::forget(e);
+        return ptr;
     }
+    std::ptr::null_mut()
 }
 
-/// Set window theme. theme: 0 = auto/system, 1 = dark, 2 = light.
-/// Call from a callback with the WryWindow pointer.
-/// Platform: Windows, Linux, macOS (behavior may be app-wide on some platforms).
+/// Return the WebView2 webview (ICoreWebView2) as a raw pointer.
+/// Windows only; returns null on other platforms or if the webview is not created.
+/// Use this e.g. for CapturePreview (screenshot) or other WebView2 APIs.
 #[no_mangle]
-pub extern "C" fn wry_window_set_theme(win: *mut WryWindow, theme: c_int) {
+pub extern "C" fn wry_window_get_webview2_webview(win: *mut WryWindow) -> *mut c_void {
     if win.is_null() {
-        return;
+        return std::ptr::null_mut();
     }
     let win = unsafe { &*win };
-    if let Some(ref window) = win.window {
-        let t = match theme {
-            1 => Some(Theme::Dark),
-            2 => Some(Theme::Light),
-            _ => None,
-        };
-        window.set_theme(t);
+    #[cfg(target_os = "windows")]
+    if let Some(ref wv) = win.webview {
+        use wry::WebViewExtWindows;
+        let w = wv.webview();
+        let ptr = unsafe { std::mem::transmute_copy::<_, *mut c_void>(&w) };
+        std::mem::forget(w);
+        return ptr;
     }
+    std::ptr::null_mut()
 }
 
-/// Set window decorations. Call from a callback with the WryWindow pointer.
+// ---------------------------------------------------------------------------
+// Cross-thread dispatch
+// ---------------------------------------------------------------------------
+
+/// Dispatch a callback to run on the event loop (main) thread. This is safe
+/// to call from any thread. The callback will receive the WryWindow pointer
+/// and the context pointer.
+///
+/// `app` is the application handle. `window_id` is the window's numeric ID
+/// returned by `wry_window_create`.
 #[no_mangle]
-pub extern "C" fn wry_window_set_decorations(win: *mut WryWindow, decorations: bool) {
-    if win.is_null() {
+pub extern "C" fn wry_window_dispatch(
+    app: *mut WryApp,
+    window_id: usize,
+    callback: DispatchCallback,
+    ctx: *mut c_void,
+) {
+    if app.is_null() {
         return;
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_decorations(decorations);
-    }
+    let app = unsafe { &*app };
+    log_err!(app.proxy.send_event(UserEvent::Dispatch {
+        window_id,
+        callback,
+        ctx: ctx as usize,
+        done: None,
+    }), "dispatch");
 }
 
-/// Set skip taskbar. Call from a callback with the WryWindow pointer. Platform: Windows, Linux.
+/// Like `wry_window_dispatch`, but block the calling thread until the callback has run on the
+/// event loop thread or `timeout_ms` elapses (< 0 = no limit), e.g. to read window state back.
+/// Returns true if the callback ran; false if the window does not exist (or closed first), the
+/// timeout elapsed, or the call was made on the event loop thread, where it would deadlock.
+///
+/// After a timeout the callback may still run later, so `ctx` must stay valid until it does or
+/// the window is gone.
 #[no_mangle]
-pub extern "C" fn wry_window_set_skip_taskbar(win: *mut WryWindow, skip: bool) {
-    if win.is_null() {
-        return;
+pub extern "C" fn wry_window_dispatch_sync(
+    app: *mut WryApp,
+    window_id: usize,
+    callback: DispatchCallback,
+    ctx: *mut c_void,
+    timeout_ms: c_int,
+) -> bool {
+    if app.is_null() {
+        return false;
     }
-    let win = unsafe { &mut *win };
-    #[cfg(any(target_os = "windows", target_os = "linux"))]
-    if let Some(ref w) = win.window {
-        #[cfg(target_os = "windows")]
-        {
-            use tao::platform::windows::WindowExtWindows;
-            let _ = w.set_skip_taskbar(skip);
-        }
-        #[cfg(target_os = "linux")]
-        {
-            use tao::platform::unix::WindowExtUnix;
-            let _ = w.set_skip_taskbar(skip);
-        }
+    let app = unsafe { &*app };
+    if std::thread::current().id() == app.loop_thread {
+        eprintln!("[wry-native] dispatch_sync: called on the event loop thread; use wry_window_dispatch");
+        return false;
+    }
+    let (done, rx) = std::sync::mpsc::channel();
+    let event = UserEvent::Dispatch { window_id, callback, ctx: ctx as usize, done: Some(done) };
+    if let Err(e) = app.proxy.send_event(event) {
+        eprintln!("[wry-native] dispatch_sync failed: {}", e);
+        return false;
+    }
+    if timeout_ms < 0 {
+        rx.recv().is_ok()
+    } else {
+        rx.recv_timeout(Duration::from_millis(timeout_ms as u64)).is_ok()
     }
 }
 
-/// Set content protection. Call from a callback with the WryWindow pointer.
+/// Dispatch a callback to run on the event loop (main) thread without targeting a
+/// window or tray. Safe to call from any thread, including before `wry_app_run`
+/// (the callback then runs once the loop has started). The callback receives the
+/// app handle and the context pointer.
 #[no_mangle]
-pub extern "C" fn wry_window_set_content_protected(win: *mut WryWindow, protected: bool) {
-    if win.is_null() {
+pub extern "C" fn wry_app_dispatch(
+    app: *mut WryApp,
+    callback: AppDispatchCallback,
+    ctx: *mut c_void,
+) {
+    if app.is_null() {
         return;
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_content_protection(protected);
+    let app = unsafe { &*app };
+    log_err!(app.proxy.send_event(UserEvent::AppDispatch {
+        callback,
+        ctx: ctx as usize,
+    }), "app dispatch");
+}
+
+// ---------------------------------------------------------------------------
+// Cookies
+// ---------------------------------------------------------------------------
+
+#[derive(serde::Serialize)]
+struct CookieJson {
+    name: String,
+    value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    secure: bool,
+    http_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<f64>,
+}
+
+fn cookie_to_json(c: &wry::cookie::Cookie<'_>) -> CookieJson {
+    let expires = c.expires().and_then(|exp| match exp {
+        wry::cookie::Expiration::Session => None,
+        wry::cookie::Expiration::DateTime(dt) => Some(dt.unix_timestamp() as f64),
+    });
+    CookieJson {
+        name: c.name().to_owned(),
+        value: c.value().to_owned(),
+        domain: c.domain().map(|s| s.to_owned()),
+        path: c.path().map(|s| s.to_owned()),
+        secure: c.secure().unwrap_or(false),
+        http_only: c.http_only().unwrap_or(false),
+        expires,
     }
 }
 
-/// Set undecorated shadow. Call from a callback with the WryWindow pointer. Platform: Windows.
+/// Get cookies for a specific URL. Returns a JSON array as a C string
+/// that the caller must free with `wry_string_free()`. Returns null on failure.
 #[no_mangle]
-pub extern "C" fn wry_window_set_shadow(win: *mut WryWindow, shadow: bool) {
-    if win.is_null() {
-        return;
+pub extern "C" fn wry_window_get_cookies_for_url(
+    win: *mut WryWindow,
+    url: *const c_char,
+) -> *mut c_char {
+    if win.is_null() || url.is_null() {
+        return std::ptr::null_mut();
     }
-    let win = unsafe { &mut *win };
-    #[cfg(target_os = "windows")]
-    if let Some(ref w) = win.window {
-        use tao::platform::windows::WindowExtWindows;
-        w.set_undecorated_shadow(shadow);
+    let win = unsafe { &*win };
+    let url_str = unsafe { c_str_to_string(url) };
+    if let Some(ref wv) = win.webview {
+        match wv.cookies_for_url(&url_str) {
+            Ok(cookies) => {
+                let json_vec: Vec<CookieJson> = cookies.iter().map(cookie_to_json).collect();
+                if let Ok(json) = serde_json::to_string(&json_vec) {
+                    return CString::new(json)
+                        .map(|cs| cs.into_raw())
+                        .unwrap_or(std::ptr::null_mut());
+                }
+            }
+            Err(e) => eprintln!("[wry-native] cookies_for_url failed: {}", e),
+        }
     }
+    std::ptr::null_mut()
 }
 
-/// Set always on bottom. Call from a callback with the WryWindow pointer.
+/// Get all cookies. Returns a JSON array as a C string
+/// that the caller must free with `wry_string_free()`. Returns null on failure.
+///
+/// **Windows note:** this function can deadlock when called from a synchronous
+/// event handler. Call from an async context or background thread.
 #[no_mangle]
-pub extern "C" fn wry_window_set_always_on_bottom(win: *mut WryWindow, always_on_bottom: bool) {
+pub extern "C" fn wry_window_get_cookies(win: *mut WryWindow) -> *mut c_char {
     if win.is_null() {
-        return;
+        return std::ptr::null_mut();
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_always_on_bottom(always_on_bottom);
+    let win = unsafe { &*win };
+    if let Some(ref wv) = win.webview {
+        match wv.cookies() {
+            Ok(cookies) => {
+                let json_vec: Vec<CookieJson> = cookies.iter().map(cookie_to_json).collect();
+                if let Ok(json) = serde_json::to_string(&json_vec) {
+                    return CString::new(json)
+                        .map(|cs| cs.into_raw())
+                        .unwrap_or(std::ptr::null_mut());
+                }
+            }
+            Err(e) => eprintln!("[wry-native] get_cookies failed: {}", e),
+        }
     }
+    std::ptr::null_mut()
 }
 
-/// Set maximizable. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_set_maximizable(win: *mut WryWindow, maximizable: bool) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_maximizable(maximizable);
-    }
+/// Host of an absolute URL without port or user info (IPv6 brackets kept).
+fn url_host(url: &str) -> Option<String> {
+    let origin = url_origin(url)?;
+    let (_, host_port) = origin.split_once("://")?;
+    let host = if host_port.starts_with('[') {
+        &host_port[..host_port.find(']')? + 1]
+    } else {
+        host_port.split(':').next()?
+    };
+    (!host.is_empty()).then(|| host.to_owned())
 }
 
-/// Set minimizable. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_set_minimizable(win: *mut WryWindow, minimizable: bool) {
-    if win.is_null() {
-        return;
+/// Build a cookie from the C arguments shared by wry_window_set_cookie and
+/// wry_window_add_initial_cookie. Empty domain/path strings count as not set; expires < 0 = session.
+unsafe fn cookie_from_c(
+    name: *const c_char,
+    value: *const c_char,
+    domain: *const c_char,
+    path: *const c_char,
+    secure: bool,
+    http_only: bool,
+    expires: f64,
+) -> wry::cookie::Cookie<'static> {
+    let name_str = c_str_to_string(name);
+    let value_str = c_str_to_string(value);
+
+    let mut builder = wry::cookie::CookieBuilder::new(name_str, value_str);
+    if !domain.is_null() {
+        let d = c_str_to_string(domain);
+        if !d.is_empty() {
+            builder = builder.domain(d);
+        }
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_minimizable(minimizable);
+    if !path.is_null() {
+        let p = c_str_to_string(path);
+        if !p.is_empty() {
+            builder = builder.path(p);
+        }
+    }
+    builder = builder.secure(secure);
+    builder = builder.http_only(http_only);
+    if expires >= 0.0 {
+        if let Ok(dt) = wry::cookie::time::OffsetDateTime::from_unix_timestamp(expires as i64) {
+            builder = builder.expires(wry::cookie::Expiration::DateTime(dt));
+        }
     }
+    builder.build()
 }
 
-/// Set closable. Call from a callback with the WryWindow pointer.
+/// Set (add or update) a cookie on the webview.
 #[no_mangle]
-pub extern "C" fn wry_window_set_closable(win: *mut WryWindow, closable: bool) {
-    if win.is_null() {
+pub extern "C" fn wry_window_set_cookie(
+    win: *mut WryWindow,
+    name: *const c_char,
+    value: *const c_char,
+    domain: *const c_char,
+    path: *const c_char,
+    secure: bool,
+    http_only: bool,
+    expires: f64,
+) {
+    if win.is_null() || name.is_null() || value.is_null() {
         return;
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_closable(closable);
+    let win = unsafe { &*win };
+    let c = unsafe { cookie_from_c(name, value, domain, path, secure, http_only, expires) };
+    if let Some(ref wv) = win.webview {
+        log_err!(wv.set_cookie(&c), "set_cookie");
     }
 }
 
-/// Set focusable. Call from a callback with the WryWindow pointer.
+/// Queue a cookie to be stored before the window's first navigation, e.g. to restore a saved
+/// session without a flash of the login page. Pre-run only: `window_id` must come from
+/// `wry_window_create` called before `wry_app_run`. With a `data_directory` the cookie is written
+/// to that profile and persists like any other.
+///
+/// `url` supplies the domain when `domain` is null or empty (its host) and the path when `path`
+/// is (`/`). Other arguments are as for `wry_window_set_cookie`.
+///
+/// Returns false if the window is unknown or already created, or no domain could be determined.
 #[no_mangle]
-pub extern "C" fn wry_window_set_focusable(win: *mut WryWindow, focusable: bool) {
-    if win.is_null() {
-        return;
+pub extern "C" fn wry_window_add_initial_cookie(
+    app: *mut WryApp,
+    window_id: usize,
+    url: *const c_char,
+    name: *const c_char,
+    value: *const c_char,
+    domain: *const c_char,
+    path: *const c_char,
+    secure: bool,
+    http_only: bool,
+    expires: f64,
+) -> bool {
+    if app.is_null() || name.is_null() || value.is_null() {
+        return false;
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_focusable(focusable);
+    let app = unsafe { &mut *app };
+    let Some(payload) = app.payloads.get_mut(&window_id) else {
+        return false;
+    };
+    let mut cookie = unsafe { cookie_from_c(name, value, domain, path, secure, http_only, expires) };
+    if cookie.domain().is_none() {
+        let url_str = if url.is_null() { String::new() } else { unsafe { c_str_to_string(url) } };
+        let Some(host) = url_host(&url_str) else {
+            eprintln!("[wry-native] add_initial_cookie: no domain and no host in url {:?}", url_str);
+            return false;
+        };
+        cookie.set_domain(host);
     }
+    if cookie.path().is_none() {
+        cookie.set_path("/");
+    }
+    payload.initial_cookies.push(cookie);
+    true
 }
 
-/// Enable or disable mouse and keyboard input to the window. Used for modal dialogs:
-/// disable the owner window while the dialog is open, then re-enable before closing the dialog.
-/// Windows only; no-op on other platforms.
+/// Delete a cookie from the webview.
 #[no_mangle]
-pub extern "C" fn wry_window_set_enabled(win: *mut WryWindow, enabled: bool) {
-    if win.is_null() {
+pub extern "C" fn wry_window_delete_cookie(
+    win: *mut WryWindow,
+    name: *const c_char,
+    value: *const c_char,
+    domain: *const c_char,
+    path: *const c_char,
+) {
+    if win.is_null() || name.is_null() || value.is_null() {
         return;
     }
     let win = unsafe { &*win };
-    #[cfg(target_os = "windows")]
-    if let Some(ref w) = win.window {
-        use tao::platform::windows::WindowExtWindows;
-        w.set_enable(enabled);
-    }
-}
+    let name_str = unsafe { c_str_to_string(name) };
+    let value_str = unsafe { c_str_to_string(value) };
 
-/// Returns whether the window is enabled (can receive input). Windows only; returns true on other platforms.
-#[no_mangle]
-pub extern "C" fn wry_window_is_enabled(win: *mut WryWindow) -> bool {
-    if win.is_null() {
-        return true;
+    let mut builder = wry::cookie::CookieBuilder::new(name_str, value_str);
+    if !domain.is_null() {
+        let d = unsafe { c_str_to_string(domain) };
+        if !d.is_empty() {
+            builder = builder.domain(d);
+        }
     }
-    let win = unsafe { &*win };
-    #[cfg(target_os = "windows")]
-    if let Some(ref w) = win.window {
-        use tao::platform::windows::WindowExtWindows;
-        return unsafe { windows::Win32::UI::Input::KeyboardAndMouse::IsWindowEnabled(windows::Win32::Foundation::HWND(w.hwnd() as _)) }.as_bool();
+    if !path.is_null() {
+        let p = unsafe { c_str_to_string(path) };
+        if !p.is_empty() {
+            builder = builder.path(p);
+        }
     }
-    true
-}
 
-/// Set webview zoom level. Call from a callback with the WryWindow pointer.
-/// 1.0 = 100%, 2.0 = 200%, etc.
-#[no_mangle]
-pub extern "C" fn wry_window_set_zoom(win: *mut WryWindow, zoom: f64) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &mut *win };
-    let z = if zoom > 0.0 { zoom } else { 1.0 };
+    let c = builder.build();
     if let Some(ref wv) = win.webview {
-        log_err!(wv.zoom(z), "zoom");
+        log_err!(wv.delete_cookie(&c), "delete_cookie");
     }
 }
 
-/// Restore the window from minimized or maximized state.
-/// Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_restore(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_minimized(false);
-        w.set_maximized(false);
-    }
-}
+// ---------------------------------------------------------------------------
+// Unit tests (pure logic)
+// ---------------------------------------------------------------------------
 
-/// Set fullscreen state. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_set_fullscreen(win: *mut WryWindow, fullscreen: bool) {
-    if win.is_null() {
-        return;
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::ffi::{CStr, CString};
+
+    use super::{
+        additional_browser_args, app_region_drag_script, argb32_to_png, badge_text, c_str_to_string,
+        cache_bust_url, capabilities, check_protocol_scheme, compute_snap_position, constrain_aspect,
+        decode_data_url, decode_icon_from_bytes, descendant_pids, dom_ready_script, eval_batch_script,
+        favicon_to_png, forget_closed_windows, frame_eval_script, initial_data_script, inject_base_href,
+        is_devtools_shortcut, is_engine_process, is_svg, live_window, normalize_locale,
+        parse_console_message, parse_hit_test_message, parse_page_ranges, pick_icon_image,
+        protocol_status_result, rasterize_svg, render_badge_icon, same_origin_frame_ids, should_exit,
+        spellcheck_script, stat_parent_pid, status_resident_bytes, supported_image_formats, tiled_edges,
+        to_wide_fixed, url_host, url_origin, user_stylesheet_script, ExitTrigger, LoadState, WindowId,
+        WryWindow, APP_REGION_PARSE_JS, AUTOPLAY_DOCUMENT_USER_ACTIVATION_REQUIRED,
+        AUTOPLAY_NO_USER_GESTURE_REQUIRED, AUTOPLAY_USER_GESTURE_REQUIRED, BADGE_ICON_SIZE, CAP_DEVTOOLS,
+        CAP_INCOGNITO, CAP_TASKBAR, CAP_VIBRANCY, DEFAULT_SVG_ICON_SIZE, PROTOCOL_DUPLICATE_SCHEME,
+        PROTOCOL_INVALID_SCHEME, PROTOCOL_OK, PROTOCOL_WINDOW_UNAVAILABLE,
+    };
+
+    // ---------------------------------------------------------------------------
+    // c_str_to_string
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn c_str_to_string_null_returns_empty() {
+        let out = unsafe { c_str_to_string(std::ptr::null()) };
+        assert_eq!(out, "");
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        if fullscreen {
-            w.set_fullscreen(Some(Fullscreen::Borderless(None)));
-        } else {
-            w.set_fullscreen(None);
-        }
+
+    #[test]
+    fn c_str_to_string_valid_utf8_returns_string() {
+        let c = CString::new("hello").unwrap();
+        let out = unsafe { c_str_to_string(c.as_ptr()) };
+        assert_eq!(out, "hello");
     }
-}
 
-/// Set maximized state. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_set_maximized(win: *mut WryWindow, maximized: bool) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_maximized(maximized);
+    #[test]
+    fn c_str_to_string_invalid_utf8_returns_empty() {
+        let c = unsafe { CStr::from_bytes_with_nul_unchecked(b"\xff\xfe\0") };
+        let out = unsafe { c_str_to_string(c.as_ptr()) };
+        assert_eq!(out, "");
     }
-}
 
-/// Set minimized state. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_set_minimized(win: *mut WryWindow, minimized: bool) {
-    if win.is_null() {
-        return;
+    // ---------------------------------------------------------------------------
+    // decode_icon_from_bytes
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn decode_icon_from_bytes_empty_returns_none() {
+        assert!(decode_icon_from_bytes(&[]).is_none());
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_minimized(minimized);
+
+    #[test]
+    fn decode_icon_from_bytes_invalid_returns_none() {
+        assert!(decode_icon_from_bytes(b"not an image").is_none());
     }
-}
 
-/// Set topmost (always on top) state. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_set_topmost(win: *mut WryWindow, topmost: bool) {
-    if win.is_null() {
-        return;
+    #[test]
+    fn decode_icon_from_bytes_valid_png_returns_some() {
+        // Minimal 1x1 red pixel PNG (68 bytes)
+        const MINIMAL_PNG: &[u8] = &[
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00,
+            0x00, 0x90, 0x77, 0x53, 0xde, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x08,
+            0xd7, 0x63, 0xf8, 0xff, 0xff, 0x3f, 0x00, 0x05, 0xfe, 0x02, 0xfe, 0xdc, 0xcc, 0x59,
+            0xe7, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+        ];
+        let icon = decode_icon_from_bytes(MINIMAL_PNG);
+        assert!(icon.is_some());
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_always_on_top(topmost);
+
+    const TALL_RED_SVG: &[u8] =
+        br##"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" width="10" height="20"><rect width="10" height="20" fill="#f00"/></svg>"##;
+
+    #[test]
+    fn is_svg_detects_documents() {
+        assert!(is_svg(TALL_RED_SVG));
+        assert!(is_svg(b"\xef\xbb\xbf  <svg/>"));
+        assert!(!is_svg(b"<?xml version=\"1.0\"?><html/>"));
+        assert!(!is_svg(&[0x89, b'P', b'N', b'G']));
     }
-}
 
-/// Set visibility state. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_set_visible(win: *mut WryWindow, visible: bool) {
-    if win.is_null() {
-        return;
+    #[test]
+    fn rasterize_svg_fits_and_centers() {
+        let (rgba, w, h) = rasterize_svg(TALL_RED_SVG, 32).expect("svg");
+        assert_eq!((w, h), (32, 32));
+        let px = |x: usize, y: usize| &rgba[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        // 10x20 scales to 16x32, centered horizontally: columns 8..24 are filled.
+        assert_eq!(px(16, 16), &[255, 0, 0, 255]);
+        assert_eq!(px(2, 16)[3], 0);
+        assert_eq!(px(29, 16)[3], 0);
+        assert!(rasterize_svg(TALL_RED_SVG, 0).is_err());
+        assert!(rasterize_svg(b"<svg", 32).is_err());
     }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_visible(visible);
+
+    #[test]
+    fn decode_icon_from_bytes_accepts_svg() {
+        assert!(decode_icon_from_bytes(TALL_RED_SVG).is_some());
+        assert_eq!(super::decode_icon_rgba(TALL_RED_SVG).unwrap().1, DEFAULT_SVG_ICON_SIZE);
     }
-}
 
-/// Enumerate all available monitors. The callback is invoked once per monitor
-/// with its position (x, y), size (width, height) in physical pixels, and
-/// the DPI scale factor. Call from the main thread (from a callback).
-#[no_mangle]
-pub extern "C" fn wry_window_get_all_monitors(
-    win: *mut WryWindow,
-    callback: MonitorCallback,
-    ctx: *mut c_void,
-) {
-    if win.is_null() {
-        return;
+    #[test]
+    fn pick_icon_image_prefers_smallest_not_smaller_than_target() {
+        let sizes = [(16, 16), (256, 256), (32, 32), (48, 48)];
+        assert_eq!(pick_icon_image(&sizes, 16), 0);
+        assert_eq!(pick_icon_image(&sizes, 20), 2);
+        assert_eq!(pick_icon_image(&sizes, 40), 3);
+        assert_eq!(pick_icon_image(&sizes, 300), 1);
+        assert_eq!(pick_icon_image(&sizes, u32::MAX), 1);
     }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        for monitor in w.available_monitors() {
-            let pos = monitor.position();
-            let size = monitor.size();
-            let scale = monitor.scale_factor();
-            callback(
-                pos.x as c_int,
-                pos.y as c_int,
-                size.width as c_int,
-                size.height as c_int,
-                scale,
-                ctx,
-            );
+
+    #[test]
+    fn capabilities_follow_platform() {
+        let caps = capabilities();
+        assert_eq!(caps & CAP_VIBRANCY != 0, cfg!(target_os = "macos"));
+        assert_eq!(caps & CAP_TASKBAR != 0, cfg!(target_os = "windows"));
+        assert_eq!(caps & CAP_DEVTOOLS != 0, cfg!(any(debug_assertions, feature = "devtools")));
+        if cfg!(any(target_os = "windows", target_os = "macos", target_os = "linux")) {
+            assert_ne!(caps & CAP_INCOGNITO, 0);
         }
     }
-}
 
-/// Set resizable state. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_set_resizable(win: *mut WryWindow, resizable: bool) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        w.set_resizable(resizable);
+    #[test]
+    fn to_wide_fixed_truncates_and_terminates() {
+        let buf: [u16; 4] = to_wide_fixed("abcdef");
+        assert_eq!(buf, [b'a' as u16, b'b' as u16, b'c' as u16, 0]);
+        // A surrogate pair that does not fit entirely is dropped.
+        let buf: [u16; 4] = to_wide_fixed("ab\u{1F600}");
+        assert_eq!(buf, [b'a' as u16, b'b' as u16, 0, 0]);
     }
-}
 
-/// Center the window on its current monitor. Call from a callback with the
-/// WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_center(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &mut *win };
-    if let Some(ref w) = win.window {
-        if let Some(monitor) = w.current_monitor() {
-            let screen_size = monitor.size();
-            let window_size = w.outer_size();
-            let x = (screen_size.width as i32 - window_size.width as i32) / 2;
-            let y = (screen_size.height as i32 - window_size.height as i32) / 2;
-            w.set_outer_position(tao::dpi::PhysicalPosition::new(x.max(0), y.max(0)));
+    #[test]
+    fn supported_image_formats_lists_enabled_decoders() {
+        let formats = supported_image_formats();
+        let formats: Vec<&str> = formats.split(',').collect();
+        for f in ["png", "ico", "jpeg", "bmp", "gif", "webp", "svg"] {
+            assert!(formats.contains(&f), "missing {}", f);
         }
+        assert!(!formats.contains(&"tiff"));
     }
-}
 
-// ---------------------------------------------------------------------------
-// WebView runtime methods (post-run, via *mut WryWindow from callbacks)
-// ---------------------------------------------------------------------------
+    // ---------------------------------------------------------------------------
+    // favicon_to_png / argb32_to_png / decode_data_url
+    // ---------------------------------------------------------------------------
 
-/// Print the webview content. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_print(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &*win };
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.print(), "print");
+    #[test]
+    fn favicon_to_png_reencodes_as_png() {
+        let mut bmp = std::io::Cursor::new(Vec::new());
+        image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]))
+            .write_to(&mut bmp, image::ImageFormat::Bmp)
+            .unwrap();
+        let png = favicon_to_png(bmp.get_ref()).expect("png");
+        assert!(png.starts_with(&[0x89, b'P', b'N', b'G']));
     }
-}
 
-/// Reload the current page. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_reload(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &*win };
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.reload(), "reload");
+    #[test]
+    fn favicon_to_png_rejects_empty_and_invalid() {
+        assert!(favicon_to_png(&[]).is_none());
+        assert!(favicon_to_png(b"not an image").is_none());
     }
-}
 
-/// Move focus to the webview. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_focus(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
-    }
-    let win = unsafe { &*win };
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.focus(), "focus");
+    #[test]
+    fn argb32_to_png_unpremultiplies() {
+        // 2x1 with 4 bytes of row padding: half-transparent red, then fully transparent.
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x8080_0000u32.to_ne_bytes());
+        data.extend_from_slice(&0u32.to_ne_bytes());
+        data.extend_from_slice(&[0; 4]);
+        let png = argb32_to_png(&data, 2, 1, 12).expect("png");
+        let img = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(img.get_pixel(0, 0).0, [255, 0, 0, 128]);
+        assert_eq!(img.get_pixel(1, 0).0, [0, 0, 0, 0]);
+        assert!(argb32_to_png(&data, 4, 1, 12).is_none());
     }
-}
 
-/// Move focus away from the webview back to the parent window.
-/// Call from a callback with the WryWindow pointer.
-///
-/// Platform: Android not implemented.
-#[no_mangle]
-pub extern "C" fn wry_window_focus_parent(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
+    #[test]
+    fn decode_data_url_base64() {
+        assert_eq!(decode_data_url("data:image/png;base64,AQID"), Some(vec![1, 2, 3]));
+        assert_eq!(decode_data_url(""), None);
+        assert_eq!(decode_data_url("data:text/plain,hello"), None);
     }
-    let win = unsafe { &*win };
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.focus_parent(), "focus_parent");
+
+    // ---------------------------------------------------------------------------
+    // badge_text / render_badge_icon
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn badge_text_caps_at_two_glyphs() {
+        assert_eq!(badge_text(7), "7");
+        assert_eq!(badge_text(42), "42");
+        assert_eq!(badge_text(150), "9+");
     }
-}
 
-/// Clear all browsing data. Call from a callback with the WryWindow pointer.
-#[no_mangle]
-pub extern "C" fn wry_window_clear_all_browsing_data(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
+    #[test]
+    fn render_badge_icon_draws_disc_and_glyphs() {
+        let size = BADGE_ICON_SIZE as usize;
+        let rgba = render_badge_icon(8);
+        assert_eq!(rgba.len(), size * size * 4);
+        // Corner outside the disc is transparent.
+        assert_eq!(rgba[3], 0);
+        // Glyph "8" has its top-left pixel lit (white) at the glyph origin.
+        let (x0, y0) = ((size - 6) / 2, (size - 10) / 2);
+        assert_eq!(&rgba[(y0 * size + x0) * 4..][..4], &[255, 255, 255, 255]);
     }
-    let win = unsafe { &*win };
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.clear_all_browsing_data(), "clear_all_browsing_data");
+
+    // ---------------------------------------------------------------------------
+    // is_devtools_shortcut
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn is_devtools_shortcut_matches_f12_and_ctrl_shift_keys() {
+        assert!(is_devtools_shortcut(0x7B, false));
+        assert!(is_devtools_shortcut(0x49, true));
+        assert!(is_devtools_shortcut(0x43, true));
+        // Ctrl+C without Shift is copy, not inspect.
+        assert!(!is_devtools_shortcut(0x43, false));
+        assert!(!is_devtools_shortcut(0x74, false)); // F5
     }
-}
 
-/// Set the webview background color at runtime (RGBA, 0-255 each).
-/// Call from a callback with the WryWindow pointer.
-///
-/// Platform: macOS not implemented.
-#[no_mangle]
-pub extern "C" fn wry_window_set_background_color(
-    win: *mut WryWindow,
-    r: u8,
-    g: u8,
-    b: u8,
-    a: u8,
-) {
-    if win.is_null() {
-        return;
+    // ---------------------------------------------------------------------------
+    // url_origin
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn url_origin_keeps_scheme_host_and_port() {
+        assert_eq!(url_origin("https://Example.com:8443/a?b#c").as_deref(), Some("https://example.com:8443"));
+        assert_eq!(url_origin("http://user@host/x").as_deref(), Some("http://host"));
+        assert_eq!(url_origin("app://localhost").as_deref(), Some("app://localhost"));
     }
-    let win = unsafe { &*win };
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.set_background_color((r, g, b, a)), "set_background_color");
+
+    #[test]
+    fn url_origin_none_without_authority() {
+        assert_eq!(url_origin("about:blank"), None);
+        assert_eq!(url_origin("data:text/html,hi"), None);
+        assert_eq!(url_origin("file:///tmp/a.html"), None);
     }
-}
 
-/// Set the window icon from RGBA pixel data at runtime.
-/// Call from a callback or dispatch with the WryWindow pointer.
-/// Pass null / zero length / zero dimensions to clear the icon.
-///
-/// Platform: Windows and Linux only. macOS has no per-window icon.
-#[no_mangle]
-pub extern "C" fn wry_window_set_icon(
-    win: *mut WryWindow,
-    rgba: *const u8,
-    rgba_len: c_int,
-    width: c_int,
-    height: c_int,
-) {
-    if win.is_null() {
-        return;
+    #[test]
+    fn url_host_strips_port_and_user() {
+        assert_eq!(url_host("https://user@Example.com:8443/login").as_deref(), Some("example.com"));
+        assert_eq!(url_host("http://[::1]:8080/").as_deref(), Some("[::1]"));
+        assert_eq!(url_host("about:blank"), None);
     }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        if rgba.is_null() || rgba_len <= 0 || width <= 0 || height <= 0 {
-            w.set_window_icon(None);
-            return;
-        }
-        let data = unsafe { std::slice::from_raw_parts(rgba, rgba_len as usize) }.to_vec();
-        match Icon::from_rgba(data, width as u32, height as u32) {
-            Ok(icon) => w.set_window_icon(Some(icon)),
-            Err(e) => eprintln!("[wry-native] wry_window_set_icon: {}", e),
-        }
+
+    // ---------------------------------------------------------------------------
+    // constrain_aspect
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn constrain_aspect_side_edges_set_height() {
+        // 16:9 client, 10 x 40 frame; dragging the right edge to a 330-wide window.
+        let rect = constrain_aspect(2, (0, 0, 330, 100), (10, 40), 16.0 / 9.0);
+        assert_eq!(rect, (0, 0, 330, 180 + 40));
     }
-}
 
-/// Set the window icon from encoded image file bytes (PNG, ICO, JPEG, BMP, GIF) at runtime.
-/// Call from a callback or dispatch with the WryWindow pointer.
-/// Pass null or zero length to clear the icon.
-///
-/// Platform: Windows and Linux only. macOS has no per-window icon.
-#[no_mangle]
-pub extern "C" fn wry_window_set_icon_from_bytes(
-    win: *mut WryWindow,
-    data: *const u8,
-    data_len: c_int,
-) {
-    if win.is_null() {
-        return;
+    #[test]
+    fn constrain_aspect_top_bottom_edges_set_width() {
+        let rect = constrain_aspect(6, (0, 0, 50, 220), (10, 40), 16.0 / 9.0);
+        assert_eq!(rect, (0, 0, 320 + 10, 220));
     }
-    let win = unsafe { &*win };
-    if let Some(ref w) = win.window {
-        if data.is_null() || data_len <= 0 {
-            w.set_window_icon(None);
-            return;
-        }
-        let bytes = unsafe { std::slice::from_raw_parts(data, data_len as usize) };
-        if let Some(icon) = decode_icon_from_bytes(bytes) {
-            w.set_window_icon(Some(icon));
-        }
+
+    #[test]
+    fn constrain_aspect_top_corners_move_top() {
+        let rect = constrain_aspect(4, (0, 500, 210, 900), (10, 0), 2.0);
+        assert_eq!(rect, (0, 800, 210, 900));
     }
-}
 
-/// Open the web inspector (dev tools).
-/// Call from a callback with the WryWindow pointer.
-///
-/// Platform: Android / iOS not supported.
-#[no_mangle]
-pub extern "C" fn wry_window_open_devtools(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
+    // ---------------------------------------------------------------------------
+    // parse_console_message
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn parse_console_message_reads_fields() {
+        let (level, message, source, line) =
+            parse_console_message(r#"[3,"disk \"low\"","https://app/main.js",42]"#).unwrap();
+        assert_eq!(level, 3);
+        assert_eq!(message, "disk \"low\"");
+        assert_eq!(source, "https://app/main.js");
+        assert_eq!(line, 42);
     }
-    let _win = unsafe { &*win };
-    #[cfg(any(debug_assertions, feature = "devtools"))]
-    if let Some(ref wv) = _win.webview {
-        wv.open_devtools();
+
+    #[test]
+    fn parse_console_message_clamps_and_rejects() {
+        assert_eq!(parse_console_message(r#"[9,"x","",-1]"#), Some((4, "x".into(), String::new(), 0)));
+        assert_eq!(parse_console_message("[0,\"x\"]"), None);
+        assert_eq!(parse_console_message("not json"), None);
     }
-}
 
-/// Close the web inspector (dev tools).
-/// Call from a callback with the WryWindow pointer.
-///
-/// Platform: Windows / Android / iOS not supported.
-#[no_mangle]
-pub extern "C" fn wry_window_close_devtools(win: *mut WryWindow) {
-    if win.is_null() {
-        return;
+    // ---------------------------------------------------------------------------
+    // spellcheck_script
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn spellcheck_script_sets_root_attribute() {
+        let on = spellcheck_script(true);
+        assert!(on.contains("document.documentElement.spellcheck = true;"));
+        assert!(on.contains("DOMContentLoaded"));
+        assert!(spellcheck_script(false).contains("spellcheck = false;"));
     }
-    let _win = unsafe { &*win };
-    #[cfg(any(debug_assertions, feature = "devtools"))]
-    if let Some(ref wv) = _win.webview {
-        wv.close_devtools();
+
+    // ---------------------------------------------------------------------------
+    // parse_page_ranges
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn parse_page_ranges_accepts_lists_and_ranges() {
+        assert_eq!(parse_page_ranges("1-3, 5,7 - 8"), Some(vec![(1, 3), (5, 5), (7, 8)]));
+        assert_eq!(parse_page_ranges(""), Some(vec![]));
+        assert_eq!(parse_page_ranges(" , "), Some(vec![]));
     }
-}
 
-/// Check if the web inspector (dev tools) is open.
-/// Call from a callback with the WryWindow pointer.
-/// Returns false if the webview is not created or devtools feature is disabled.
-///
-/// Platform: Windows / Android / iOS not supported.
-#[no_mangle]
-pub extern "C" fn wry_window_is_devtools_open(win: *mut WryWindow) -> bool {
-    if win.is_null() {
-        return false;
+    #[test]
+    fn parse_page_ranges_rejects_malformed() {
+        assert_eq!(parse_page_ranges("0-2"), None);
+        assert_eq!(parse_page_ranges("4-2"), None);
+        assert_eq!(parse_page_ranges("1-a"), None);
+        assert_eq!(parse_page_ranges("-3"), None);
     }
-    let _win = unsafe { &*win };
-    #[cfg(any(debug_assertions, feature = "devtools"))]
-    if let Some(ref wv) = _win.webview {
-        return wv.is_devtools_open();
+
+    // ---------------------------------------------------------------------------
+    // LoadState
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn load_state_unarmed_waiter_skips_load_in_progress() {
+        let mut state = LoadState::default();
+        state.started();
+        let (armed_tx, armed_rx) = std::sync::mpsc::channel();
+        let (next_tx, next_rx) = std::sync::mpsc::channel();
+        state.waiters.push((armed_tx, true));
+        state.waiters.push((next_tx, false));
+
+        state.finished(true);
+        assert_eq!(armed_rx.try_recv(), Ok(true));
+        assert!(next_rx.try_recv().is_err());
+
+        state.started();
+        state.failed = true;
+        state.finished(!state.failed);
+        assert_eq!(next_rx.try_recv(), Ok(false));
+        assert!(state.waiters.is_empty() && !state.loading);
     }
-    false
-}
 
-/// Get the WebView/WebKit engine version on the current platform.
-/// Returns a pointer to a UTF-8 C string that the caller must free with
-/// `wry_string_free()`. Returns null on failure.
-#[no_mangle]
-pub extern "C" fn wry_webview_version() -> *mut c_char {
-    match webview_version() {
-        Ok(version) => CString::new(version)
-            .map(|cs| cs.into_raw())
-            .unwrap_or(std::ptr::null_mut()),
-        Err(_) => std::ptr::null_mut(),
+    // ---------------------------------------------------------------------------
+    // eval_batch_script
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn eval_batch_script_keeps_order_and_isolates_errors() {
+        let scripts = vec!["update(1) // first".to_owned(), "update(2)".to_owned()];
+        assert_eq!(
+            eval_batch_script(&scripts),
+            "try {\nupdate(1) // first\n} catch (e) { console.error(e); }\n\
+             try {\nupdate(2)\n} catch (e) { console.error(e); }"
+        );
     }
-}
 
-// ---------------------------------------------------------------------------
-// WebView2 native handles (Windows only)
-// ---------------------------------------------------------------------------
-// ---------------------------------------------------------------------------
-// Windows native window handles (HWND, HINSTANCE)
-// ---------------------------------------------------------------------------
+    // ---------------------------------------------------------------------------
+    // initial_data_script
+    // ---------------------------------------------------------------------------
 
-/// Return the window's HWND. Windows only; returns null on other platforms.
-/// The handle is valid until the window is destroyed.
-#[no_mangle]
-pub extern "C" fn wry_window_get_hwnd(win: *mut WryWindow) -> *mut c_void {
-    if win.is_null() {
-        return std::ptr::null_mut();
+    #[test]
+    fn initial_data_script_embeds_normalized_json() {
+        let script = initial_data_script(r#" { "theme": "dark", "api": "https://x/\u2028" } "#).unwrap();
+        assert!(script.starts_with("window.__WRY_DATA = {"));
+        assert!(script.contains(r#""theme":"dark""#));
+        assert!(script.ends_with("};"));
+        assert_eq!(initial_data_script("[1, 2]").unwrap(), "window.__WRY_DATA = [1,2];");
+        assert_eq!(initial_data_script("{ theme: 'dark' }"), None);
+        assert_eq!(initial_data_script("{} alert(1)"), None);
     }
-    let win = unsafe { &*win };
-    #[cfg(target_os = "windows")]
-    if let Some(ref w) = win.window {
-        use tao::platform::windows::WindowExtWindows;
-        return w.hwnd() as *mut c_void;
+
+    // ---------------------------------------------------------------------------
+    // dom_ready_script
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn dom_ready_script_waits_for_dom_content_loaded() {
+        let script = dom_ready_script("document.body.dataset.ready = '1'; // done");
+        assert!(script.contains("document.readyState === 'loading'"));
+        assert!(script.contains("addEventListener('DOMContentLoaded', run, { once: true })"));
+        assert!(script.contains("\ndocument.body.dataset.ready = '1'; // done\n}"));
     }
-    std::ptr::null_mut()
-}
 
-/// Return the window's HINSTANCE (module instance handle). Windows only; returns null on other platforms.
-#[no_mangle]
-pub extern "C" fn wry_window_get_hinstance(win: *mut WryWindow) -> *mut c_void {
-    if win.is_null() {
-        return std::ptr::null_mut();
+    // ---------------------------------------------------------------------------
+    // frame_eval_script
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn frame_eval_script_looks_up_registered_frame() {
+        let script = frame_eval_script("12", "go('x')").unwrap();
+        assert!(script.contains("reg.byId.get(12)"));
+        assert!(script.contains(r#"el.contentWindow.eval("go('x')");"#));
     }
-    let win = unsafe { &*win };
-    #[cfg(target_os = "windows")]
-    if let Some(ref w) = win.window {
-        use tao::platform::windows::WindowExtWindows;
-        let hwnd = w.hwnd();
-        let ptr = unsafe {
-            windows::Win32::UI::WindowsAndMessaging::GetWindowLongPtrW(
-                windows::Win32::Foundation::HWND(hwnd as _),
-                windows::Win32::UI::WindowsAndMessaging::GWLP_HINSTANCE,
-            )
-        };
-        return ptr as *mut c_void;
+
+    #[test]
+    fn frame_eval_script_rejects_malformed_ids() {
+        assert!(frame_eval_script("", "1").is_none());
+        assert!(frame_eval_script("1.0", "1").is_none());
+        assert!(frame_eval_script("a", "1").is_none());
+        assert!(frame_eval_script("-1", "1").is_none());
+        assert!(frame_eval_script("+1", "1").is_none());
+        assert!(frame_eval_script("0", "1").is_none());
     }
-    std::ptr::null_mut()
-}
 
-// ---------------------------------------------------------------------------
-// Return raw COM interface pointers (ICoreWebView2Controller, ICoreWebView2Environment,
-// ICoreWebView2). The caller (e.g. C#) may use them with the WebView2 SDK. We clone the
-// interface so the returned pointer has its own ref; the caller must not release it
-// until they are done (or they may wrap with Marshal.GetObjectForIUnknown which AddRefs).
+    #[test]
+    fn same_origin_frame_ids_skips_cross_origin_frames() {
+        let json = r#"[{"id":"1","name":"","url":"app://x/a","sameOrigin":true},
+                       {"id":"2","name":"ad","url":"https://ads.example/","sameOrigin":false},
+                       {"id":"3","name":"","url":"app://x/b","sameOrigin":true}]"#;
+        assert_eq!(same_origin_frame_ids(json), HashSet::from([1, 3]));
+        assert!(same_origin_frame_ids("not json").is_empty());
+    }
 
-/// Return the WebView2 controller (ICoreWebView2Controller) as a raw pointer.
-/// Windows only; returns null on other platforms or if the webview is not created.
-/// The pointer is valid until the window is destroyed. Caller must not release the COM reference.
-#[no_mangle]
-pub extern "C" fn wry_window_get_webview2_controller(win: *mut WryWindow) -> *mut c_void {
-    if win.is_null() {
-        return std::ptr::null_mut();
+    // ---------------------------------------------------------------------------
+    // additional_browser_args
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn additional_browser_args_keep_wry_defaults() {
+        let args =
+            additional_browser_args(Some(AUTOPLAY_DOCUMENT_USER_ACTIVATION_REQUIRED), false, None, None, true).unwrap();
+        assert!(args.starts_with("--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection "));
+        assert!(args.ends_with(" --autoplay-policy=document-user-activation-required"));
+        let args = additional_browser_args(Some(AUTOPLAY_NO_USER_GESTURE_REQUIRED), false, None, None, true).unwrap();
+        assert!(args.ends_with("=no-user-gesture-required"));
+        assert_eq!(additional_browser_args(Some(3), false, None, None, true), None);
+        assert_eq!(additional_browser_args(None, false, None, None, true), None);
     }
-    let win = unsafe { &*win };
-    #[cfg(target_os = "windows")]
-    if let Some(ref wv) = win.webview {
-        use wry::WebViewExtWindows;
-        let c = wv.controller();
-        let ptr = unsafe { std::mem::transmute_copy::<_, *mut c_void>(&c) };
-        std::mem::forget(c);
-        return ptr;
+
+    #[test]
+    fn additional_browser_args_remote_debugging_port() {
+        let args = additional_browser_args(None, false, Some(9222), None, true).unwrap();
+        assert_eq!(
+            args,
+            "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection --remote-debugging-port=9222"
+        );
+        let args = additional_browser_args(Some(AUTOPLAY_USER_GESTURE_REQUIRED), false, Some(9222), None, true).unwrap();
+        assert!(args.ends_with(" --autoplay-policy=user-gesture-required --remote-debugging-port=9222"));
     }
-    std::ptr::null_mut()
-}
 
-/// Return the WebView2 environment (ICoreWebView2Environment) as a raw pointer.
-/// Windows only; returns null on other platforms or if the webview is not created.
-#[no_mangle]
-pub extern "C" fn wry_window_get_webview2_environment(win: *mut WryWindow) -> *mut c_void {
-    if win.is_null() {
-        return std::ptr::null_mut();
+    #[test]
+    fn additional_browser_args_language() {
+        let args = additional_browser_args(None, false, None, Some("de-DE"), true).unwrap();
+        assert!(args.ends_with("msSmartScreenProtection --lang=de-DE"));
     }
-    let win = unsafe { &*win };
-    #[cfg(target_os = "windows")]
-    if let Some(ref wv) = win.webview {
-        use wry::WebViewExtWindows;
-        let e = wv.environment();
-        let ptr = unsafe { std::mem::transmute_copy::<_, *mut c_void>(&e) };
-        std::mem::forget(e);
-        return ptr;
+
+    #[test]
+    fn additional_browser_args_cache_disabled() {
+        let args = additional_browser_args(None, false, None, None, false).unwrap();
+        assert!(args.ends_with("msSmartScreenProtection --disk-cache-size=1"));
+        let args = additional_browser_args(None, false, None, Some("fr"), false).unwrap();
+        assert!(args.ends_with(" --lang=fr --disk-cache-size=1"));
     }
-    std::ptr::null_mut()
-}
 
-/// Return the WebView2 webview (ICoreWebView2) as a raw pointer.
-/// Windows only; returns null on other platforms or if the webview is not created.
-/// Use this e.g. for CapturePreview (screenshot) or other WebView2 APIs.
-#[no_mangle]
-pub extern "C" fn wry_window_get_webview2_webview(win: *mut WryWindow) -> *mut c_void {
-    if win.is_null() {
-        return std::ptr::null_mut();
+    #[test]
+    fn additional_browser_args_autoplay_without_policy() {
+        let args = additional_browser_args(None, true, Some(9222), None, true).unwrap();
+        assert!(args.ends_with(" --autoplay-policy=no-user-gesture-required --remote-debugging-port=9222"));
+        let args = additional_browser_args(Some(AUTOPLAY_USER_GESTURE_REQUIRED), true, None, None, true).unwrap();
+        assert!(args.ends_with(" --autoplay-policy=user-gesture-required"));
     }
-    let win = unsafe { &*win };
-    #[cfg(target_os = "windows")]
-    if let Some(ref wv) = win.webview {
-        use wry::WebViewExtWindows;
-        let w = wv.webview();
-        let ptr = unsafe { std::mem::transmute_copy::<_, *mut c_void>(&w) };
-        std::mem::forget(w);
-        return ptr;
+
+    // ---------------------------------------------------------------------------
+    // normalize_locale
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn normalize_locale_accepts_bcp47_and_posix() {
+        assert_eq!(normalize_locale("de-DE").as_deref(), Some("de-DE"));
+        assert_eq!(normalize_locale(" pt_BR ").as_deref(), Some("pt-BR"));
+        assert_eq!(normalize_locale("zh-Hant-TW").as_deref(), Some("zh-Hant-TW"));
+        assert_eq!(normalize_locale(""), None);
+        assert_eq!(normalize_locale("en--US"), None);
+        assert_eq!(normalize_locale("en US"), None);
+        assert_eq!(normalize_locale("de-DE --remote-debugging-port=1"), None);
     }
-    std::ptr::null_mut()
-}
 
-// ---------------------------------------------------------------------------
-// Cross-thread dispatch
-// ---------------------------------------------------------------------------
+    // ---------------------------------------------------------------------------
+    // forget_closed_windows
+    // ---------------------------------------------------------------------------
 
-/// Dispatch a callback to run on the event loop (main) thread. This is safe
-/// to call from any thread. The callback will receive the WryWindow pointer
-/// and the context pointer.
-///
-/// `app` is the application handle. `window_id` is the window's numeric ID
-/// returned by `wry_window_create`.
-#[no_mangle]
-pub extern "C" fn wry_window_dispatch(
-    app: *mut WryApp,
-    window_id: usize,
-    callback: DispatchCallback,
-    ctx: *mut c_void,
-) {
-    if app.is_null() {
-        return;
+    #[test]
+    fn closed_window_id_does_not_resolve_to_next_window() {
+        // Create window 1, then close it from a dispatch (wry_window_close drops its tao window).
+        let wid = unsafe { WindowId::dummy() };
+        let mut live_windows = HashMap::new();
+        let mut id_to_window_id = HashMap::new();
+        id_to_window_id.insert(1, wid);
+        live_windows.insert(wid, WryWindow::new(1));
+        assert_eq!(live_window(1, &id_to_window_id, &live_windows).map(|w| w.id), Some(1));
+
+        assert_eq!(forget_closed_windows(&mut live_windows, &mut id_to_window_id), vec![1]);
+        assert!(live_windows.is_empty());
+
+        // Create window 2; the platform hands out the closed window's WindowId again. An owner /
+        // parent reference to window 1 must not resolve to it through a stale mapping.
+        id_to_window_id.insert(2, wid);
+        live_windows.insert(wid, WryWindow::new(2));
+        assert!(live_window(1, &id_to_window_id, &live_windows).is_none());
+        assert_eq!(live_window(2, &id_to_window_id, &live_windows).map(|w| w.id), Some(2));
+        assert!(forget_closed_windows(&mut live_windows, &mut id_to_window_id).is_empty());
+    }
+
+    // ---------------------------------------------------------------------------
+    // check_protocol_scheme
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn check_protocol_scheme_validates_syntax() {
+        assert_eq!(check_protocol_scheme("app", []), PROTOCOL_OK);
+        assert_eq!(check_protocol_scheme("my-app+v1.2", []), PROTOCOL_OK);
+        assert_eq!(check_protocol_scheme("", []), PROTOCOL_INVALID_SCHEME);
+        assert_eq!(check_protocol_scheme("1app", []), PROTOCOL_INVALID_SCHEME);
+        assert_eq!(check_protocol_scheme("my app", []), PROTOCOL_INVALID_SCHEME);
+        assert_eq!(check_protocol_scheme("app://", []), PROTOCOL_INVALID_SCHEME);
+        assert_eq!(check_protocol_scheme("HTTPS", []), PROTOCOL_INVALID_SCHEME);
     }
-    let app = unsafe { &*app };
-    log_err!(app.proxy.send_event(UserEvent::Dispatch {
-        window_id,
-        callback,
-        ctx: ctx as usize,
-    }), "dispatch");
-}
 
-// ---------------------------------------------------------------------------
-// Cookies
-// ---------------------------------------------------------------------------
+    #[test]
+    fn check_protocol_scheme_rejects_duplicates() {
+        assert_eq!(check_protocol_scheme("app", ["assets", "app"]), PROTOCOL_DUPLICATE_SCHEME);
+        assert_eq!(check_protocol_scheme("App", ["app"]), PROTOCOL_DUPLICATE_SCHEME);
+        assert_eq!(check_protocol_scheme("app2", ["app"]), PROTOCOL_OK);
+    }
 
-#[derive(serde::Serialize)]
-struct CookieJson {
-    name: String,
-    value: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    domain: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    path: Option<String>,
-    secure: bool,
-    http_only: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    expires: Option<f64>,
-}
+    #[test]
+    fn protocol_status_result_describes_refusals() {
+        assert_eq!(protocol_status_result("app", PROTOCOL_OK), Ok(()));
+        assert!(protocol_status_result("app", PROTOCOL_DUPLICATE_SCHEME).unwrap_err().contains("already registered"));
+        assert!(protocol_status_result("1app", PROTOCOL_INVALID_SCHEME).unwrap_err().contains("not a valid"));
+        assert!(protocol_status_result("app", PROTOCOL_WINDOW_UNAVAILABLE).unwrap_err().contains("status 3"));
+    }
 
-fn cookie_to_json(c: &wry::cookie::Cookie<'_>) -> CookieJson {
-    let expires = c.expires().and_then(|exp| match exp {
-        wry::cookie::Expiration::Session => None,
-        wry::cookie::Expiration::DateTime(dt) => Some(dt.unix_timestamp() as f64),
-    });
-    CookieJson {
-        name: c.name().to_owned(),
-        value: c.value().to_owned(),
-        domain: c.domain().map(|s| s.to_owned()),
-        path: c.path().map(|s| s.to_owned()),
-        secure: c.secure().unwrap_or(false),
-        http_only: c.http_only().unwrap_or(false),
-        expires,
+    // ---------------------------------------------------------------------------
+    // engine memory usage (/proc parsing)
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn stat_parent_pid_skips_command_name() {
+        assert_eq!(stat_parent_pid("4242 (WebKitWebProcess) S 4200 4200 4200 0 -1"), Some(4200));
+        assert_eq!(stat_parent_pid("17 (a) b (c)) R 1 17 17 0"), Some(1));
+        assert_eq!(stat_parent_pid("garbage"), None);
     }
-}
 
-/// Get cookies for a specific URL. Returns a JSON array as a C string
-/// that the caller must free with `wry_string_free()`. Returns null on failure.
-#[no_mangle]
-pub extern "C" fn wry_window_get_cookies_for_url(
-    win: *mut WryWindow,
-    url: *const c_char,
-) -> *mut c_char {
-    if win.is_null() || url.is_null() {
-        return std::ptr::null_mut();
+    #[test]
+    fn status_resident_bytes_reads_vm_rss() {
+        let status = "Name:\tWebKitWebProcess\nVmPeak:\t  900 kB\nVmRSS:\t  123456 kB\nThreads:\t12\n";
+        assert_eq!(status_resident_bytes(status), Some(123456 * 1024));
+        // Kernel threads have no VmRSS line.
+        assert_eq!(status_resident_bytes("Name:\tkthreadd\n"), None);
     }
-    let win = unsafe { &*win };
-    let url_str = unsafe { c_str_to_string(url) };
-    if let Some(ref wv) = win.webview {
-        match wv.cookies_for_url(&url_str) {
-            Ok(cookies) => {
-                let json_vec: Vec<CookieJson> = cookies.iter().map(cookie_to_json).collect();
-                if let Ok(json) = serde_json::to_string(&json_vec) {
-                    return CString::new(json)
-                        .map(|cs| cs.into_raw())
-                        .unwrap_or(std::ptr::null_mut());
-                }
-            }
-            Err(e) => eprintln!("[wry-native] cookies_for_url failed: {}", e),
-        }
+
+    #[test]
+    fn descendant_pids_walks_grandchildren() {
+        // 100 -> bwrap 200 -> web process 300; 100 -> network 400; 500 is unrelated.
+        let parents = [(200, 100), (300, 200), (400, 100), (500, 1)];
+        let mut pids = descendant_pids(100, &parents);
+        pids.sort();
+        assert_eq!(pids, vec![200, 300, 400]);
+        assert!(descendant_pids(500, &parents).is_empty());
     }
-    std::ptr::null_mut()
-}
 
-/// Get all cookies. Returns a JSON array as a C string
-/// that the caller must free with `wry_string_free()`. Returns null on failure.
-///
-/// **Windows note:** this function can deadlock when called from a synchronous
-/// event handler. Call from an async context or background thread.
-#[no_mangle]
-pub extern "C" fn wry_window_get_cookies(win: *mut WryWindow) -> *mut c_char {
-    if win.is_null() {
-        return std::ptr::null_mut();
+    #[test]
+    fn is_engine_process_matches_truncated_comm() {
+        assert!(is_engine_process("WebKitWebProces\n"));
+        assert!(is_engine_process("WebKitNetworkPr\n"));
+        assert!(is_engine_process("bwrap\n"));
+        assert!(is_engine_process("xdg-dbus-proxy"));
+        assert!(!is_engine_process("bash\n"));
+        assert!(!is_engine_process("WebKit\n"));
     }
-    let win = unsafe { &*win };
-    if let Some(ref wv) = win.webview {
-        match wv.cookies() {
-            Ok(cookies) => {
-                let json_vec: Vec<CookieJson> = cookies.iter().map(cookie_to_json).collect();
-                if let Ok(json) = serde_json::to_string(&json_vec) {
-                    return CString::new(json)
-                        .map(|cs| cs.into_raw())
-                        .unwrap_or(std::ptr::null_mut());
-                }
-            }
-            Err(e) => eprintln!("[wry-native] get_cookies failed: {}", e),
-        }
+
+    // ---------------------------------------------------------------------------
+    // app_region_drag_script
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn app_region_drag_script_wraps_parser_and_listener() {
+        let script = app_region_drag_script();
+        assert!(script.starts_with("(function () {\nvar APP_REGION_DECL"));
+        assert!(script.contains("window.ipc.postMessage('__wry_drag_region:'"));
+        assert!(script.ends_with("}, true);\n})();"));
     }
-    std::ptr::null_mut()
-}
 
-/// Set (add or update) a cookie on the webview.
-#[no_mangle]
-pub extern "C" fn wry_window_set_cookie(
-    win: *mut WryWindow,
-    name: *const c_char,
-    value: *const c_char,
-    domain: *const c_char,
-    path: *const c_char,
-    secure: bool,
-    http_only: bool,
-    expires: f64,
-) {
-    if win.is_null() || name.is_null() || value.is_null() {
-        return;
+    /// Runs the page-side region resolution in Node; skipped where Node is not installed.
+    #[test]
+    fn app_region_parse_resolves_regions() {
+        let checks = r#"
+            const assert = require('assert');
+            assert.strictEqual(appRegionDeclared('color: red; -webkit-app-region: drag'), 'drag');
+            assert.strictEqual(appRegionDeclared('app-region:no-drag;'), 'no-drag');
+            assert.strictEqual(appRegionDeclared('--my-app-region: drag'), '');
+            assert.strictEqual(appRegionDeclared(null), '');
+            assert.deepStrictEqual(
+                appRegionRules('.bar { -webkit-app-region: drag } .btn, a { app-region: no-drag } p { color: red }'),
+                [['.bar', 'drag'], ['.btn, a', 'no-drag']]);
+            assert.deepStrictEqual(appRegionRules('@media (min-width: 1px) { .x { app-region: drag } }'), [['.x', 'drag']]);
+            assert.strictEqual(appRegionOf('no-drag', 'app-region: drag', 'drag', ['drag']), 'no-drag');
+            assert.strictEqual(appRegionOf(null, 'app-region: drag', '', ['no-drag']), 'drag');
+            assert.strictEqual(appRegionOf(null, null, 'drag', ['no-drag']), 'drag');
+            assert.strictEqual(appRegionOf(null, null, 'none', ['drag', 'no-drag']), 'no-drag');
+            assert.strictEqual(appRegionOf(null, null, '', []), '');
+        "#;
+        let script = format!("{}\n{}", APP_REGION_PARSE_JS, checks);
+        let Ok(output) = std::process::Command::new("node").arg("-e").arg(&script).output() else {
+            return;
+        };
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
     }
-    let win = unsafe { &*win };
-    let name_str = unsafe { c_str_to_string(name) };
-    let value_str = unsafe { c_str_to_string(value) };
 
-    let mut builder = wry::cookie::CookieBuilder::new(name_str, value_str);
-    if !domain.is_null() {
-        let d = unsafe { c_str_to_string(domain) };
-        if !d.is_empty() {
-            builder = builder.domain(d);
-        }
+    // ---------------------------------------------------------------------------
+    // user_stylesheet_script
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn user_stylesheet_script_embeds_css_as_string() {
+        let script = user_stylesheet_script("body { background: #000 !important; }\n/* \"x\" */");
+        assert!(script.contains(r#"var css = "body { background: #000 !important; }\n/* \"x\" */";"#));
+        assert!(script.contains("document.createElement('style')"));
     }
-    if !path.is_null() {
-        let p = unsafe { c_str_to_string(path) };
-        if !p.is_empty() {
-            builder = builder.path(p);
-        }
+
+    // ---------------------------------------------------------------------------
+    // cache_bust_url
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn cache_bust_url_appends_param() {
+        assert_eq!(cache_bust_url("https://a.test/p", 5), "https://a.test/p?_wry_reload=5");
+        assert_eq!(cache_bust_url("https://a.test/p?x=1", 5), "https://a.test/p?x=1&_wry_reload=5");
     }
-    builder = builder.secure(secure);
-    builder = builder.http_only(http_only);
-    if expires >= 0.0 {
-        if let Ok(dt) = wry::cookie::time::OffsetDateTime::from_unix_timestamp(expires as i64) {
-            builder = builder.expires(wry::cookie::Expiration::DateTime(dt));
-        }
+
+    #[test]
+    fn cache_bust_url_replaces_param_and_keeps_fragment() {
+        assert_eq!(
+            cache_bust_url("https://a.test/p?_wry_reload=1&x=1#top", 7),
+            "https://a.test/p?x=1&_wry_reload=7#top"
+        );
     }
 
-    let c = builder.build();
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.set_cookie(&c), "set_cookie");
+    // ---------------------------------------------------------------------------
+    // inject_base_href
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn inject_base_href_goes_first_in_head() {
+        assert_eq!(
+            inject_base_href("<html><HEAD lang=\"en\"><title>t</title></head><body></body></html>", "app://assets/"),
+            "<html><HEAD lang=\"en\"><base href=\"app://assets/\"><title>t</title></head><body></body></html>"
+        );
     }
-}
 
-/// Delete a cookie from the webview.
-#[no_mangle]
-pub extern "C" fn wry_window_delete_cookie(
-    win: *mut WryWindow,
-    name: *const c_char,
-    value: *const c_char,
-    domain: *const c_char,
-    path: *const c_char,
-) {
-    if win.is_null() || name.is_null() || value.is_null() {
-        return;
+    #[test]
+    fn inject_base_href_skips_header_and_falls_back() {
+        assert_eq!(
+            inject_base_href("<html><body><header>h</header></body></html>", "https://a.test/"),
+            "<html><base href=\"https://a.test/\"><body><header>h</header></body></html>"
+        );
+        assert_eq!(
+            inject_base_href("<p>x</p>", "https://a.test/?a=1&b=\"2\""),
+            "<base href=\"https://a.test/?a=1&amp;b=&quot;2&quot;\"><p>x</p>"
+        );
     }
-    let win = unsafe { &*win };
-    let name_str = unsafe { c_str_to_string(name) };
-    let value_str = unsafe { c_str_to_string(value) };
 
-    let mut builder = wry::cookie::CookieBuilder::new(name_str, value_str);
-    if !domain.is_null() {
-        let d = unsafe { c_str_to_string(domain) };
-        if !d.is_empty() {
-            builder = builder.domain(d);
+    // ---------------------------------------------------------------------------
+    // should_exit (event loop exit decision)
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn should_exit_never_while_windows_remain() {
+        for trigger in [ExitTrigger::WindowClosed, ExitTrigger::TrayRemoved] {
+            for flag in [true, false] {
+                assert!(!should_exit(trigger, 1, 0, flag));
+                assert!(!should_exit(trigger, 2, 1, flag));
+            }
         }
     }
-    if !path.is_null() {
-        let p = unsafe { c_str_to_string(path) };
-        if !p.is_empty() {
-            builder = builder.path(p);
+
+    #[test]
+    fn should_exit_when_nothing_alive() {
+        for trigger in [ExitTrigger::WindowClosed, ExitTrigger::TrayRemoved] {
+            assert!(should_exit(trigger, 0, 0, true));
+            assert!(should_exit(trigger, 0, 0, false));
         }
     }
 
-    let c = builder.build();
-    if let Some(ref wv) = win.webview {
-        log_err!(wv.delete_cookie(&c), "delete_cookie");
+    #[test]
+    fn should_exit_last_window_closed_with_trays_follows_setting() {
+        assert!(should_exit(ExitTrigger::WindowClosed, 0, 1, true));
+        assert!(!should_exit(ExitTrigger::WindowClosed, 0, 1, false));
     }
-}
-
-// ---------------------------------------------------------------------------
-// Unit tests (pure logic)
-// ---------------------------------------------------------------------------
-
-#[cfg(test)]
-mod tests {
-    use std::ffi::{CStr, CString};
 
-    use super::{c_str_to_string, decode_icon_from_bytes};
+    #[test]
+    fn should_exit_tray_removed_keeps_running_while_other_trays_remain() {
+        // A tray-only app (no windows) with the default setting ends only with its last tray.
+        assert!(!should_exit(ExitTrigger::TrayRemoved, 0, 1, true));
+        assert!(!should_exit(ExitTrigger::TrayRemoved, 0, 2, false));
+    }
 
     // ---------------------------------------------------------------------------
-    // c_str_to_string
+    // tiled_edges
     // ---------------------------------------------------------------------------
 
+    const AREA: (i32, i32, i32, i32) = (0, 0, 1920, 1040);
+
     #[test]
-    fn c_str_to_string_null_returns_empty() {
-        let out = unsafe { c_str_to_string(std::ptr::null()) };
-        assert_eq!(out, "");
+    fn tiled_edges_left_half() {
+        assert_eq!(tiled_edges((-7, 0, 974, 1047), AREA, 16), 1 | 2 | 8);
     }
 
     #[test]
-    fn c_str_to_string_valid_utf8_returns_string() {
-        let c = CString::new("hello").unwrap();
-        let out = unsafe { c_str_to_string(c.as_ptr()) };
-        assert_eq!(out, "hello");
+    fn tiled_edges_right_half() {
+        assert_eq!(tiled_edges((953, 0, 974, 1047), AREA, 16), 2 | 4 | 8);
     }
 
     #[test]
-    fn c_str_to_string_invalid_utf8_returns_empty() {
-        let c = unsafe { CStr::from_bytes_with_nul_unchecked(b"\xff\xfe\0") };
-        let out = unsafe { c_str_to_string(c.as_ptr()) };
-        assert_eq!(out, "");
+    fn tiled_edges_top_left_quarter() {
+        assert_eq!(tiled_edges((-7, 0, 974, 527), AREA, 16), 1 | 2);
+    }
+
+    #[test]
+    fn tiled_edges_floating_or_covering_is_zero() {
+        // Floating in the middle.
+        assert_eq!(tiled_edges((200, 200, 800, 600), AREA, 16), 0);
+        // Parked in a corner but not half-sized.
+        assert_eq!(tiled_edges((0, 0, 800, 600), AREA, 16), 0);
+        // Covering the whole area.
+        assert_eq!(tiled_edges((-7, -7, 1934, 1054), AREA, 16), 0);
     }
 
     // ---------------------------------------------------------------------------
-    // decode_icon_from_bytes
+    // compute_snap_position
     // ---------------------------------------------------------------------------
 
     #[test]
-    fn decode_icon_from_bytes_empty_returns_none() {
-        assert!(decode_icon_from_bytes(&[]).is_none());
+    fn snap_pulls_to_near_monitor_edges() {
+        // Near the left and top edges.
+        assert_eq!(compute_snap_position((12, -9, 800, 600), &[], AREA, 16), (0, 0));
+        // Near the right and bottom edges.
+        assert_eq!(compute_snap_position((1110, 430, 800, 600), &[], AREA, 16), (1120, 440));
     }
 
     #[test]
-    fn decode_icon_from_bytes_invalid_returns_none() {
-        assert!(decode_icon_from_bytes(b"not an image").is_none());
+    fn snap_leaves_far_axes_alone() {
+        assert_eq!(compute_snap_position((200, 5, 800, 600), &[], AREA, 16), (200, 0));
+        assert_eq!(compute_snap_position((200, 200, 800, 600), &[], AREA, 16), (200, 200));
+        assert_eq!(compute_snap_position((17, 200, 800, 600), &[], AREA, 16), (17, 200));
     }
 
     #[test]
-    fn decode_icon_from_bytes_valid_png_returns_some() {
-        // Minimal 1x1 red pixel PNG (68 bytes)
-        const MINIMAL_PNG: &[u8] = &[
-            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48,
-            0x44, 0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00,
-            0x00, 0x90, 0x77, 0x53, 0xde, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x08,
-            0xd7, 0x63, 0xf8, 0xff, 0xff, 0x3f, 0x00, 0x05, 0xfe, 0x02, 0xfe, 0xdc, 0xcc, 0x59,
-            0xe7, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
-        ];
-        let icon = decode_icon_from_bytes(MINIMAL_PNG);
-        assert!(icon.is_some());
+    fn snap_prefers_the_closer_edge() {
+        // Window almost as wide as the area: both edges in range, the left one is closer.
+        assert_eq!(compute_snap_position((4, 200, 1910, 600), &[], AREA, 16), (0, 200));
+        assert_eq!(compute_snap_position((8, 200, 1910, 600), &[], AREA, 16), (10, 200));
+    }
+
+    #[test]
+    fn snap_docks_against_sibling_edges() {
+        let other = [(500, 300, 400, 300)];
+        // Left edge near the sibling's right edge; tops nearly aligned.
+        assert_eq!(compute_snap_position((910, 305, 300, 200), &other, AREA, 16), (900, 300));
+        // Right edge near the sibling's left edge.
+        assert_eq!(compute_snap_position((190, 400, 300, 200), &other, AREA, 16), (200, 400));
+        // Top edge near the sibling's bottom edge; left edges nearly aligned.
+        assert_eq!(compute_snap_position((492, 612, 300, 200), &other, AREA, 16), (500, 600));
+    }
+
+    #[test]
+    fn snap_ignores_siblings_not_alongside() {
+        // Horizontally next to the sibling but far below it: no docking on x.
+        let other = [(500, 100, 400, 100)];
+        assert_eq!(compute_snap_position((910, 600, 300, 200), &other, AREA, 16), (910, 600));
+    }
+
+    // ---------------------------------------------------------------------------
+    // parse_hit_test_message
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn parse_hit_test_message_valid() {
+        assert_eq!(parse_hit_test_message("12,-3,2"), Some((12, -3, 2)));
+    }
+
+    #[test]
+    fn parse_hit_test_message_rejects_malformed() {
+        assert_eq!(parse_hit_test_message(""), None);
+        assert_eq!(parse_hit_test_message("1,2"), None);
+        assert_eq!(parse_hit_test_message("1,2,3,4"), None);
+        assert_eq!(parse_hit_test_message("a,2,1"), None);
     }
 }
 
@@ -0,0 +1,101 @@
+//! Monitor configuration changes (a display was connected, disconnected or rearranged).
+//! Platform listeners post `UserEvent::MonitorsChanged` into the event loop, which calls the
+//! `wry_app_on_monitors_changed` callback. Only the signal is delivered; callers re-enumerate.
+//!
+//! - Windows: `WM_DISPLAYCHANGE` on the shared hidden listener window (`listen_system_messages`).
+//!   Also fires when a monitor's resolution or color depth changes.
+//! - macOS: `NSApplicationDidChangeScreenParametersNotification`.
+//! - Linux: GDK `monitor-added` / `monitor-removed` on the default display.
+
+use tao::event_loop::EventLoopProxy;
+
+use crate::UserEvent;
+
+/// Start the platform listener. Called once from wry_app_run (event loop thread) when a
+/// monitors-changed handler is set.
+pub(crate) fn start_monitor_listener(proxy: &EventLoopProxy<UserEvent>) {
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    platform::start(proxy.clone());
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let _ = proxy;
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::sync::OnceLock;
+
+    use tao::event_loop::EventLoopProxy;
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::WM_DISPLAYCHANGE;
+
+    use super::*;
+
+    /// Proxy used by the message hook; set once, by the first start().
+    static PROXY: OnceLock<EventLoopProxy<UserEvent>> = OnceLock::new();
+
+    pub(super) fn start(proxy: EventLoopProxy<UserEvent>) {
+        if PROXY.set(proxy).is_err() {
+            return; // already listening
+        }
+        crate::listen_system_messages(on_message, |_| {});
+    }
+
+    unsafe fn on_message(msg: u32, _wparam: WPARAM, _lparam: LPARAM) {
+        if msg == WM_DISPLAYCHANGE {
+            if let Some(proxy) = PROXY.get() {
+                let _ = proxy.send_event(UserEvent::MonitorsChanged);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ptr::NonNull;
+
+    use block2::RcBlock;
+    use objc2_app_kit::NSApplicationDidChangeScreenParametersNotification;
+    use objc2_foundation::{NSNotification, NSNotificationCenter};
+    use tao::event_loop::EventLoopProxy;
+
+    use super::*;
+
+    pub(super) fn start(proxy: EventLoopProxy<UserEvent>) {
+        let block = RcBlock::new(move |_: NonNull<NSNotification>| {
+            let _ = proxy.send_event(UserEvent::MonitorsChanged);
+        });
+        let center = NSNotificationCenter::defaultCenter();
+        let token = unsafe {
+            center.addObserverForName_object_queue_usingBlock(
+                Some(NSApplicationDidChangeScreenParametersNotification),
+                None,
+                None,
+                &block,
+            )
+        };
+        // The observer lives for the rest of the process.
+        std::mem::forget(token);
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use gtk::gdk;
+    use tao::event_loop::EventLoopProxy;
+
+    use super::*;
+
+    pub(super) fn start(proxy: EventLoopProxy<UserEvent>) {
+        let Some(display) = gdk::Display::default() else {
+            eprintln!("[wry-native] monitors changed: no default GDK display");
+            return;
+        };
+        let added = proxy.clone();
+        display.connect_monitor_added(move |_, _| {
+            let _ = added.send_event(UserEvent::MonitorsChanged);
+        });
+        display.connect_monitor_removed(move |_, _| {
+            let _ = proxy.send_event(UserEvent::MonitorsChanged);
+        });
+    }
+}
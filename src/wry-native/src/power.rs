@@ -0,0 +1,431 @@
+//! System power notifications: sleep / wake, battery saver and session lock.
+//! Platform listeners post `UserEvent::PowerEvent` into the event loop, which hands the kind to
+//! the `wry_app_on_power_event` callback.
+//!
+//! - Windows: the shared hidden listener window (`listen_system_messages`; `WM_POWERBROADCAST`,
+//!   `WM_WTSSESSION_CHANGE`).
+//! - macOS: NSWorkspace sleep / wake, the distributed screen lock notifications and
+//!   `NSProcessInfoPowerStateDidChangeNotification`.
+//! - Linux: logind over the system D-Bus (`PrepareForSleep`, session `Lock` / `Unlock`).
+//!   Low power is not reported.
+//!
+//! Also the keep-awake assertions behind `wry_app_set_keep_awake` (see [`KeepAwake`]).
+
+use std::ffi::c_int;
+
+use tao::event_loop::EventLoopProxy;
+
+use crate::UserEvent;
+
+/// The system is about to sleep.
+pub(crate) const POWER_SUSPEND: c_int = 0;
+/// The system woke up.
+pub(crate) const POWER_RESUME: c_int = 1;
+/// Battery saver / low power mode was turned on.
+pub(crate) const POWER_LOW_POWER: c_int = 2;
+/// The user session was locked.
+pub(crate) const POWER_SCREEN_LOCK: c_int = 3;
+/// The user session was unlocked.
+pub(crate) const POWER_SCREEN_UNLOCK: c_int = 4;
+
+fn post(proxy: &EventLoopProxy<UserEvent>, kind: c_int) {
+    let _ = proxy.send_event(UserEvent::PowerEvent { kind });
+}
+
+/// Start the platform listener. Called once from wry_app_run when a power event handler is set.
+pub(crate) fn start_power_listener(proxy: &EventLoopProxy<UserEvent>) {
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    platform::start(proxy.clone());
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let _ = proxy;
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+pub(crate) use platform::KeepAwake;
+
+/// Keeps the display and / or the system from idle sleep while alive; dropping it releases the
+/// platform assertion. Not supported on this platform.
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub(crate) struct KeepAwake;
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+impl KeepAwake {
+    pub(crate) fn acquire(_display: bool, _system: bool) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+const KEEP_AWAKE_REASON: &str = "Requested by the application";
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::sync::OnceLock;
+
+    use tao::event_loop::EventLoopProxy;
+    use windows::core::{HSTRING, PWSTR};
+    use windows::Win32::Foundation::{CloseHandle, HANDLE, HWND, LPARAM, WPARAM};
+    use windows::Win32::System::Power::{
+        PowerCreateRequest, PowerRequestDisplayRequired, PowerRequestSystemRequired, PowerSetRequest,
+        RegisterPowerSettingNotification, POWERBROADCAST_SETTING,
+    };
+    use windows::Win32::System::RemoteDesktop::{
+        WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION, WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+    };
+    use windows::Win32::System::SystemServices::{GUID_POWER_SAVING_STATUS, POWER_REQUEST_CONTEXT_VERSION};
+    use windows::Win32::System::Threading::{POWER_REQUEST_CONTEXT_SIMPLE_STRING, REASON_CONTEXT, REASON_CONTEXT_0};
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    use super::*;
+
+    /// Proxy used by the message hook; set once, by the first start().
+    static PROXY: OnceLock<EventLoopProxy<UserEvent>> = OnceLock::new();
+
+    pub(super) fn start(proxy: EventLoopProxy<UserEvent>) {
+        if PROXY.set(proxy).is_err() {
+            return; // already listening
+        }
+        crate::listen_system_messages(on_message, register_notifications);
+    }
+
+    /// Session and battery saver messages are only sent to windows that ask for them.
+    fn register_notifications(hwnd: HWND) {
+        unsafe {
+            if let Err(e) = WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) {
+                eprintln!("[wry-native] power events: session notifications unavailable: {}", e);
+            }
+            if let Err(e) =
+                RegisterPowerSettingNotification(HANDLE(hwnd.0), &GUID_POWER_SAVING_STATUS, DEVICE_NOTIFY_WINDOW_HANDLE)
+            {
+                eprintln!("[wry-native] power events: battery saver notifications unavailable: {}", e);
+            }
+        }
+    }
+
+    unsafe fn on_message(msg: u32, wparam: WPARAM, lparam: LPARAM) {
+        let kind = match msg {
+            WM_POWERBROADCAST => match wparam.0 as u32 {
+                PBT_APMSUSPEND => Some(POWER_SUSPEND),
+                // Sent on every wake; PBT_APMRESUMESUSPEND follows only when a user is present.
+                PBT_APMRESUMEAUTOMATIC => Some(POWER_RESUME),
+                PBT_POWERSETTINGCHANGE => {
+                    let setting = &*(lparam.0 as *const POWERBROADCAST_SETTING);
+                    let saver_on = setting.PowerSetting == GUID_POWER_SAVING_STATUS
+                        && setting.DataLength >= 1
+                        && setting.Data[0] != 0;
+                    saver_on.then_some(POWER_LOW_POWER)
+                }
+                _ => None,
+            },
+            WM_WTSSESSION_CHANGE => match wparam.0 as u32 {
+                WTS_SESSION_LOCK => Some(POWER_SCREEN_LOCK),
+                WTS_SESSION_UNLOCK => Some(POWER_SCREEN_UNLOCK),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let (Some(kind), Some(proxy)) = (kind, PROXY.get()) {
+            post(proxy, kind);
+        }
+    }
+
+    /// Power request object (PowerCreateRequest). Unlike SetThreadExecutionState it is not tied
+    /// to the calling thread, so it can be taken and released from any thread.
+    pub(crate) struct KeepAwake(HANDLE);
+
+    // Safety: a power request handle may be used and closed from any thread.
+    unsafe impl Send for KeepAwake {}
+
+    impl KeepAwake {
+        pub(crate) fn acquire(display: bool, system: bool) -> Option<Self> {
+            unsafe {
+                let reason = HSTRING::from(KEEP_AWAKE_REASON);
+                let context = REASON_CONTEXT {
+                    Version: POWER_REQUEST_CONTEXT_VERSION,
+                    Flags: POWER_REQUEST_CONTEXT_SIMPLE_STRING,
+                    Reason: REASON_CONTEXT_0 { SimpleReasonString: PWSTR(reason.as_ptr() as *mut u16) },
+                };
+                let handle = match PowerCreateRequest(&context) {
+                    Ok(handle) => handle,
+                    Err(e) => {
+                        eprintln!("[wry-native] keep awake: PowerCreateRequest failed: {}", e);
+                        return None;
+                    }
+                };
+                // Keep the guard first so an error below still closes the handle.
+                let guard = KeepAwake(handle);
+                let kinds = [(display, PowerRequestDisplayRequired), (system, PowerRequestSystemRequired)];
+                for (_, kind) in kinds.into_iter().filter(|(wanted, _)| *wanted) {
+                    if let Err(e) = PowerSetRequest(handle, kind) {
+                        eprintln!("[wry-native] keep awake: PowerSetRequest failed: {}", e);
+                        return None;
+                    }
+                }
+                Some(guard)
+            }
+        }
+    }
+
+    impl Drop for KeepAwake {
+        fn drop(&mut self) {
+            // Closing the request handle clears any requests set on it.
+            unsafe {
+                let _ = CloseHandle(self.0);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ptr::NonNull;
+
+    use std::ffi::c_void;
+
+    use block2::RcBlock;
+    use objc2::rc::Retained;
+    use objc2_app_kit::{NSWorkspace, NSWorkspaceDidWakeNotification, NSWorkspaceWillSleepNotification};
+    use objc2_foundation::{
+        NSDistributedNotificationCenter, NSNotification, NSNotificationCenter, NSProcessInfo,
+        NSProcessInfoPowerStateDidChangeNotification, NSString,
+    };
+    use tao::event_loop::EventLoopProxy;
+
+    use super::*;
+
+    pub(super) fn start(proxy: EventLoopProxy<UserEvent>) {
+        let workspace = NSWorkspace::sharedWorkspace().notificationCenter();
+        unsafe {
+            observe(&workspace, NSWorkspaceWillSleepNotification, &proxy, Some(POWER_SUSPEND));
+            observe(&workspace, NSWorkspaceDidWakeNotification, &proxy, Some(POWER_RESUME));
+        }
+        let distributed = NSDistributedNotificationCenter::defaultCenter();
+        let locked = NSString::from_str("com.apple.screenIsLocked");
+        let unlocked = NSString::from_str("com.apple.screenIsUnlocked");
+        observe(&distributed, &locked, &proxy, Some(POWER_SCREEN_LOCK));
+        observe(&distributed, &unlocked, &proxy, Some(POWER_SCREEN_UNLOCK));
+        // Posted for both directions; only report turning low power mode on.
+        let default_center = NSNotificationCenter::defaultCenter();
+        unsafe { observe(&default_center, NSProcessInfoPowerStateDidChangeNotification, &proxy, None) };
+    }
+
+    /// Post `kind` whenever `name` is posted on `center`. None = low power mode check.
+    /// Observers live for the rest of the process, so the tokens are leaked.
+    fn observe(center: &NSNotificationCenter, name: &NSString, proxy: &EventLoopProxy<UserEvent>, kind: Option<c_int>) {
+        let proxy = proxy.clone();
+        let block = RcBlock::new(move |_: NonNull<NSNotification>| match kind {
+            Some(kind) => post(&proxy, kind),
+            None => {
+                if NSProcessInfo::processInfo().isLowPowerModeEnabled() {
+                    post(&proxy, POWER_LOW_POWER);
+                }
+            }
+        });
+        let token = unsafe { center.addObserverForName_object_queue_usingBlock(Some(name), None, None, &block) };
+        std::mem::forget(token);
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPMAssertionCreateWithName(assertion_type: *const c_void, level: u32, name: *const c_void, id: *mut u32) -> i32;
+        fn IOPMAssertionRelease(id: u32) -> i32;
+    }
+
+    const ASSERTION_LEVEL_ON: u32 = 255;
+
+    /// IOKit power assertion IDs, released on drop.
+    pub(crate) struct KeepAwake(Vec<u32>);
+
+    impl KeepAwake {
+        pub(crate) fn acquire(display: bool, system: bool) -> Option<Self> {
+            let mut kinds = Vec::new();
+            if display {
+                kinds.push("PreventUserIdleDisplaySleep");
+            }
+            if system {
+                kinds.push("PreventUserIdleSystemSleep");
+            }
+            let name = NSString::from_str(KEEP_AWAKE_REASON);
+            let mut guard = KeepAwake(Vec::new());
+            for kind in kinds {
+                // NSString is toll-free bridged with CFStringRef.
+                let kind = NSString::from_str(kind);
+                let mut id = 0u32;
+                let status = unsafe {
+                    IOPMAssertionCreateWithName(
+                        Retained::as_ptr(&kind) as *const c_void,
+                        ASSERTION_LEVEL_ON,
+                        Retained::as_ptr(&name) as *const c_void,
+                        &mut id,
+                    )
+                };
+                if status != 0 {
+                    eprintln!("[wry-native] keep awake: IOPMAssertionCreateWithName failed: {:#x}", status);
+                    return None; // drops (releases) the assertions taken so far
+                }
+                guard.0.push(id);
+            }
+            Some(guard)
+        }
+    }
+
+    impl Drop for KeepAwake {
+        fn drop(&mut self) {
+            for &id in &self.0 {
+                unsafe { IOPMAssertionRelease(id) };
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::os::fd::{FromRawFd, OwnedFd};
+
+    use tao::event_loop::EventLoopProxy;
+    use webkit2gtk::gio::{self, prelude::*};
+    use webkit2gtk::glib::{self, ToVariant, VariantTy};
+
+    use super::*;
+
+    const LOGIN1: &str = "org.freedesktop.login1";
+
+    pub(super) fn start(proxy: EventLoopProxy<UserEvent>) {
+        let connection = match gio::bus_get_sync(gio::BusType::System, None::<&gio::Cancellable>) {
+            Ok(connection) => connection,
+            Err(e) => {
+                eprintln!("[wry-native] power events: system bus unavailable: {}", e);
+                return;
+            }
+        };
+
+        let sleep_proxy = proxy.clone();
+        connection.signal_subscribe(
+            Some(LOGIN1),
+            Some("org.freedesktop.login1.Manager"),
+            Some("PrepareForSleep"),
+            Some("/org/freedesktop/login1"),
+            None,
+            gio::DBusSignalFlags::NONE,
+            move |_, _, _, _, _, params| {
+                if let Some((sleeping,)) = params.get::<(bool,)>() {
+                    post(&sleep_proxy, if sleeping { POWER_SUSPEND } else { POWER_RESUME });
+                }
+            },
+        );
+
+        // Lock / Unlock are emitted on the session object; listen to ours (all if it is unknown).
+        let session = connection
+            .call_sync(
+                Some(LOGIN1),
+                "/org/freedesktop/login1",
+                "org.freedesktop.login1.Manager",
+                "GetSessionByPID",
+                Some(&(std::process::id(),).to_variant()),
+                Some(VariantTy::new("(o)").expect("valid variant type")),
+                gio::DBusCallFlags::NONE,
+                -1,
+                None::<&gio::Cancellable>,
+            )
+            .ok()
+            .and_then(|reply| reply.child_value(0).str().map(str::to_owned));
+        for (member, kind) in [("Lock", POWER_SCREEN_LOCK), ("Unlock", POWER_SCREEN_UNLOCK)] {
+            let proxy = proxy.clone();
+            connection.signal_subscribe(
+                Some(LOGIN1),
+                Some("org.freedesktop.login1.Session"),
+                Some(member),
+                session.as_deref(),
+                None,
+                gio::DBusSignalFlags::NONE,
+                move |_, _, _, _, _, _| post(&proxy, kind),
+            );
+        }
+
+        // Subscriptions live for the rest of the process.
+        std::mem::forget(connection);
+    }
+    /// Held inhibitors: a screensaver cookie on the session bus (display) and a logind
+    /// inhibitor file descriptor (system). Dropping uninhibits / closes them.
+    pub(crate) struct KeepAwake {
+        screensaver: Option<(gio::DBusConnection, u32)>,
+        _sleep_inhibitor: Option<OwnedFd>,
+    }
+
+    impl KeepAwake {
+        pub(crate) fn acquire(display: bool, system: bool) -> Option<Self> {
+            let mut guard = KeepAwake { screensaver: None, _sleep_inhibitor: None };
+            if display {
+                guard.screensaver = Some(inhibit_screensaver()?);
+            }
+            if system {
+                guard._sleep_inhibitor = Some(inhibit_sleep()?);
+            }
+            Some(guard)
+        }
+    }
+
+    impl Drop for KeepAwake {
+        fn drop(&mut self) {
+            if let Some((connection, cookie)) = self.screensaver.take() {
+                let result = connection.call_sync(
+                    Some("org.freedesktop.ScreenSaver"),
+                    "/org/freedesktop/ScreenSaver",
+                    "org.freedesktop.ScreenSaver",
+                    "UnInhibit",
+                    Some(&(cookie,).to_variant()),
+                    None,
+                    gio::DBusCallFlags::NONE,
+                    -1,
+                    None::<&gio::Cancellable>,
+                );
+                if let Err(e) = result {
+                    eprintln!("[wry-native] keep awake: ScreenSaver.UnInhibit failed: {}", e);
+                }
+            }
+        }
+    }
+
+    /// org.freedesktop.ScreenSaver.Inhibit on the session bus; blocks screen blanking and
+    /// idle actions until UnInhibit (or until this process drops off the bus).
+    fn inhibit_screensaver() -> Option<(gio::DBusConnection, u32)> {
+        let result = gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>).and_then(|connection| {
+            let reply = connection.call_sync(
+                Some("org.freedesktop.ScreenSaver"),
+                "/org/freedesktop/ScreenSaver",
+                "org.freedesktop.ScreenSaver",
+                "Inhibit",
+                Some(&("wry-native", KEEP_AWAKE_REASON).to_variant()),
+                Some(VariantTy::new("(u)").expect("valid variant type")),
+                gio::DBusCallFlags::NONE,
+                -1,
+                None::<&gio::Cancellable>,
+            )?;
+            Ok((connection, reply.child_value(0).get::<u32>().unwrap_or_default()))
+        });
+        result.map_err(|e| eprintln!("[wry-native] keep awake: ScreenSaver.Inhibit failed: {}", e)).ok()
+    }
+
+    /// logind `Inhibit("idle:sleep", ..., "block")`; the inhibitor lasts as long as the returned fd is open.
+    fn inhibit_sleep() -> Option<OwnedFd> {
+        let result = gio::bus_get_sync(gio::BusType::System, None::<&gio::Cancellable>).and_then(|connection| {
+            let (reply, fds) = connection.call_with_unix_fd_list_sync(
+                Some(LOGIN1),
+                "/org/freedesktop/login1",
+                "org.freedesktop.login1.Manager",
+                "Inhibit",
+                Some(&("idle:sleep", "wry-native", KEEP_AWAKE_REASON, "block").to_variant()),
+                Some(VariantTy::new("(h)").expect("valid variant type")),
+                gio::DBusCallFlags::NONE,
+                -1,
+                None::<&gio::UnixFDList>,
+                None::<&gio::Cancellable>,
+            )?;
+            let index = reply.child_value(0).get::<glib::variant::Handle>().map_or(0, |h| h.0);
+            let fds = fds.ok_or_else(|| glib::Error::new(gio::IOErrorEnum::Failed, "no inhibitor fd returned"))?;
+            // get() returns a duplicate we own.
+            Ok(unsafe { OwnedFd::from_raw_fd(fds.get(index)?) })
+        });
+        result.map_err(|e| eprintln!("[wry-native] keep awake: logind Inhibit failed: {}", e)).ok()
+    }
+}
@@ -0,0 +1,162 @@
+//! Shell integration API: hand URLs and paths off to the operating system.
+//! Uses the `open` crate to launch the user's default handler, and the platform
+//! file manager for "show in folder".
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::ffi::c_char;
+use std::path::Path;
+use std::process::Command;
+
+use crate::c_str_to_string;
+
+/// URL schemes that `wry_open_external` will hand to the OS.
+const EXTERNAL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Returns true if `url` starts with one of the allowed external schemes (case-insensitive).
+fn is_external_url(url: &str) -> bool {
+    match url.split_once(':') {
+        Some((scheme, rest)) => {
+            !rest.is_empty() && EXTERNAL_SCHEMES.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+        }
+        None => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// External URLs
+// ---------------------------------------------------------------------------
+
+/// Open a URL in the user's default browser (or mail client for `mailto:`).
+/// Only http, https and mailto URLs are accepted; anything else is rejected so
+/// page-supplied strings cannot launch arbitrary programs or local files.
+/// Returns true if the OS handler was launched. Safe to call from any thread.
+#[no_mangle]
+pub extern "C" fn wry_open_external(url: *const c_char) -> bool {
+    let url = unsafe { c_str_to_string(url) };
+    if !is_external_url(&url) {
+        eprintln!("[wry-native] wry_open_external: rejected url '{}'", url);
+        return false;
+    }
+    match open::that_detached(&url) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("[wry-native] wry_open_external failed: {}", e);
+            false
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Local paths
+// ---------------------------------------------------------------------------
+
+/// Open a file or folder with its default application.
+/// Returns true if the OS handler was launched, false if the path does not exist or launching failed.
+#[no_mangle]
+pub extern "C" fn wry_open_path(path: *const c_char) -> bool {
+    let path = unsafe { c_str_to_string(path) };
+    if path.is_empty() || !Path::new(&path).exists() {
+        return false;
+    }
+    match open::that_detached(&path) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("[wry-native] wry_open_path failed: {}", e);
+            false
+        }
+    }
+}
+
+/// Show a file or folder in the OS file manager ("Show in folder").
+/// - Windows: `explorer.exe /select,<path>` (opens the folder with the item selected)
+/// - macOS: `open -R <path>` (reveals the item in Finder)
+/// - Linux: opens the containing folder with `xdg-open`; there is no portable way to select the item
+/// Returns true if the file manager was launched, false if the path does not exist or launching failed.
+#[no_mangle]
+pub extern "C" fn wry_reveal_path(path: *const c_char) -> bool {
+    let path = unsafe { c_str_to_string(path) };
+    let p = Path::new(&path);
+    if path.is_empty() || !p.exists() {
+        return false;
+    }
+
+    #[cfg(target_os = "windows")]
+    let result = {
+        use std::os::windows::process::CommandExt;
+        // explorer.exe parses its own command line; the comma must be glued to the quoted path.
+        Command::new("explorer.exe")
+            .raw_arg(format!("/select,\"{}\"", p.display()))
+            .spawn()
+    };
+    #[cfg(target_os = "macos")]
+    let result = Command::new("open").arg("-R").arg(p).spawn();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = Command::new("xdg-open").arg(containing_dir(p)).spawn();
+
+    match result {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("[wry-native] wry_reveal_path failed: {}", e);
+            false
+        }
+    }
+}
+
+/// Folder to open for `p` in wry_reveal_path on Linux: `p` itself if it is a directory, otherwise
+/// its parent, or "." for a bare relative file name (whose parent is empty).
+#[cfg_attr(any(target_os = "windows", target_os = "macos"), allow(dead_code))]
+fn containing_dir(p: &Path) -> &Path {
+    if p.is_dir() {
+        return p;
+    }
+    match p.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Unit tests (pure logic)
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{containing_dir, is_external_url};
+
+    #[test]
+    fn is_external_url_accepts_http_https_mailto() {
+        assert!(is_external_url("http://example.com"));
+        assert!(is_external_url("HTTPS://example.com/path"));
+        assert!(is_external_url("mailto:someone@example.com"));
+    }
+
+    #[test]
+    fn is_external_url_rejects_other_schemes() {
+        assert!(!is_external_url(""));
+        assert!(!is_external_url("example.com"));
+        assert!(!is_external_url("file:///etc/passwd"));
+        assert!(!is_external_url("javascript:alert(1)"));
+        assert!(!is_external_url("https:"));
+    }
+
+    #[test]
+    fn open_and_reveal_reject_missing_paths() {
+        let missing = std::ffi::CString::new("/definitely/not/a/real/path/wry-native").unwrap();
+        assert!(!super::wry_open_path(missing.as_ptr()));
+        assert!(!super::wry_reveal_path(missing.as_ptr()));
+        assert!(!super::wry_open_path(std::ptr::null()));
+        assert!(!super::wry_reveal_path(std::ptr::null()));
+    }
+
+    #[test]
+    fn containing_dir_of_relative_file_name_is_current_dir() {
+        assert_eq!(containing_dir(Path::new("not-a-real-file-wry.txt")), Path::new("."));
+        assert_eq!(containing_dir(Path::new("docs/not-a-real-file-wry.txt")), Path::new("docs"));
+        assert_eq!(containing_dir(Path::new("/not/a/real/file-wry.txt")), Path::new("/not/a/real"));
+        let dir = std::env::temp_dir();
+        assert_eq!(containing_dir(&dir), dir.as_path());
+    }
+}
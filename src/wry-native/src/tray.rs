@@ -4,6 +4,7 @@
 
 use std::collections::HashMap;
 use std::ffi::{c_char, c_int, c_void, CString};
+use std::time::{Duration, Instant};
 
 use tray_icon::TrayIconBuilder;
 use tray_icon::menu as tray_menu;
@@ -274,6 +275,32 @@ impl TrayCreatePayload {
     }
 }
 
+/// Decode encoded image file bytes (PNG, ICO, JPEG, BMP, GIF, WebP, SVG) into a tray icon.
+fn decode_tray_icon(bytes: &[u8]) -> Option<tray_icon::Icon> {
+    let (rgba, w, h) = match crate::decode_icon_rgba(bytes) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            eprintln!("[wry-native] tray icon {}", e);
+            return None;
+        }
+    };
+    match tray_icon::Icon::from_rgba(rgba, w, h) {
+        Ok(icon) => Some(icon),
+        Err(e) => {
+            eprintln!("[wry-native] tray icon from_rgba failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Frame-sequence animation driven by the event loop (see `WryTray::tick_animation`).
+struct TrayAnimation {
+    frames: Vec<tray_icon::Icon>,
+    interval: Duration,
+    index: usize,
+    next_due: Instant,
+}
+
 // ---------------------------------------------------------------------------
 // WryTray -- per-tray-icon state
 // ---------------------------------------------------------------------------
@@ -290,6 +317,11 @@ pub struct WryTray {
     pub(crate) menu_item_ids: Vec<String>,
     pub(crate) live_items: HashMap<String, LiveMenuItem>,
     live_menu: Option<tray_menu::Menu>,
+    animation: Option<TrayAnimation>,
+    /// Window shown and focused on a left click (0 = none). Handled by the event loop.
+    pub(crate) activate_window_id: usize,
+    /// Icon rect (x, y, width, height; physical pixels) from the most recent tray event.
+    last_rect: Option<(f64, f64, u32, u32)>,
 }
 
 impl WryTray {
@@ -302,6 +334,9 @@ impl WryTray {
             menu_item_ids: Vec::new(),
             live_items: HashMap::new(),
             live_menu: None,
+            animation: None,
+            activate_window_id: 0,
+            last_rect: None,
         }
     }
 
@@ -345,9 +380,17 @@ impl WryTray {
     }
 
     /// Dispatch a tray icon event (click, double-click, etc.) to the C callback.
-    pub(crate) fn handle_tray_event(&self, event: &tray_icon::TrayIconEvent) {
-        let Some((cb, ctx)) = self.event_handler else { return; };
+    pub(crate) fn handle_tray_event(&mut self, event: &tray_icon::TrayIconEvent) {
         use tray_icon::TrayIconEvent as TIE;
+        if let TIE::Click { rect, .. }
+        | TIE::DoubleClick { rect, .. }
+        | TIE::Enter { rect, .. }
+        | TIE::Move { rect, .. }
+        | TIE::Leave { rect, .. } = event
+        {
+            self.last_rect = Some((rect.position.x, rect.position.y, rect.size.width, rect.size.height));
+        }
+        let Some((cb, ctx)) = self.event_handler else { return; };
         let (evt, x, y, ix, iy, iw, ih, btn, st) = match event {
             TIE::Click { position, rect, button, button_state, .. } => {
                 let b = match button {
@@ -403,6 +446,22 @@ impl WryTray {
         callback(tray_ptr, ctx as *mut c_void);
     }
 
+    /// Advance the icon animation if its next frame is due. Returns the instant the next
+    /// frame is due, or None if no animation is running. Called by the event loop after
+    /// every event; the loop sleeps with `ControlFlow::WaitUntil` on the earliest deadline.
+    pub(crate) fn tick_animation(&mut self, now: Instant) -> Option<Instant> {
+        let anim = self.animation.as_mut()?;
+        if now >= anim.next_due {
+            anim.index = (anim.index + 1) % anim.frames.len();
+            if let Some(ref t) = self.tray {
+                log_err!(t.set_icon(Some(anim.frames[anim.index].clone())), "tray animation set_icon");
+            }
+            // Skip missed frames rather than bursting to catch up.
+            anim.next_due = (anim.next_due + anim.interval).max(now);
+        }
+        Some(anim.next_due)
+    }
+
     /// Append a newly created item to either the top-level menu (parent_id empty)
     /// or a submenu identified by parent_id.
     fn append_to_parent(&self, parent_id: &str, item: &dyn tray_menu::IsMenuItem) -> bool {
@@ -498,6 +557,81 @@ pub(crate) fn setup_tray_event_handlers(
     }));
 }
 
+// ---------------------------------------------------------------------------
+// Dock menu (macOS)
+// ---------------------------------------------------------------------------
+
+/// macOS: the menu shown below the app's windows when the dock icon is right-clicked.
+/// Lives on the main thread; read by the `applicationDockMenu:` method added to tao's app delegate.
+#[cfg(target_os = "macos")]
+struct DockMenu {
+    menu: tray_menu::Menu,
+    live_items: HashMap<String, LiveMenuItem>,
+    menu_event_handler: Option<(TrayMenuEventCallback, usize)>,
+}
+
+#[cfg(target_os = "macos")]
+thread_local! {
+    static DOCK_MENU: std::cell::RefCell<Option<DockMenu>> = const { std::cell::RefCell::new(None) };
+}
+
+/// macOS: add `applicationDockMenu:` to the class of tao's NSApplication delegate (once; later
+/// calls find the method already present). tao does not implement it, so nothing is overridden.
+#[cfg(target_os = "macos")]
+fn install_dock_menu_method() -> bool {
+    use objc2::runtime::{AnyObject, Imp, Sel};
+    use objc2::{ffi, sel, MainThreadMarker};
+    use objc2_app_kit::NSApplication;
+
+    extern "C-unwind" fn application_dock_menu(_this: *mut AnyObject, _cmd: Sel, _app: *mut AnyObject) -> *mut AnyObject {
+        use tray_menu::ContextMenu;
+        DOCK_MENU.with(|dock| {
+            dock.borrow()
+                .as_ref()
+                .map_or(std::ptr::null_mut(), |d| d.menu.ns_menu() as *mut AnyObject)
+        })
+    }
+
+    let Some(mtm) = MainThreadMarker::new() else {
+        eprintln!("[wry-native] dock menu: must be set on the main thread");
+        return false;
+    };
+    let Some(delegate) = NSApplication::sharedApplication(mtm).delegate() else {
+        eprintln!("[wry-native] dock menu: application has no delegate");
+        return false;
+    };
+    unsafe {
+        let class = (*(objc2::rc::Retained::as_ptr(&delegate) as *const AnyObject)).class();
+        let imp: Imp = std::mem::transmute(
+            application_dock_menu as extern "C-unwind" fn(*mut AnyObject, Sel, *mut AnyObject) -> *mut AnyObject,
+        );
+        // Returns NO when the method exists already (a previous call added it).
+        let _ = ffi::class_addMethod(class as *const _ as *mut _, sel!(applicationDockMenu:), imp, c"@@:@".as_ptr());
+    }
+    true
+}
+
+/// Route a menu event to the dock menu's callback if the item belongs to it.
+/// Called by the event loop for menu events no tray claimed.
+pub(crate) fn handle_dock_menu_event(menu_id: &str) {
+    #[cfg(target_os = "macos")]
+    DOCK_MENU.with(|dock| {
+        let handler = dock
+            .borrow()
+            .as_ref()
+            .filter(|d| d.live_items.contains_key(menu_id))
+            .and_then(|d| d.menu_event_handler);
+        // Borrow released before the callback, which may replace the dock menu.
+        if let Some((cb, ctx)) = handler {
+            if let Ok(c_id) = CString::new(menu_id) {
+                cb(c_id.as_ptr(), ctx as *mut c_void);
+            }
+        }
+    });
+    #[cfg(not(target_os = "macos"))]
+    let _ = menu_id;
+}
+
 // ===========================================================================
 // EXPORTED C API
 // ===========================================================================
@@ -664,23 +798,102 @@ pub extern "C" fn wry_tray_set_icon_from_bytes(
             return;
         }
         let bytes = unsafe { std::slice::from_raw_parts(data, data_len as usize) };
-        match image::load_from_memory(bytes) {
-            Ok(img) => {
-                use image::GenericImageView;
-                let rgba = img.to_rgba8();
-                let (w, h) = img.dimensions();
-                match tray_icon::Icon::from_rgba(rgba.into_raw(), w, h) {
-                    Ok(icon) => { log_err!(t.set_icon(Some(icon)), "tray set_icon"); }
-                    Err(e) => { eprintln!("[wry-native] tray icon from_rgba failed: {}", e); }
-                }
-            }
-            Err(e) => {
-                eprintln!("[wry-native] tray icon image decode failed: {}", e);
-            }
+        if let Some(icon) = decode_tray_icon(bytes) {
+            log_err!(t.set_icon(Some(icon)), "tray set_icon");
         }
     }
 }
 
+/// Animate the tray icon by cycling through a sequence of encoded image frames
+/// (PNG, ICO, JPEG, BMP, GIF, WebP, SVG) every `interval_ms` milliseconds. Timing runs inside the
+/// event loop, so no host timer or dispatch is needed. Replaces any running animation.
+///
+/// - `frames`: array of `frame_count` pointers to image bytes
+/// - `frame_lens`: array of `frame_count` byte lengths
+/// - `interval_ms`: frame duration; values below 16 are clamped to 16
+///
+/// Frames that fail to decode are skipped. The frame data is copied; the caller may free it on return.
+#[no_mangle]
+pub extern "C" fn wry_tray_set_animation(
+    tray: *mut WryTray,
+    frames: *const *const u8,
+    frame_count: c_int,
+    frame_lens: *const c_int,
+    interval_ms: c_int,
+) {
+    if tray.is_null() || frames.is_null() || frame_lens.is_null() || frame_count <= 0 { return; }
+    let tray = unsafe { &mut *tray };
+    let ptrs = unsafe { std::slice::from_raw_parts(frames, frame_count as usize) };
+    let lens = unsafe { std::slice::from_raw_parts(frame_lens, frame_count as usize) };
+    let icons: Vec<tray_icon::Icon> = ptrs
+        .iter()
+        .zip(lens)
+        .filter(|(p, len)| !p.is_null() && **len > 0)
+        .filter_map(|(p, len)| decode_tray_icon(unsafe { std::slice::from_raw_parts(*p, *len as usize) }))
+        .collect();
+    if icons.is_empty() {
+        eprintln!("[wry-native] wry_tray_set_animation: no frames decoded");
+        return;
+    }
+    if let Some(ref t) = tray.tray {
+        log_err!(t.set_icon(Some(icons[0].clone())), "tray animation set_icon");
+    }
+    let interval = Duration::from_millis(interval_ms.max(16) as u64);
+    tray.animation = Some(TrayAnimation {
+        frames: icons,
+        interval,
+        index: 0,
+        next_due: Instant::now() + interval,
+    });
+}
+
+/// Stop a running tray icon animation. The icon keeps the frame it was showing.
+#[no_mangle]
+pub extern "C" fn wry_tray_stop_animation(tray: *mut WryTray) {
+    if tray.is_null() { return; }
+    let tray = unsafe { &mut *tray };
+    tray.animation = None;
+}
+
+/// Make a left click on the tray icon show, restore and focus a window.
+/// `window_id` is the ID returned by `wry_window_create`; pass 0 to turn this off.
+/// The tray event callback still fires for the click.
+#[no_mangle]
+pub extern "C" fn wry_tray_set_activate_window(tray: *mut WryTray, window_id: usize) {
+    if tray.is_null() { return; }
+    let tray = unsafe { &mut *tray };
+    tray.activate_window_id = window_id;
+}
+
+/// Get the tray icon's screen rectangle in physical pixels.
+/// Queries the platform first; if that is unavailable, falls back to the rect reported by the
+/// most recent tray event (click, enter, move, ...). Returns false if neither is known.
+///
+/// Platform: Windows and macOS can query the rect at any time. Linux cannot, so the rect is
+/// only known after the pointer has interacted with the icon, and may be stale if the panel moved.
+#[no_mangle]
+pub extern "C" fn wry_tray_get_rect(
+    tray: *mut WryTray,
+    x: *mut f64,
+    y: *mut f64,
+    width: *mut u32,
+    height: *mut u32,
+) -> bool {
+    if tray.is_null() { return false; }
+    let tray = unsafe { &*tray };
+    let queried = tray.tray.as_ref().and_then(|t| t.rect()).map(|r| {
+        (r.position.x, r.position.y, r.size.width, r.size.height)
+    });
+    let Some((rx, ry, rw, rh)) = queried.or(tray.last_rect) else { return false; };
+    unsafe {
+        if !x.is_null() { *x = rx; }
+        if !y.is_null() { *y = ry; }
+        if !width.is_null() { *width = rw; }
+        if !height.is_null() { *height = rh; }
+    }
+    true
+}
+
 /// Set the tray tooltip.
 #[no_mangle]
 pub extern "C" fn wry_tray_set_tooltip(tray: *mut WryTray, tooltip: *const c_char) {
@@ -1004,7 +1217,8 @@ pub extern "C" fn wry_tray_menu_item_remove_at(
 // ---------------------------------------------------------------------------
 
 /// Dispatch a callback to run on the event loop (main) thread for a tray.
-/// Safe to call from any thread.
+/// Safe to call from any thread. Dispatches that arrive before the tray is
+/// materialized are queued and run right after it is created.
 #[no_mangle]
 pub extern "C" fn wry_tray_dispatch(
     app: *mut WryApp,
@@ -1031,3 +1245,49 @@ pub extern "C" fn wry_tray_remove(app: *mut WryApp, tray_id: usize) {
         tray_id,
     }), "tray remove");
 }
+
+// ---------------------------------------------------------------------------
+// Dock menu (macOS)
+// ---------------------------------------------------------------------------
+
+/// Set the menu shown when the app's dock icon is right-clicked (macOS `applicationDockMenu:`),
+/// built with the tray menu functions (`wry_tray_menu_new`, `wry_tray_menu_add_item`, ...).
+/// Takes ownership of the menu; pass null to remove it. Clicks call
+/// `callback(item_id, ctx)` on the event loop thread, like a tray menu's `menu_event_callback`;
+/// use item IDs distinct from those of tray menus. Call on the main thread after `wry_app_new`,
+/// before or during `wry_app_run`. Returns false if the menu could not be installed.
+///
+/// Platform: macOS only. Elsewhere the menu is freed and false is returned.
+#[no_mangle]
+pub extern "C" fn wry_app_set_dock_menu(
+    app: *mut WryApp,
+    menu: *mut WryTrayMenu,
+    callback: Option<TrayMenuEventCallback>,
+    ctx: *mut c_void,
+) -> bool {
+    let menu = (!menu.is_null()).then(|| unsafe { Box::from_raw(menu) });
+    if app.is_null() { return false; }
+    #[cfg(target_os = "macos")]
+    {
+        let Some(menu) = menu else {
+            DOCK_MENU.with(|dock| dock.borrow_mut().take());
+            return true;
+        };
+        if !install_dock_menu_method() {
+            return false;
+        }
+        let (muda_menu, live_items) = menu.build();
+        let dock_menu = DockMenu {
+            menu: muda_menu,
+            live_items,
+            menu_event_handler: callback.map(|cb| (cb, ctx as usize)),
+        };
+        DOCK_MENU.with(|dock| *dock.borrow_mut() = Some(dock_menu));
+        true
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (menu, callback, ctx);
+        false
+    }
+}